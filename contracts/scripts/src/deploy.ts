@@ -10,10 +10,12 @@ tezos.setProvider({
 
 (async () => {
   try {
-    console.log("> Deploying smart rollup bridge...");
+    // The bridge of the tokens, or `tez_bridge` for the bridge of the tez
+    const bridge = process.argv[3] ?? "bridge";
+    console.log(`> Deploying smart rollup ${bridge}...`);
 
     // Load code
-    const code = fs.readFileSync(`${__dirname}/../../michelson/bridge.tz`).toString();
+    const code = fs.readFileSync(`${__dirname}/../../michelson/${bridge}.tz`).toString();
 
     // Deploy
     const op = await tezos.contract.originate({
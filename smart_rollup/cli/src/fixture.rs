@@ -0,0 +1,348 @@
+use std::collections::BTreeMap;
+
+use kernel::constants::{L1_BRIDGE_CONTRACT_ADDRESS, L1_TEZ_BRIDGE_CONTRACT_ADDRESS};
+use kernel::core::message::Message;
+use kernel::core::public_key_hash::PublicKeyHash;
+use kernel::core::token::{TicketContent, Token};
+use kernel_client::{to_external_hex, transfer, withdraw, Signer};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tezos_crypto_rs::hash::{ContractKt1Hash, ContractTz1Hash};
+
+use crate::utils::parse_token;
+
+const DEFAULT_TIMESTAMP: &str = "2023-05-19T05:45:50.473Z";
+
+/// Readable description of the inbox, see debug/scenario.json
+#[derive(Deserialize)]
+pub struct Scenario {
+    /// Secret keys (edsk...) by account name
+    accounts: BTreeMap<String, String>,
+    /// Timestamp of the signed messages, fixed so that the fixture is reproducible
+    timestamp: Option<String>,
+    /// Steps of each level of the inbox
+    levels: Vec<Vec<Step>>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum Step {
+    /// Ticket sent by the bridge, on behalf of an L1 account
+    /// The unit tickets of tez are sent by the tez bridge, other tickets by their ticketer
+    Deposit {
+        to: String,
+        token: String,
+        amount: u128,
+    },
+    Transfer {
+        from: String,
+        to: String,
+        token: String,
+        amount: u128,
+        /// Overrides the next nonce of the account, for invalid messages
+        nonce: Option<u64>,
+        /// Lane of the nonce, defaults to the strict sequence of the account
+        #[serde(default)]
+        lane: u32,
+    },
+    Withdraw {
+        from: String,
+        to: String,
+        token: String,
+        amount: u128,
+        nonce: Option<u64>,
+        #[serde(default)]
+        lane: u32,
+    },
+}
+
+struct Generator {
+    signers: BTreeMap<String, Signer>,
+    /// Last valid nonce of each lane of each account
+    nonces: BTreeMap<(String, u32), u64>,
+    timestamp: String,
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// Michelson contents of a ticket of the payload of a deposit
+fn ticket_contents(content: &TicketContent) -> String {
+    match content {
+        TicketContent::Unit => "Unit".to_string(),
+        TicketContent::Bytes(bytes) => format!("0x{}", hex(bytes)),
+        TicketContent::String(string) => format!("{:?}", string),
+        TicketContent::Nat(nat) => nat.to_string(),
+        TicketContent::Pair(nat, None) => format!("Pair {} None", nat),
+        TicketContent::Pair(nat, Some(bytes)) => format!("Pair {} (Some 0x{})", nat, hex(bytes)),
+    }
+}
+
+/// Binary representation of an implicit account, as expected by the debugger
+fn implicit_address_bytes(address: &str) -> Result<String, String> {
+    let tz1 = ContractTz1Hash::from_base58_check(address)
+        .map_err(|_| format!("Invalid tz1 address: {}", address))?;
+    Ok(format!("0000{}", hex(tz1.as_ref())))
+}
+
+/// Binary representation of an originated contract, as expected by the debugger
+fn contract_address_bytes(address: &str) -> Result<String, String> {
+    let kt1 = ContractKt1Hash::from_base58_check(address)
+        .map_err(|_| format!("Invalid KT1 address: {}", address))?;
+    Ok(format!("01{}00", hex(kt1.as_ref())))
+}
+
+impl Generator {
+    /// Resolves an account name to its tz1, a tz1 is returned as is
+    fn address(&self, account: &str) -> Result<String, String> {
+        match self.signers.get(account) {
+            Some(signer) => Ok(signer.public_key_hash().to_string()),
+            None => Ok(PublicKeyHash::from_b58(account)
+                .map_err(|_| format!("Unknown account: {}", account))?
+                .to_string()),
+        }
+    }
+
+    fn signer(&self, account: &str) -> Result<&Signer, String> {
+        self.signers
+            .get(account)
+            .ok_or_else(|| format!("Unknown account: {}", account))
+    }
+
+    /// Returns the nonce to use, only a valid nonce is recorded for the lane of the account
+    fn nonce(&mut self, account: &str, lane: u32, nonce: Option<u64>) -> u64 {
+        let key = (account.to_string(), lane);
+        let next = self.nonces.get(&key).copied().unwrap_or_default() + 1;
+        match nonce {
+            Some(nonce) if nonce != next => nonce,
+            _ => {
+                self.nonces.insert(key, next);
+                next
+            }
+        }
+    }
+
+    fn step(&mut self, step: &Step) -> Result<Value, String> {
+        match step {
+            Step::Deposit { to, token, amount } => {
+                let token = parse_token(token)?;
+                let (bridge, contents) = match &token {
+                    Token::Bytes(bytes) => {
+                        (L1_BRIDGE_CONTRACT_ADDRESS, format!("0x{}", hex(bytes)))
+                    }
+                    Token::Tez => (L1_TEZ_BRIDGE_CONTRACT_ADDRESS, "Unit".to_string()),
+                    Token::Ticket { ticketer, content } => {
+                        (ticketer.as_str(), ticket_contents(content))
+                    }
+                };
+                let payload = format!(
+                    "Pair (Pair 0x{} (Pair {} {})) 0x{}",
+                    contract_address_bytes(bridge)?,
+                    contents,
+                    amount,
+                    implicit_address_bytes(&self.address(to)?)?
+                );
+                Ok(json!({ "payload": payload, "sender": bridge }))
+            }
+            Step::Transfer {
+                from,
+                to,
+                token,
+                amount,
+                nonce,
+                lane,
+            } => {
+                let destination = PublicKeyHash::from_b58(&self.address(to)?)
+                    .map_err(|_| format!("Invalid destination: {}", to))?;
+                let nonce = self.nonce(from, *lane, *nonce);
+                let mut inner = transfer(nonce, parse_token(token)?, destination, *amount);
+                inner.lane = *lane;
+                let message = self
+                    .signer(from)?
+                    .sign(inner, &self.timestamp)
+                    .map_err(|err| err.to_string())?;
+                let external =
+                    to_external_hex(&Message::Transfer(message)).map_err(|err| err.to_string())?;
+                Ok(json!({ "external": external }))
+            }
+            Step::Withdraw {
+                from,
+                to,
+                token,
+                amount,
+                nonce,
+                lane,
+            } => {
+                let destination = PublicKeyHash::from_b58(&self.address(to)?)
+                    .map_err(|_| format!("Invalid destination: {}", to))?;
+                let nonce = self.nonce(from, *lane, *nonce);
+                let mut inner = withdraw(nonce, parse_token(token)?, destination, *amount);
+                inner.lane = *lane;
+                let message = self
+                    .signer(from)?
+                    .sign(inner, &self.timestamp)
+                    .map_err(|err| err.to_string())?;
+                let external =
+                    to_external_hex(&Message::Withdraw(message)).map_err(|err| err.to_string())?;
+                Ok(json!({ "external": external }))
+            }
+        }
+    }
+}
+
+/// Generates the inputs of the WASM debugger, one array of messages per level
+pub fn generate(scenario: &Scenario) -> Result<Value, String> {
+    let mut signers = BTreeMap::default();
+    for (name, secret_key) in scenario.accounts.iter() {
+        let signer =
+            Signer::from_b58(secret_key).map_err(|_| format!("Invalid secret key for {}", name))?;
+        signers.insert(name.clone(), signer);
+    }
+
+    let mut generator = Generator {
+        signers,
+        nonces: BTreeMap::default(),
+        timestamp: scenario
+            .timestamp
+            .clone()
+            .unwrap_or_else(|| DEFAULT_TIMESTAMP.to_string()),
+    };
+
+    let levels = scenario
+        .levels
+        .iter()
+        .map(|level| {
+            level
+                .iter()
+                .map(|step| generator.step(step))
+                .collect::<Result<Vec<Value>, String>>()
+        })
+        .collect::<Result<Vec<Vec<Value>>, String>>()?;
+    Ok(json!(levels))
+}
+
+#[cfg(test)]
+mod tests {
+    use kernel::constants::L1_BRIDGE_CONTRACT_ADDRESS;
+    use serde_json::Value;
+    use tezos_crypto_rs::hash::{ContractKt1Hash, ContractTz1Hash};
+    use tezos_data_encoding::enc::{BinResult, BinWriter};
+    use tezos_smart_rollup::host::Runtime;
+    use tezos_smart_rollup::michelson::{
+        ticket::BytesTicket, MichelsonBytes, MichelsonContract, MichelsonPair,
+    };
+    use tezos_smart_rollup::storage::path::OwnedPath;
+    use tezos_smart_rollup::types::{Contract, PublicKeyHash as L1PublicKeyHash};
+    use tezos_smart_rollup_mock::{MockHost, TransferMetadata};
+
+    use super::{generate, Scenario, Signer};
+
+    const SCENARIO: &str = include_str!("../../kernel/debug/scenario.json");
+    const INPUTS: &str = include_str!("../../kernel/debug/inputs.json");
+
+    /// Raw bytes of an external message, written as is in the inbox
+    struct External(Vec<u8>);
+
+    impl BinWriter for External {
+        fn bin_write(&self, output: &mut Vec<u8>) -> BinResult {
+            output.extend_from_slice(&self.0);
+            Ok(())
+        }
+    }
+
+    fn unhex(hex: &str) -> Vec<u8> {
+        let hex = hex.trim_start_matches("0x");
+        (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    /// Adds the deposit of the debugger to the inbox, from the bytes of its payload
+    /// Only the bytes tickets of the bridge are used by the scenario
+    fn add_deposit(host: &mut MockHost, input: &Value) {
+        let payload = input["payload"].as_str().unwrap();
+        let words: Vec<&str> = payload
+            .split_whitespace()
+            .map(|word| word.trim_matches(|c| c == '(' || c == ')'))
+            .collect();
+        let (ticketer, contents, amount, destination) = (words[2], words[4], words[5], words[6]);
+
+        // 0x01 ${kt1} 0x00 for the ticketer, 0x0000 ${tz1} for the destination
+        let ticketer = ContractKt1Hash::try_from(&unhex(ticketer)[1..21])
+            .unwrap()
+            .to_base58_check();
+        assert_eq!(input["sender"].as_str().unwrap(), ticketer);
+        let destination = ContractTz1Hash::try_from(&unhex(destination)[2..])
+            .unwrap()
+            .to_base58_check();
+
+        let ticket = BytesTicket::new(
+            Contract::from_b58check(&ticketer).unwrap(),
+            MichelsonBytes(unhex(contents)),
+            amount.parse::<u128>().unwrap(),
+        )
+        .unwrap();
+        let payload = MichelsonPair(
+            ticket,
+            MichelsonContract(Contract::from_b58check(&destination).unwrap()),
+        );
+        let metadata = TransferMetadata::new(
+            ContractKt1Hash::from_base58_check(&ticketer).unwrap(),
+            L1PublicKeyHash::from_b58check(&destination).unwrap(),
+        );
+        host.add_transfer(payload, &metadata);
+    }
+
+    fn balance(host: &MockHost, account: &str) -> u128 {
+        let path = OwnedPath::try_from(format!("/ledger/{}/1234", account).into_bytes()).unwrap();
+        let bytes = host.store_read(&path, 0, 16).unwrap();
+        u128::from_be_bytes(bytes.try_into().unwrap())
+    }
+
+    #[test]
+    fn test_inputs_are_generated_from_the_scenario() {
+        let scenario: Scenario = serde_json::from_str(SCENARIO).unwrap();
+        let inputs: Value = serde_json::from_str(INPUTS).unwrap();
+        assert_eq!(inputs, generate(&scenario).unwrap());
+    }
+
+    #[test]
+    fn test_inputs_are_accepted_by_the_kernel() {
+        let scenario: Scenario = serde_json::from_str(SCENARIO).unwrap();
+        let inputs = generate(&scenario).unwrap();
+        let mut host = MockHost::default();
+
+        let mut outbox = 0;
+        for level in inputs.as_array().unwrap() {
+            for input in level.as_array().unwrap() {
+                match input["external"].as_str() {
+                    Some(external) => host.add_external(External(unhex(external))),
+                    None => {
+                        assert_eq!(L1_BRIDGE_CONTRACT_ADDRESS, input["sender"]);
+                        add_deposit(&mut host, input);
+                    }
+                }
+            }
+            let level = host.run_level(kernel::entry);
+            outbox += host.outbox_at(level).len();
+        }
+
+        // The transfer with a wrong nonce is rejected, the other messages are applied
+        let address = |name: &str| {
+            let signer = Signer::from_b58(&scenario.accounts[name]).unwrap();
+            signer.public_key_hash().to_string()
+        };
+        assert_eq!(
+            10_000_000_000 - 10_000_000 - 5000 + 1000,
+            balance(&host, &address("alice"))
+        );
+        assert_eq!(5000 - 1000 - 2000, balance(&host, &address("bob")));
+        assert_eq!(
+            10_000_000,
+            balance(&host, "tz1Pe4aBjsW9ZGWaFXa47megxFD1LGGFAW3C")
+        );
+        assert_eq!(1, outbox);
+    }
+}
@@ -0,0 +1,809 @@
+use std::fs;
+use std::path::PathBuf;
+
+use clap::{Args, Parser, Subcommand};
+use kernel::core::htlc::htlc_id;
+use kernel::core::message::{
+    AuthorizeSessionKeyContent, Content, DepositAction, Inner, Message, MultisigContent,
+    MultisigMessage, OpenStreamContent, OperatorUpdate, OrderContent, PoolContent, SignedMessage,
+    SponsoredMessage, SwapContent, SwapLeg, SwapMessage, VestContent,
+};
+use kernel::core::multisig::Multisig;
+use kernel::core::public_key::PublicKey;
+use kernel::core::stream::stream_id;
+use kernel::core::token::Token;
+use kernel::core::vesting::vesting_id;
+use kernel_client::{
+    approve, authorize_session_key, cancel_stream, claim, claim_vested, deposit_action, lock,
+    multisig, open_stream, order, pool, refund, rotate_key, sponsor, swap, to_external_hex,
+    transfer, transfer_from, update_operators, vest, withdraw, withdraw_stream, Signer,
+};
+
+use fixture::Scenario;
+use utils::{external_json, hex_to_bytes, now, parse_address, parse_token};
+use wallet::Wallet;
+
+mod fixture;
+mod utils;
+mod wallet;
+
+/// Wallet for the tickets transfer rollup
+#[derive(Parser)]
+#[command(name = "tickets-cli")]
+struct Cli {
+    /// Path of the wallet file
+    #[arg(long, default_value = "wallet.json")]
+    wallet: PathBuf,
+
+    /// tz1 of the account to sign for, when its key has been rotated to the key of the signer
+    #[arg(long, global = true)]
+    account: Option<String>,
+
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Generates a new edsk key stored under the alias
+    GenKey { alias: String },
+    /// Imports an existing edsk key under the alias
+    ImportKey { alias: String, secret_key: String },
+    /// Shows the public key and the tz1 of a key
+    ShowAddress { alias: String },
+    /// Builds and signs a transfer between two L2 accounts
+    Transfer {
+        #[command(flatten)]
+        args: OperationArgs,
+        /// tz1 receiving the tokens
+        #[arg(long)]
+        destination: String,
+        /// tz1 of the account the tokens are debited from, when the signer is one of its session keys
+        /// The nonce is then the next nonce of this account
+        #[arg(long)]
+        source: Option<String>,
+    },
+    /// Builds and signs the rotation of the key of the signer's account to --key
+    RotateKey {
+        /// Alias or secret key of the signer
+        #[arg(long)]
+        from: String,
+        /// Alias, secret key or edpk of the new key
+        #[arg(long)]
+        key: String,
+        /// Next nonce of the account
+        #[arg(long)]
+        nonce: u64,
+        /// Defaults to the current time
+        #[arg(long)]
+        timestamp: Option<String>,
+    },
+    /// Builds and signs the authorization of a session key to transfer up to --amount tokens of the signer
+    AuthorizeSessionKey {
+        #[command(flatten)]
+        args: OperationArgs,
+        /// tz1 of the session key
+        #[arg(long)]
+        session_key: String,
+        /// Level from which the key can't transfer anymore
+        #[arg(long)]
+        expires_level: u32,
+    },
+    /// Builds and signs a withdrawal to a L1 account
+    Withdraw {
+        #[command(flatten)]
+        args: OperationArgs,
+        /// L1 tz1 receiving the tokens
+        #[arg(long)]
+        destination: String,
+    },
+    /// Builds and signs an approval of the spender, to spend up to --amount tokens of the signer
+    Approve {
+        #[command(flatten)]
+        args: OperationArgs,
+        /// tz1 allowed to spend the tokens
+        #[arg(long)]
+        spender: String,
+    },
+    /// Builds and signs a transfer of the tokens of the owner, the signer being a spender or an operator
+    TransferFrom {
+        #[command(flatten)]
+        args: OperationArgs,
+        /// tz1 owning the tokens
+        #[arg(long)]
+        owner: String,
+        /// tz1 receiving the tokens
+        #[arg(long)]
+        destination: String,
+    },
+    /// Builds and signs the addition, or the removal, of an operator of the signer for a token
+    UpdateOperator {
+        /// Alias or secret key of the signer
+        #[arg(long)]
+        from: String,
+        /// tz1 of the operator
+        #[arg(long)]
+        operator: String,
+        /// Packed token in hex, tez, or {"ticketer":"KT1...","content":{"Nat":"0"}}
+        #[arg(long)]
+        token: String,
+        /// Removes the operator instead of adding it
+        #[arg(long)]
+        remove: bool,
+        /// Next nonce of the signer
+        #[arg(long)]
+        nonce: u64,
+        /// Defaults to the current time
+        #[arg(long)]
+        timestamp: Option<String>,
+    },
+    /// Builds and signs a lock of tokens for the recipient, claimable with the preimage of the hashlock
+    Lock {
+        #[command(flatten)]
+        args: OperationArgs,
+        /// tz1 claiming the tokens
+        #[arg(long)]
+        recipient: String,
+        /// Blake2b hash of the preimage, in hex
+        #[arg(long)]
+        hashlock: String,
+        /// Level from which the tokens can be refunded
+        #[arg(long)]
+        timeout_level: u32,
+    },
+    /// Builds and signs the claim of a lock, with the preimage of its hashlock
+    Claim {
+        #[command(flatten)]
+        args: HtlcArgs,
+        /// Preimage of the hashlock, in hex
+        #[arg(long)]
+        preimage: String,
+    },
+    /// Builds and signs the refund of an expired lock
+    Refund(HtlcArgs),
+    /// Builds and signs a schedule vesting --amount tokens for the beneficiary, linearly until the end level
+    Vest {
+        #[command(flatten)]
+        args: OperationArgs,
+        /// tz1 claiming the vested tokens
+        #[arg(long)]
+        beneficiary: String,
+        #[arg(long)]
+        start_level: u32,
+        /// Level before which nothing can be claimed, defaults to the start level
+        #[arg(long)]
+        cliff_level: Option<u32>,
+        #[arg(long)]
+        end_level: u32,
+    },
+    /// Builds and signs the release of the vested tokens of a schedule
+    ClaimVested(HtlcArgs),
+    /// Builds and signs a stream paying the --amount deposit to the recipient at a fixed rate per level
+    OpenStream {
+        #[command(flatten)]
+        args: OperationArgs,
+        /// tz1 withdrawing the streamed tokens
+        #[arg(long)]
+        recipient: String,
+        #[arg(long)]
+        rate_per_level: u128,
+    },
+    /// Builds and signs the release of the accrued tokens of a stream
+    WithdrawStream(HtlcArgs),
+    /// Builds and signs the cancellation of a stream, by its sender or its recipient
+    CancelStream(HtlcArgs),
+    /// Signs a leg of a swap, both parties have to sign the same swap, printed as json
+    SwapLeg {
+        /// Alias or secret key of the signer, one of the parties
+        #[arg(long)]
+        from: String,
+        /// tz1 sending the first token
+        #[arg(long)]
+        first: String,
+        #[arg(long)]
+        first_token: String,
+        #[arg(long)]
+        first_amount: u128,
+        /// tz1 sending the second token
+        #[arg(long)]
+        second: String,
+        #[arg(long)]
+        second_token: String,
+        #[arg(long)]
+        second_amount: u128,
+        /// Level from which the swap can't be applied, until then the leg is only cancelled by
+        /// using its nonce
+        #[arg(long)]
+        expires_level: u32,
+        /// Next nonce of the signer
+        #[arg(long)]
+        nonce: u64,
+        /// Defaults to the current time
+        #[arg(long)]
+        timestamp: Option<String>,
+    },
+    /// Builds a swap from the legs signed by the first and the second party
+    Swap { first: PathBuf, second: PathBuf },
+    /// Builds and signs an operation on the pools, given as json
+    /// e.g. '{"Swap":{"token_in":[18,52],"amount_in":100,"token_out":[171,205],"min_out":90,"deadline_level":1000}}'
+    Pool {
+        /// Alias or secret key of the signer
+        #[arg(long)]
+        from: String,
+        /// Next nonce of the signer
+        #[arg(long)]
+        nonce: u64,
+        /// Defaults to the current time
+        #[arg(long)]
+        timestamp: Option<String>,
+        operation: String,
+    },
+    /// Builds and signs an operation on the order book, given as json
+    /// e.g. '{"Place":{"base":[18,52],"quote":[171,205],"side":"Buy","price":3,"amount":100}}'
+    Order {
+        /// Alias or secret key of the signer
+        #[arg(long)]
+        from: String,
+        /// Next nonce of the signer
+        #[arg(long)]
+        nonce: u64,
+        /// Defaults to the current time
+        #[arg(long)]
+        timestamp: Option<String>,
+        operation: String,
+    },
+    /// Wraps the external message of an account, given in hex, with the signature of a relayer
+    Sponsor {
+        /// Alias or secret key of the relayer
+        #[arg(long)]
+        from: String,
+        /// Next nonce of the relayer
+        #[arg(long)]
+        nonce: u64,
+        /// Token of the fee paid by the relayer to the admin, as --token
+        #[arg(long, default_value = "tez")]
+        fee_token: String,
+        /// Fee paid by the relayer to the admin
+        #[arg(long, default_value_t = 0)]
+        fee_amount: u128,
+        /// Defaults to the current time
+        #[arg(long)]
+        timestamp: Option<String>,
+        hex: String,
+    },
+    /// Shows the address of the multisig account controlled by --threshold of the keys
+    MultisigAddress {
+        #[command(flatten)]
+        multisig: MultisigArgs,
+    },
+    /// Builds a transfer, or a withdrawal, from a multisig account, signed by one of its keys
+    /// The messages signed by the other keys are merged with multisig-merge
+    MultisigSign {
+        #[command(flatten)]
+        args: OperationArgs,
+        #[command(flatten)]
+        multisig: MultisigArgs,
+        /// tz1 receiving the tokens
+        #[arg(long)]
+        destination: String,
+        /// Withdraws to the L1 account --destination instead of transferring
+        #[arg(long)]
+        withdraw: bool,
+    },
+    /// Merges the signatures of the same multisig message, each given in hex
+    MultisigMerge { hexes: Vec<String> },
+    /// Encodes the action of a deposit, given as json, to the hex of the bytes sent with the ticket
+    /// e.g. '{"Transfer":{"destination":"tz1..."}}'
+    DepositAction { action: String },
+    /// Decodes the hex of an external message to json
+    Decode { hex: String },
+    /// Generates the inputs of the WASM debugger from a scenario
+    Fixture {
+        scenario: PathBuf,
+        /// Defaults to the standard output
+        #[arg(long)]
+        output: Option<PathBuf>,
+    },
+}
+
+#[derive(Args)]
+struct OperationArgs {
+    /// Alias or secret key of the signer
+    #[arg(long)]
+    from: String,
+    /// Packed token in hex, tez, or {"ticketer":"KT1...","content":{"Nat":"0"}}
+    #[arg(long)]
+    token: String,
+    #[arg(long)]
+    amount: u128,
+    /// Next nonce of the signer on the lane
+    #[arg(long)]
+    nonce: u64,
+    /// Lane of the nonce, messages on different lanes can be included in any order
+    #[arg(long, default_value_t = 0)]
+    lane: u32,
+    /// Defaults to the current time
+    #[arg(long)]
+    timestamp: Option<String>,
+}
+
+#[derive(Args)]
+struct HtlcArgs {
+    /// Alias or secret key of the signer
+    #[arg(long)]
+    from: String,
+    /// Identifier of the lock, of the vesting schedule or of the stream
+    #[arg(long)]
+    id: String,
+    /// Next nonce of the signer
+    #[arg(long)]
+    nonce: u64,
+    /// Defaults to the current time
+    #[arg(long)]
+    timestamp: Option<String>,
+}
+
+#[derive(Args)]
+struct MultisigArgs {
+    /// Aliases, secret keys or edpks of the keys of the account, separated by commas
+    #[arg(long, value_delimiter = ',', required = true)]
+    keys: Vec<String>,
+    /// Number of keys required to sign
+    #[arg(long)]
+    threshold: u8,
+}
+
+impl MultisigArgs {
+    fn parse(&self, wallet: &Wallet) -> Result<Multisig, String> {
+        let keys = self
+            .keys
+            .iter()
+            .map(|key| public_key(wallet, key))
+            .collect::<Result<Vec<PublicKey>, String>>()?;
+        Multisig::new(keys, self.threshold).map_err(|err| err.to_string())
+    }
+}
+
+impl OperationArgs {
+    fn parse(&self, wallet: &Wallet) -> Result<(Signer, Token, String), String> {
+        let signer = wallet.signer(&self.from)?;
+        let token = parse_token(&self.token)?;
+        let timestamp = self.timestamp.clone().unwrap_or_else(now);
+        Ok((signer, token, timestamp))
+    }
+}
+
+/// Parses an edpk, or returns the public key of an alias or of a secret key of the wallet
+fn public_key(wallet: &Wallet, key: &str) -> Result<PublicKey, String> {
+    match PublicKey::from_b58(key) {
+        Ok(key) => Ok(key),
+        Err(_) => Ok(wallet.signer(key)?.public_key().clone()),
+    }
+}
+
+/// Prints the external message, in hex
+fn print_message(message: &Message) -> Result<(), String> {
+    let hex = to_external_hex(message).map_err(|err| err.to_string())?;
+    println!("{}", hex);
+    Ok(())
+}
+
+/// Signs the inner and prints the external message wrapping it, in hex
+fn print_signed<C: Content>(
+    signer: &Signer,
+    inner: Inner<C>,
+    timestamp: &str,
+    wrap: impl FnOnce(SignedMessage<C>) -> Message,
+) -> Result<(), String> {
+    let message = signer
+        .sign(inner, timestamp)
+        .map_err(|err| err.to_string())?;
+    print_message(&wrap(message))
+}
+
+fn print_signer(signer: &Signer) {
+    println!("Public key: {}", signer.public_key().to_b58());
+    println!("Address: {}", signer.public_key_hash().to_string());
+}
+
+/// Reminds that the secret keys are not encrypted, only protected by the file permissions
+fn print_stored_key(wallet: &Wallet, signer: &Signer) {
+    print_signer(signer);
+    eprintln!(
+        "Warning: the secret key is stored unencrypted in {}, keep this file private",
+        wallet.path().display()
+    );
+}
+
+fn run(cli: Cli) -> Result<(), String> {
+    let mut wallet = Wallet::load(cli.wallet)?;
+    if let Some(account) = cli.account {
+        wallet.sign_for(parse_address(&account)?);
+    }
+
+    match cli.command {
+        Command::GenKey { alias } => {
+            let signer = wallet.generate(&alias)?;
+            print_stored_key(&wallet, &signer)
+        }
+        Command::ImportKey { alias, secret_key } => {
+            let signer = wallet.import(&alias, &secret_key)?;
+            print_stored_key(&wallet, &signer)
+        }
+        Command::ShowAddress { alias } => print_signer(&wallet.signer(&alias)?),
+        Command::Transfer {
+            args,
+            destination,
+            source,
+        } => {
+            let (signer, token, timestamp) = args.parse(&wallet)?;
+            let destination = parse_address(&destination)?;
+            let mut inner = transfer(args.nonce, token, destination, args.amount);
+            inner.lane = args.lane;
+            inner.content.source = source.as_deref().map(parse_address).transpose()?;
+            print_signed(&signer, inner, &timestamp, Message::Transfer)?;
+        }
+        Command::RotateKey {
+            from,
+            key,
+            nonce,
+            timestamp,
+        } => {
+            let signer = wallet.signer(&from)?;
+            let key = public_key(&wallet, &key)?;
+            let timestamp = timestamp.unwrap_or_else(now);
+            print_signed(
+                &signer,
+                rotate_key(nonce, key),
+                &timestamp,
+                Message::RotateKey,
+            )?;
+        }
+        Command::AuthorizeSessionKey {
+            args,
+            session_key,
+            expires_level,
+        } => {
+            let (signer, token, timestamp) = args.parse(&wallet)?;
+            let content = AuthorizeSessionKeyContent {
+                key: parse_address(&session_key)?,
+                token,
+                max_amount: args.amount,
+                expires_level,
+            };
+            let mut inner = authorize_session_key(args.nonce, content);
+            inner.lane = args.lane;
+            print_signed(&signer, inner, &timestamp, Message::AuthorizeSessionKey)?;
+        }
+        Command::Withdraw { args, destination } => {
+            let (signer, token, timestamp) = args.parse(&wallet)?;
+            let destination = parse_address(&destination)?;
+            let mut inner = withdraw(args.nonce, token, destination, args.amount);
+            inner.lane = args.lane;
+            print_signed(&signer, inner, &timestamp, Message::Withdraw)?;
+        }
+        Command::Approve { args, spender } => {
+            let (signer, token, timestamp) = args.parse(&wallet)?;
+            let spender = parse_address(&spender)?;
+            let mut inner = approve(args.nonce, spender, token, args.amount);
+            inner.lane = args.lane;
+            print_signed(&signer, inner, &timestamp, Message::Approve)?;
+        }
+        Command::TransferFrom {
+            args,
+            owner,
+            destination,
+        } => {
+            let (signer, token, timestamp) = args.parse(&wallet)?;
+            let owner = parse_address(&owner)?;
+            let destination = parse_address(&destination)?;
+            let mut inner = transfer_from(args.nonce, owner, token, destination, args.amount);
+            inner.lane = args.lane;
+            print_signed(&signer, inner, &timestamp, Message::TransferFrom)?;
+        }
+        Command::UpdateOperator {
+            from,
+            operator,
+            token,
+            remove,
+            nonce,
+            timestamp,
+        } => {
+            let signer = wallet.signer(&from)?;
+            let operator = parse_address(&operator)?;
+            let token = parse_token(&token)?;
+            let update = if remove {
+                OperatorUpdate::Remove { operator, token }
+            } else {
+                OperatorUpdate::Add { operator, token }
+            };
+            let inner = update_operators(nonce, vec![update]);
+            let timestamp = timestamp.unwrap_or_else(now);
+            print_signed(&signer, inner, &timestamp, Message::UpdateOperators)?;
+        }
+        Command::Lock {
+            args,
+            recipient,
+            hashlock,
+            timeout_level,
+        } => {
+            let (signer, token, timestamp) = args.parse(&wallet)?;
+            let recipient = parse_address(&recipient)?;
+            let hashlock = hex_to_bytes(&hashlock)?;
+            let mut inner = lock(
+                args.nonce,
+                token,
+                args.amount,
+                recipient,
+                hashlock,
+                timeout_level,
+            );
+            inner.lane = args.lane;
+            let sender = signer.public_key_hash();
+            print_signed(&signer, inner, &timestamp, |message| {
+                println!("Lock: {}", htlc_id(&sender, message.inner()));
+                Message::Htlc(message)
+            })?;
+        }
+        Command::Claim { args, preimage } => {
+            let signer = wallet.signer(&args.from)?;
+            let inner = claim(args.nonce, args.id, hex_to_bytes(&preimage)?);
+            let timestamp = args.timestamp.unwrap_or_else(now);
+            print_signed(&signer, inner, &timestamp, Message::Htlc)?;
+        }
+        Command::Refund(args) => {
+            let signer = wallet.signer(&args.from)?;
+            let inner = refund(args.nonce, args.id);
+            let timestamp = args.timestamp.unwrap_or_else(now);
+            print_signed(&signer, inner, &timestamp, Message::Htlc)?;
+        }
+        Command::Vest {
+            args,
+            beneficiary,
+            start_level,
+            cliff_level,
+            end_level,
+        } => {
+            let (signer, token, timestamp) = args.parse(&wallet)?;
+            let content = VestContent {
+                beneficiary: parse_address(&beneficiary)?,
+                token,
+                total: args.amount,
+                start_level,
+                cliff_level: cliff_level.unwrap_or(start_level),
+                end_level,
+            };
+            let mut inner = vest(args.nonce, content);
+            inner.lane = args.lane;
+            let sender = signer.public_key_hash();
+            print_signed(&signer, inner, &timestamp, |message| {
+                println!("Vesting: {}", vesting_id(&sender, message.inner()));
+                Message::Vesting(message)
+            })?;
+        }
+        Command::ClaimVested(args) => {
+            let signer = wallet.signer(&args.from)?;
+            let inner = claim_vested(args.nonce, args.id);
+            let timestamp = args.timestamp.unwrap_or_else(now);
+            print_signed(&signer, inner, &timestamp, Message::Vesting)?;
+        }
+        Command::OpenStream {
+            args,
+            recipient,
+            rate_per_level,
+        } => {
+            let (signer, token, timestamp) = args.parse(&wallet)?;
+            let content = OpenStreamContent {
+                recipient: parse_address(&recipient)?,
+                token,
+                rate_per_level,
+                deposit: args.amount,
+            };
+            let mut inner = open_stream(args.nonce, content);
+            inner.lane = args.lane;
+            let sender = signer.public_key_hash();
+            print_signed(&signer, inner, &timestamp, |message| {
+                println!("Stream: {}", stream_id(&sender, message.inner()));
+                Message::Stream(message)
+            })?;
+        }
+        Command::WithdrawStream(args) => {
+            let signer = wallet.signer(&args.from)?;
+            let inner = withdraw_stream(args.nonce, args.id);
+            let timestamp = args.timestamp.unwrap_or_else(now);
+            print_signed(&signer, inner, &timestamp, Message::Stream)?;
+        }
+        Command::CancelStream(args) => {
+            let signer = wallet.signer(&args.from)?;
+            let inner = cancel_stream(args.nonce, args.id);
+            let timestamp = args.timestamp.unwrap_or_else(now);
+            print_signed(&signer, inner, &timestamp, Message::Stream)?;
+        }
+        Command::SwapLeg {
+            from,
+            first,
+            first_token,
+            first_amount,
+            second,
+            second_token,
+            second_amount,
+            expires_level,
+            nonce,
+            timestamp,
+        } => {
+            let signer = wallet.signer(&from)?;
+            let content = SwapContent {
+                first: parse_address(&first)?,
+                first_token: parse_token(&first_token)?,
+                first_amount,
+                second: parse_address(&second)?,
+                second_token: parse_token(&second_token)?,
+                second_amount,
+                expires_level,
+            };
+            let leg = signer
+                .sign(swap(nonce, content), &timestamp.unwrap_or_else(now))
+                .map_err(|err| err.to_string())?;
+            let json = serde_json::to_string_pretty(&leg).map_err(|err| err.to_string())?;
+            println!("{}", json);
+        }
+        Command::Swap { first, second } => {
+            let read_leg = |path: PathBuf| -> Result<SwapLeg, String> {
+                let leg = fs::read_to_string(&path)
+                    .map_err(|err| format!("Cannot read {}: {}", path.display(), err))?;
+                serde_json::from_str(&leg).map_err(|err| err.to_string())
+            };
+            let message = SwapMessage {
+                first: read_leg(first)?,
+                second: read_leg(second)?,
+            };
+            print_message(&Message::Swap(Box::new(message)))?;
+        }
+        Command::Pool {
+            from,
+            nonce,
+            timestamp,
+            operation,
+        } => {
+            let signer = wallet.signer(&from)?;
+            let content: PoolContent =
+                serde_json::from_str(&operation).map_err(|err| err.to_string())?;
+            let timestamp = timestamp.unwrap_or_else(now);
+            print_signed(&signer, pool(nonce, content), &timestamp, Message::Pool)?;
+        }
+        Command::Order {
+            from,
+            nonce,
+            timestamp,
+            operation,
+        } => {
+            let signer = wallet.signer(&from)?;
+            let content: OrderContent =
+                serde_json::from_str(&operation).map_err(|err| err.to_string())?;
+            let timestamp = timestamp.unwrap_or_else(now);
+            print_signed(&signer, order(nonce, content), &timestamp, Message::Order)?;
+        }
+        Command::Sponsor {
+            from,
+            nonce,
+            fee_token,
+            fee_amount,
+            timestamp,
+            hex,
+        } => {
+            let signer = wallet.signer(&from)?;
+            let message: Message = serde_json_wasm::from_slice(&external_json(&hex)?)
+                .map_err(|err| err.to_string())?;
+            let fee_token = parse_token(&fee_token)?;
+            let inner = sponsor(nonce, &message, fee_token, fee_amount)
+                .ok_or("The message is not signed by a single account")?;
+            let timestamp = timestamp.unwrap_or_else(now);
+            print_signed(&signer, inner, &timestamp, |sponsor| {
+                Message::Sponsored(Box::new(SponsoredMessage { message, sponsor }))
+            })?;
+        }
+        Command::MultisigAddress { multisig } => {
+            let address = multisig
+                .parse(&wallet)?
+                .address()
+                .map_err(|err| err.to_string())?;
+            println!("Address: {}", address.to_string());
+        }
+        Command::MultisigSign {
+            args,
+            multisig: multisig_args,
+            destination,
+            withdraw,
+        } => {
+            let (signer, token, timestamp) = args.parse(&wallet)?;
+            let destination = parse_address(&destination)?;
+            let account = multisig_args.parse(&wallet)?;
+            let content = if withdraw {
+                MultisigContent::Withdraw(
+                    kernel_client::withdraw(args.nonce, token, destination, args.amount).content,
+                )
+            } else {
+                MultisigContent::Transfer(
+                    transfer(args.nonce, token, destination, args.amount).content,
+                )
+            };
+            let address = account.address().map_err(|err| err.to_string())?;
+            let mut inner = multisig(address, args.nonce, content);
+            inner.lane = args.lane;
+            let signature = signer
+                .cosign(&inner, &timestamp)
+                .map_err(|err| err.to_string())?;
+            let message = MultisigMessage {
+                keys: account.keys().to_vec(),
+                threshold: account.threshold(),
+                signatures: vec![signature],
+                inner,
+            };
+            print_message(&Message::Multisig(message))?;
+        }
+        Command::MultisigMerge { hexes } => {
+            let mut merged: Option<MultisigMessage> = None;
+            for hex in hexes {
+                let message: Message = serde_json_wasm::from_slice(&external_json(&hex)?)
+                    .map_err(|err| err.to_string())?;
+                let Message::Multisig(message) = message else {
+                    return Err("Not a multisig message".to_string());
+                };
+                match merged.as_mut() {
+                    None => merged = Some(message),
+                    Some(merged) => {
+                        // The hash of the inner covers the address of the multisig
+                        if merged.keys != message.keys
+                            || merged.threshold != message.threshold
+                            || merged.inner.hash().to_string() != message.inner.hash().to_string()
+                        {
+                            return Err("The multisig messages are different".to_string());
+                        }
+                        merged.signatures.extend(message.signatures);
+                    }
+                }
+            }
+            let message = merged.ok_or("No multisig message to merge")?;
+            print_message(&Message::Multisig(message))?;
+        }
+        Command::DepositAction { action } => {
+            let action: DepositAction =
+                serde_json::from_str(&action).map_err(|err| err.to_string())?;
+            let bytes = deposit_action(&action).map_err(|err| err.to_string())?;
+            let hex: String = bytes.iter().map(|byte| format!("{:02x}", byte)).collect();
+            println!("{}", hex);
+        }
+        Command::Decode { hex } => {
+            let json = external_json(&hex)?;
+
+            // Makes sure the kernel is able to decode the message
+            serde_json_wasm::from_slice::<Message>(&json).map_err(|err| err.to_string())?;
+
+            let value: serde_json::Value =
+                serde_json::from_slice(&json).map_err(|err| err.to_string())?;
+            let pretty = serde_json::to_string_pretty(&value).map_err(|err| err.to_string())?;
+            println!("{}", pretty);
+        }
+        Command::Fixture { scenario, output } => {
+            let scenario = fs::read_to_string(&scenario)
+                .map_err(|err| format!("Cannot read {}: {}", scenario.display(), err))?;
+            let scenario: Scenario =
+                serde_json::from_str(&scenario).map_err(|err| err.to_string())?;
+
+            let inputs = fixture::generate(&scenario)?;
+            let inputs = serde_json::to_string_pretty(&inputs).map_err(|err| err.to_string())?;
+            match output {
+                Some(output) => fs::write(output, inputs + "\n").map_err(|err| err.to_string())?,
+                None => println!("{}", inputs),
+            }
+        }
+    }
+    Ok(())
+}
+
+fn main() {
+    if let Err(err) = run(Cli::parse()) {
+        eprintln!("{}", err);
+        std::process::exit(1);
+    }
+}
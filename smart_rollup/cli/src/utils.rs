@@ -0,0 +1,74 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use kernel::constants::{EXTERNAL_MESSAGE_REP, TEZ_TOKEN};
+use kernel::core::public_key_hash::PublicKeyHash;
+use kernel::core::token::Token;
+
+pub fn hex_to_bytes(hex: &str) -> Result<Vec<u8>, String> {
+    let hex = hex.trim().trim_start_matches("0x");
+    if hex.len() % 2 == 1 {
+        return Err(format!("Invalid hex: {}", hex));
+    }
+
+    (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).map_err(|err| err.to_string()))
+        .collect()
+}
+
+/// Parses "tez", the json of a ticket, or a packed token in hex
+pub fn parse_token(token: &str) -> Result<Token, String> {
+    if token == TEZ_TOKEN {
+        return Ok(Token::Tez);
+    }
+    if token.trim_start().starts_with('{') {
+        return serde_json::from_str(token).map_err(|err| err.to_string());
+    }
+    Ok(Token::Bytes(hex_to_bytes(token)?))
+}
+
+/// Parses the tz1 of an L2 or L1 account
+pub fn parse_address(address: &str) -> Result<PublicKeyHash, String> {
+    PublicKeyHash::from_b58(address).map_err(|_| format!("Invalid tz1 address: {}", address))
+}
+
+/// Returns the json of an external message of the kernel given in hex
+pub fn external_json(hex: &str) -> Result<Vec<u8>, String> {
+    let bytes = hex_to_bytes(hex)?;
+    match bytes.split_first() {
+        Some((&EXTERNAL_MESSAGE_REP, json)) => Ok(json.to_vec()),
+        _ => Err("Not an external message of the kernel".to_string()),
+    }
+}
+
+/// Current UTC time formatted like a javascript `Date.toISOString()`, e.g 2023-05-19T05:45:50.473Z
+pub fn now() -> String {
+    let elapsed = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    let seconds = elapsed.as_secs() as i64;
+    let (days, seconds_of_day) = (seconds.div_euclid(86400), seconds.rem_euclid(86400));
+
+    // Civil date from the number of days since 1970-01-01
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let day_of_era = z.rem_euclid(146097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z",
+        year,
+        month,
+        day,
+        seconds_of_day / 3600,
+        seconds_of_day % 3600 / 60,
+        seconds_of_day % 60,
+        elapsed.subsec_millis()
+    )
+}
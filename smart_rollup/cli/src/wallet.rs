@@ -0,0 +1,170 @@
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use kernel::core::public_key_hash::PublicKeyHash;
+use kernel_client::Signer;
+use tezos_crypto_rs::hash::SeedEd25519;
+
+/// Aliases and secret keys (edsk...), stored unencrypted as a json file
+/// that only its owner can read and write
+pub struct Wallet {
+    path: PathBuf,
+    keys: BTreeMap<String, String>,
+    /// Account the signers sign for, when its key has been rotated
+    account: Option<PublicKeyHash>,
+}
+
+impl Wallet {
+    /// Loads the wallet, an empty one is returned if the file does not exist yet
+    pub fn load(path: PathBuf) -> Result<Self, String> {
+        let keys = match fs::read_to_string(&path) {
+            Ok(content) => serde_json::from_str(&content)
+                .map_err(|err| format!("Cannot read wallet {}: {}", path.display(), err))?,
+            Err(_) => BTreeMap::default(),
+        };
+        Ok(Wallet {
+            path,
+            keys,
+            account: None,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn save(&self) -> Result<(), String> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent).map_err(|err| err.to_string())?;
+        }
+        let content = serde_json::to_string_pretty(&self.keys).map_err(|err| err.to_string())?;
+
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create(true).truncate(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+            options.mode(0o600);
+            // The mode is only applied when the file is created
+            if self.path.exists() {
+                fs::set_permissions(&self.path, fs::Permissions::from_mode(0o600))
+                    .map_err(|err| err.to_string())?;
+            }
+        }
+        let mut file = options.open(&self.path).map_err(|err| err.to_string())?;
+        file.write_all(content.as_bytes())
+            .map_err(|err| err.to_string())
+    }
+
+    /// Generates a new secret key and stores it under the alias
+    pub fn generate(&mut self, alias: &str) -> Result<Signer, String> {
+        let mut seed = [0_u8; 32];
+        getrandom::getrandom(&mut seed).map_err(|err| err.to_string())?;
+        let seed = SeedEd25519::try_from(seed.to_vec())
+            .map_err(|_| "Cannot create the seed".to_string())?;
+
+        self.import(alias, &seed.to_base58_check())
+    }
+
+    /// Stores an existing secret key under the alias
+    pub fn import(&mut self, alias: &str, secret_key: &str) -> Result<Signer, String> {
+        if self.keys.contains_key(alias) {
+            return Err(format!("Alias {} already exists", alias));
+        }
+
+        let signer = Signer::from_b58(secret_key).map_err(|err| err.to_string())?;
+        self.keys.insert(alias.to_string(), secret_key.to_string());
+        self.save()?;
+        Ok(signer)
+    }
+
+    /// Makes the signers sign for the account, whose key has been rotated to theirs
+    pub fn sign_for(&mut self, account: PublicKeyHash) {
+        self.account = Some(account);
+    }
+
+    /// Returns the signer of an alias, a raw secret key is also accepted
+    pub fn signer(&self, alias: &str) -> Result<Signer, String> {
+        let secret_key = self.keys.get(alias).map(String::as_str).unwrap_or(alias);
+        let signer = Signer::from_b58(secret_key)
+            .map_err(|_| format!("Unknown alias or secret key: {}", alias))?;
+        Ok(match &self.account {
+            Some(account) => signer.with_account(account.clone()),
+            None => signer,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::PathBuf;
+
+    use super::Wallet;
+
+    const SECRET_KEY: &str = "edsk31vznjHSSpGExDMHYASz45VZqXN4DPxvsa4hAyY8dHM28cZzp6";
+
+    /// Path of a wallet which does not exist yet, in a directory of its own
+    fn wallet_path(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("tickets-cli-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir.join("wallet.json")
+    }
+
+    #[test]
+    fn test_imported_key_is_saved() {
+        let path = wallet_path("import");
+        let mut wallet = Wallet::load(path.clone()).unwrap();
+        let signer = wallet.import("alice", SECRET_KEY).unwrap();
+        assert!(wallet.import("alice", SECRET_KEY).is_err());
+
+        let wallet = Wallet::load(path.clone()).unwrap();
+        let loaded = wallet.signer("alice").unwrap();
+        assert_eq!(
+            signer.public_key_hash().to_string(),
+            loaded.public_key_hash().to_string()
+        );
+        // A raw secret key is accepted, an unknown alias is not
+        assert!(wallet.signer(SECRET_KEY).is_ok());
+        assert!(wallet.signer("bob").is_err());
+
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[test]
+    fn test_generated_keys_are_different() {
+        let path = wallet_path("generate");
+        let mut wallet = Wallet::load(path.clone()).unwrap();
+        let alice = wallet.generate("alice").unwrap();
+        let bob = wallet.generate("bob").unwrap();
+        assert!(alice.public_key_hash() != bob.public_key_hash());
+
+        let wallet = Wallet::load(path.clone()).unwrap();
+        assert_eq!(
+            bob.public_key_hash().to_string(),
+            wallet.signer("bob").unwrap().public_key_hash().to_string()
+        );
+
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_wallet_is_only_readable_by_its_owner() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let path = wallet_path("mode");
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, "{}").unwrap();
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o644)).unwrap();
+
+        let mut wallet = Wallet::load(path.clone()).unwrap();
+        wallet.import("alice", SECRET_KEY).unwrap();
+        let mode = fs::metadata(&path).unwrap().permissions().mode();
+        assert_eq!(0o600, mode & 0o777);
+
+        fs::remove_dir_all(path.parent().unwrap()).unwrap();
+    }
+}
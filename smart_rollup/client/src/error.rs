@@ -0,0 +1,22 @@
+/// Represents all the error of the client
+#[derive(Debug)]
+pub enum Error {
+    InvalidSecretKey,
+    Signing,
+    InvalidSignature,
+    Serialization,
+}
+
+impl ToString for Error {
+    fn to_string(&self) -> String {
+        let err = match self {
+            Error::InvalidSecretKey => "Cannot decode the secret key",
+            Error::Signing => "Cannot sign the message",
+            Error::InvalidSignature => "Cannot convert the signature to Ed25519",
+            Error::Serialization => "Cannot serialize the message",
+        };
+        err.to_string()
+    }
+}
+
+pub type Result<A> = std::result::Result<A, Error>;
@@ -0,0 +1,296 @@
+use kernel::constants::EXTERNAL_MESSAGE_REP;
+use kernel::core::message::{
+    AdminContent, ApproveContent, AuthorizeSessionKeyContent, Content, DepositAction, HtlcContent,
+    Inner, LockContent, Message, MultisigContent, OpenStreamContent, OperatorUpdate, OrderContent,
+    PoolContent, RotateKeyContent, SponsorContent, StreamContent, SwapContent, TransferContent,
+    TransferFromContent, UpdateOperatorsContent, VestContent, VestingContent, WithdrawContent,
+};
+use kernel::core::nonce::Nonce;
+use kernel::core::public_key::PublicKey;
+use kernel::core::public_key_hash::PublicKeyHash;
+use kernel::core::token::Token;
+
+pub mod error;
+pub mod signer;
+
+pub use error::{Error, Result};
+pub use signer::Signer;
+
+/// Builds the inner of the content, signed on the default lane for the account of the signer
+fn inner<C: Content>(nonce: u64, content: C) -> Inner<C> {
+    Inner {
+        nonce: Nonce(nonce),
+        lane: 0,
+        account: None,
+        content,
+    }
+}
+
+/// Builds the inner of a transfer between two L2 accounts
+pub fn transfer(
+    nonce: u64,
+    token: Token,
+    destination: PublicKeyHash,
+    amount: u128,
+) -> Inner<TransferContent> {
+    inner(
+        nonce,
+        TransferContent {
+            token,
+            destination,
+            amount,
+            source: None,
+        },
+    )
+}
+
+/// Builds the inner of a withdrawal to a L1 account
+pub fn withdraw(
+    nonce: u64,
+    token: Token,
+    destination: PublicKeyHash,
+    amount: u128,
+) -> Inner<WithdrawContent> {
+    inner(
+        nonce,
+        WithdrawContent {
+            token,
+            destination,
+            amount,
+        },
+    )
+}
+
+/// Builds the inner allowing the spender to transfer tokens of the signer
+pub fn approve(
+    nonce: u64,
+    spender: PublicKeyHash,
+    token: Token,
+    amount: u128,
+) -> Inner<ApproveContent> {
+    inner(
+        nonce,
+        ApproveContent {
+            spender,
+            token,
+            amount,
+        },
+    )
+}
+
+/// Builds the inner of a transfer of the tokens of the owner, signed by a spender
+pub fn transfer_from(
+    nonce: u64,
+    owner: PublicKeyHash,
+    token: Token,
+    destination: PublicKeyHash,
+    amount: u128,
+) -> Inner<TransferFromContent> {
+    inner(
+        nonce,
+        TransferFromContent {
+            owner,
+            token,
+            destination,
+            amount,
+        },
+    )
+}
+
+/// Builds the inner adding or removing operators of the signer
+pub fn update_operators(nonce: u64, updates: Vec<OperatorUpdate>) -> Inner<UpdateOperatorsContent> {
+    inner(nonce, UpdateOperatorsContent { updates })
+}
+
+/// Builds the inner escrowing tokens of the signer until the preimage of the hashlock is revealed
+pub fn lock(
+    nonce: u64,
+    token: Token,
+    amount: u128,
+    recipient: PublicKeyHash,
+    hashlock: Vec<u8>,
+    timeout_level: u32,
+) -> Inner<HtlcContent> {
+    inner(
+        nonce,
+        HtlcContent::Lock(LockContent {
+            token,
+            amount,
+            recipient,
+            hashlock,
+            timeout_level,
+        }),
+    )
+}
+
+/// Builds the inner releasing the tokens of a lock to its recipient
+pub fn claim(nonce: u64, id: String, preimage: Vec<u8>) -> Inner<HtlcContent> {
+    inner(nonce, HtlcContent::Claim { id, preimage })
+}
+
+/// Builds the inner giving the tokens of an expired lock back to its sender
+pub fn refund(nonce: u64, id: String) -> Inner<HtlcContent> {
+    inner(nonce, HtlcContent::Refund { id })
+}
+
+/// Builds the inner escrowing tokens vested linearly for the beneficiary
+pub fn vest(nonce: u64, content: VestContent) -> Inner<VestingContent> {
+    inner(nonce, VestingContent::Vest(content))
+}
+
+/// Builds the inner releasing the vested tokens of a schedule to its beneficiary
+pub fn claim_vested(nonce: u64, id: String) -> Inner<VestingContent> {
+    inner(nonce, VestingContent::ClaimVested { id })
+}
+
+/// Builds the inner opening a stream paying the deposit to the recipient at a fixed rate per level
+pub fn open_stream(nonce: u64, content: OpenStreamContent) -> Inner<StreamContent> {
+    inner(nonce, StreamContent::OpenStream(content))
+}
+
+/// Builds the inner releasing the accrued tokens of a stream to its recipient
+pub fn withdraw_stream(nonce: u64, id: String) -> Inner<StreamContent> {
+    inner(nonce, StreamContent::WithdrawStream { id })
+}
+
+/// Builds the inner closing a stream, signed by its sender or its recipient
+pub fn cancel_stream(nonce: u64, id: String) -> Inner<StreamContent> {
+    inner(nonce, StreamContent::CancelStream { id })
+}
+
+/// Builds the inner of a leg of a swap, both parties sign the same content with their own nonce
+/// Until it expires, a signed leg can only be cancelled by using its nonce in another message
+pub fn swap(nonce: u64, content: SwapContent) -> Inner<SwapContent> {
+    inner(nonce, content)
+}
+
+/// Builds the inner of an operation on the pools
+pub fn pool(nonce: u64, content: PoolContent) -> Inner<PoolContent> {
+    inner(nonce, content)
+}
+
+/// Builds the inner of an operation on the order book
+pub fn order(nonce: u64, content: OrderContent) -> Inner<OrderContent> {
+    inner(nonce, content)
+}
+
+/// Builds the inner of a relayer sponsoring the message of an account, and paying its fee
+/// Returns None if the message is not signed by a single account
+pub fn sponsor(
+    nonce: u64,
+    message: &Message,
+    fee_token: Token,
+    fee_amount: u128,
+) -> Option<Inner<SponsorContent>> {
+    let (account, hash) = message.signed_hash()?;
+    Some(inner(
+        nonce,
+        SponsorContent {
+            account,
+            hash: hash.to_string(),
+            fee_token,
+            fee_amount,
+        },
+    ))
+}
+
+/// Builds the inner authorizing a session key to transfer tokens of the signer
+pub fn authorize_session_key(
+    nonce: u64,
+    content: AuthorizeSessionKeyContent,
+) -> Inner<AuthorizeSessionKeyContent> {
+    inner(nonce, content)
+}
+
+/// Builds the inner allowing the key to sign for the account of the signer, instead of its current key
+pub fn rotate_key(nonce: u64, key: PublicKey) -> Inner<RotateKeyContent> {
+    inner(nonce, RotateKeyContent { key })
+}
+
+/// Builds the inner of a transfer or of a withdrawal from a multisig account
+/// The address of the account is signed, so that the signatures can't be used by another
+/// multisig sharing some of its keys
+pub fn multisig(
+    address: PublicKeyHash,
+    nonce: u64,
+    content: MultisigContent,
+) -> Inner<MultisigContent> {
+    Inner {
+        account: Some(address),
+        ..inner(nonce, content)
+    }
+}
+
+/// Builds the inner of a message of the admin, to pause or freeze operations
+pub fn admin(nonce: u64, content: AdminContent) -> Inner<AdminContent> {
+    inner(nonce, content)
+}
+
+/// Encodes the action of a deposit, as the bytes sent to the rollup alongside the ticket
+pub fn deposit_action(action: &DepositAction) -> Result<Vec<u8>> {
+    serde_json_wasm::to_vec(action).map_err(|_| Error::Serialization)
+}
+
+/// Encodes the message as an external message, in hex
+/// The result can be sent with `octez-client send smart rollup message "hex:[ \"${hex}\" ]"`
+pub fn to_external_hex(message: &Message) -> Result<String> {
+    let json = serde_json_wasm::to_vec(message).map_err(|_| Error::Serialization)?;
+
+    let bytes = [vec![EXTERNAL_MESSAGE_REP], json].concat();
+    Ok(bytes.iter().map(|byte| format!("{:02x}", byte)).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use kernel::core::message::Message;
+    use kernel::core::public_key_hash::PublicKeyHash;
+    use kernel::core::token::Token;
+
+    use super::{to_external_hex, transfer, Signer};
+
+    const SECRET_KEY: &str = "edsk31vznjHSSpGExDMHYASz45VZqXN4DPxvsa4hAyY8dHM28cZzp6";
+    const TIMESTAMP: &str = "2023-05-19T05:45:50.473Z";
+
+    #[test]
+    fn test_signer_public_key_hash() {
+        let signer = Signer::from_b58(SECRET_KEY).unwrap();
+        assert_eq!(
+            "edpkuSLWfVU1Vq7Jg9FucPyKmma6otcMHac9zG4oU1KMHSTBpJuGQ2",
+            signer.public_key().to_b58()
+        );
+        assert_eq!(
+            "tz1TGu6TN5GSez2ndXXeDX6LgUDvLzPLqgYV",
+            signer.public_key_hash().to_string()
+        );
+    }
+
+    #[test]
+    fn test_signed_transfer_verifies() {
+        let signer = Signer::from_b58(SECRET_KEY).unwrap();
+        let destination = PublicKeyHash::from_b58("tz1Pe4aBjsW9ZGWaFXa47megxFD1LGGFAW3C").unwrap();
+        let inner = transfer(1, Token::Bytes(vec![0x12, 0x34]), destination, 10000000);
+
+        let message = signer.sign(inner, TIMESTAMP).unwrap();
+        let payload = message.inner().signing_payload(TIMESTAMP);
+
+        assert!(message
+            .signature()
+            .verify(message.public_key(), &payload)
+            .is_ok());
+    }
+
+    #[test]
+    fn test_external_message_matches_debug_inputs() {
+        let signer = Signer::from_b58(SECRET_KEY).unwrap();
+        let destination = PublicKeyHash::from_b58("tz1Pe4aBjsW9ZGWaFXa47megxFD1LGGFAW3C").unwrap();
+        let inner = transfer(1, Token::Bytes(vec![0x12, 0x34]), destination, 10000000);
+
+        let message = Message::Transfer(signer.sign(inner, TIMESTAMP).unwrap());
+
+        // Same transfer as the one of kernel/debug/inputs.json
+        assert_eq!(
+            "557b225472616e73666572223a7b22706b6579223a7b2245643235353139223a226564706b75534c57665655315671374a673946756350794b6d6d61366f74634d486163397a47346f55314b4d48535442704a75475132227d2c227369676e6174757265223a7b2245643235353139223a22656473696774684d4e7639534650695a65576d4a58356675646d7370666578385a6244627150467373525562383743456638576776574c6f4b633441386d666144574234366a6a58504362556a625252526a6b385158507661393952456a6250394c6b227d2c2274696d657374616d70223a22323032332d30352d31395430353a34353a35302e3437335a222c22696e6e6572223a7b226e6f6e6365223a312c22636f6e74656e74223a7b22746f6b656e223a5b31382c35325d2c2264657374696e6174696f6e223a7b22547a31223a22747a3150653461426a7357395a47576146586134376d6567784644314c47474641573343227d2c22616d6f756e74223a223130303030303030227d7d7d7d",
+            to_external_hex(&message).unwrap()
+        );
+    }
+}
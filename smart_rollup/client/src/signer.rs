@@ -0,0 +1,92 @@
+use crate::error::{Error, Result};
+use kernel::core::message::{Content, Inner, MultisigSignature, SignedMessage};
+use kernel::core::public_key::PublicKey;
+use kernel::core::public_key_hash::PublicKeyHash;
+use kernel::core::signature::Signature;
+use tezos_crypto_rs::hash::{Ed25519Signature, SecretKeyEd25519, SeedEd25519};
+
+/// Ed25519 key pair signing messages for the kernel
+pub struct Signer {
+    public_key: PublicKey,
+    secret_key: SecretKeyEd25519,
+    /// Account signed for, when its key has been rotated to the key of the signer
+    account: Option<PublicKeyHash>,
+}
+
+impl Signer {
+    /// Creates a signer from a b58 encoded seed (edsk...)
+    pub fn from_b58(secret_key: &str) -> Result<Self> {
+        let (public_key, secret_key) = SeedEd25519::from_base58_check(secret_key)
+            .map_err(|_| Error::InvalidSecretKey)?
+            .keypair()
+            .map_err(|_| Error::InvalidSecretKey)?;
+
+        Ok(Signer {
+            public_key: PublicKey::Ed25519(public_key),
+            secret_key,
+            account: None,
+        })
+    }
+
+    /// Returns the public key of the signer
+    pub fn public_key(&self) -> &PublicKey {
+        &self.public_key
+    }
+
+    /// Signs for the account, whose key has been rotated to the key of the signer
+    pub fn with_account(mut self, account: PublicKeyHash) -> Self {
+        self.account = Some(account);
+        self
+    }
+
+    /// Returns the L2 account of the signer
+    pub fn public_key_hash(&self) -> PublicKeyHash {
+        match &self.account {
+            Some(account) => account.clone(),
+            None => PublicKeyHash::from(&self.public_key),
+        }
+    }
+
+    /// Signs raw bytes, they are hashed with blake2b before being signed
+    pub fn sign_bytes(&self, data: &[u8]) -> Result<Signature> {
+        let signature = self.secret_key.sign([data]).map_err(|_| Error::Signing)?;
+        let signature = Ed25519Signature::try_from(signature.as_ref().to_vec())
+            .map_err(|_| Error::InvalidSignature)?;
+        Ok(Signature::Ed25519(signature))
+    }
+
+    /// Signs the inner and attaches the signature, the public key and the timestamp to it
+    /// The account signed for is set in the inner
+    pub fn sign<C: Content>(
+        &self,
+        mut inner: Inner<C>,
+        timestamp: &str,
+    ) -> Result<SignedMessage<C>> {
+        if self.account.is_some() {
+            inner.account = self.account.clone();
+        }
+        let signature = self.sign_bytes(&inner.signing_payload(timestamp))?;
+
+        Ok(SignedMessage {
+            pkey: self.public_key.clone(),
+            signature,
+            timestamp: timestamp.to_string(),
+            inner,
+        })
+    }
+
+    /// Signs the inner of a multisig message, without attaching the signature to it
+    pub fn cosign<C: Content>(
+        &self,
+        inner: &Inner<C>,
+        timestamp: &str,
+    ) -> Result<MultisigSignature> {
+        let signature = self.sign_bytes(&inner.signing_payload(timestamp))?;
+
+        Ok(MultisigSignature {
+            pkey: self.public_key.clone(),
+            signature,
+            timestamp: timestamp.to_string(),
+        })
+    }
+}
@@ -0,0 +1,658 @@
+//! Cost of each kind of message processed by the kernel
+//!
+//! The benchmark is a sequence of levels: each operation has a level preparing its state,
+//! with fresh accounts, followed by the level of the measured message.
+//! The sequence runs on the mock host, which gives the time and the durable storage bytes
+//! written by each measured level, and the outbox bytes it writes for L1.
+//!
+//! Ticks can only be counted by the PVM: the same sequence is written at bench time to
+//! `target/bench/inputs.json` for the WASM debugger, see `make bench-ticks`.
+//! The level column gives the level of the inputs of each operation.
+
+#[path = "../tests/common/mod.rs"]
+mod common;
+
+use std::fs;
+use std::path::PathBuf;
+use std::time::{Duration, Instant};
+
+use common::*;
+use kernel::constants::L1_BRIDGE_CONTRACT_ADDRESS;
+use kernel::core::book::Side;
+use kernel::core::message::{
+    AdminContent, DepositAction, Message, OpenStreamContent, OperationKind, OrderContent,
+    PoolContent, SwapMessage, VestContent,
+};
+use kernel::core::public_key_hash::PublicKeyHash;
+use kernel::core::token::Token;
+use kernel_client::{deposit_action, to_external_hex};
+use tezos_crypto_rs::hash::{ContractKt1Hash, ContractTz1Hash, SeedEd25519};
+use tezos_smart_rollup::host::Runtime;
+use tezos_smart_rollup::storage::path::{concat, OwnedPath};
+use tezos_smart_rollup_mock::MockHost;
+
+const SAMPLES: u32 = 100;
+const OTHER_TOKEN: [u8; 2] = [0x56, 0x78];
+const CONTRACT: &str = "KT1PWx2mnDueood7fEmfbBDKx1D9BAnnXitn";
+/// Top level paths of the durable storage written by the kernel
+const DURABLE_PATHS: [&str; 17] = [
+    "/ledger",
+    "/tez",
+    "/nonce",
+    "/lane",
+    "/frozen",
+    "/paused",
+    "/allowance",
+    "/operator",
+    "/htlc",
+    "/pool",
+    "/vesting",
+    "/stream",
+    "/session",
+    "/pubkey",
+    "/book",
+    "/receipts",
+    "/pending",
+];
+
+enum Input {
+    /// Ticket of the bridge credited to the account, with an optional action
+    Deposit {
+        account: String,
+        token: Vec<u8>,
+        amount: u128,
+        action: Option<DepositAction>,
+    },
+    /// Ticket of the bridge naming a contract, which is refunded
+    DepositToContract {
+        amount: u128,
+    },
+    Message(Box<Message>),
+}
+
+struct Level {
+    /// Name of the measured operation, None for the levels preparing the state
+    name: Option<&'static str>,
+    inputs: Vec<Input>,
+}
+
+struct Bench {
+    levels: Vec<Level>,
+    keys: u8,
+    /// The identifiers of the orders are given by a counter of the kernel
+    orders: u64,
+}
+
+impl Bench {
+    /// Returns new secret keys, so that each operation starts from fresh accounts
+    fn keys<const N: usize>(&mut self) -> [String; N] {
+        [(); N].map(|_| {
+            self.keys += 1;
+            SeedEd25519::try_from(vec![self.keys; 32])
+                .unwrap()
+                .to_base58_check()
+        })
+    }
+
+    fn setup(&mut self, inputs: Vec<Input>) {
+        self.levels.push(Level { name: None, inputs });
+    }
+
+    fn measure(&mut self, name: &'static str, inputs: Vec<Input>) {
+        self.levels.push(Level {
+            name: Some(name),
+            inputs,
+        });
+    }
+
+    fn next_order(&mut self) -> u64 {
+        self.orders += 1;
+        self.orders - 1
+    }
+}
+
+fn deposit(secret_key: &str, token: &[u8], amount: u128) -> Input {
+    Input::Deposit {
+        account: address(secret_key),
+        token: token.to_vec(),
+        amount,
+        action: None,
+    }
+}
+
+fn message(message: Message) -> Input {
+    Input::Message(Box::new(message))
+}
+
+fn pkh(secret_key: &str) -> PublicKeyHash {
+    PublicKeyHash::from_b58(&address(secret_key)).unwrap()
+}
+
+fn token() -> Token {
+    Token::Bytes(TOKEN.to_vec())
+}
+
+fn other_token() -> Token {
+    Token::Bytes(OTHER_TOKEN.to_vec())
+}
+
+/// Builds the levels of every kind of message
+fn operations() -> Bench {
+    let mut bench = Bench {
+        levels: Vec::new(),
+        keys: 0,
+        orders: 0,
+    };
+
+    bench.measure("empty level", vec![]);
+
+    let [a, b] = bench.keys();
+    bench.measure("deposit (new account)", vec![deposit(&a, &TOKEN, 1_000)]);
+    bench.measure("deposit", vec![deposit(&a, &TOKEN, 1_000)]);
+    let action = DepositAction::Transfer {
+        destination: pkh(&b),
+    };
+    bench.measure(
+        "deposit and transfer",
+        vec![Input::Deposit {
+            account: address(&a),
+            token: TOKEN.to_vec(),
+            amount: 1_000,
+            action: Some(action),
+        }],
+    );
+    bench.measure(
+        "deposit refunded",
+        vec![Input::DepositToContract { amount: 1_000 }],
+    );
+
+    let [a, b] = bench.keys();
+    bench.setup(vec![deposit(&a, &TOKEN, 1_000_000)]);
+    let transfer = |nonce| signed_transfer(&a, nonce, &TOKEN, &address(&b), 1_000);
+    bench.measure("transfer (new account)", vec![message(transfer(1))]);
+    bench.measure("transfer", vec![message(transfer(2))]);
+    bench.measure(
+        "withdraw",
+        vec![message(signed_withdraw(&a, 3, &TOKEN, &address(&a), 1_000))],
+    );
+
+    let [a, b] = bench.keys();
+    bench.setup(vec![deposit(&a, &TOKEN, 1_000_000)]);
+    bench.measure(
+        "approve",
+        vec![message(signed_approve(&a, 1, &address(&b), &TOKEN, 10_000))],
+    );
+    bench.measure(
+        "transfer from",
+        vec![message(signed_transfer_from(
+            &b,
+            1,
+            &address(&a),
+            &TOKEN,
+            &address(&b),
+            1_000,
+        ))],
+    );
+    bench.measure(
+        "update operators",
+        vec![message(signed_update_operator(
+            &a,
+            2,
+            &address(&b),
+            &TOKEN,
+            true,
+        ))],
+    );
+
+    let [a, b] = bench.keys();
+    let (lock, id) = signed_lock(&a, 1, &TOKEN, 1_000, &address(&b), b"secret", u32::MAX);
+    bench.setup(vec![deposit(&a, &TOKEN, 1_000_000)]);
+    bench.measure("htlc lock", vec![message(lock)]);
+    bench.measure(
+        "htlc claim",
+        vec![message(signed_claim(&b, 1, &id, b"secret"))],
+    );
+
+    let [a, b] = bench.keys();
+    bench.setup(vec![
+        deposit(&a, &TOKEN, 1_000_000),
+        deposit(&b, &OTHER_TOKEN, 1_000_000),
+    ]);
+    let content = || {
+        swap_content(
+            &address(&a),
+            &TOKEN,
+            1_000,
+            &address(&b),
+            &OTHER_TOKEN,
+            2_000,
+            u32::MAX,
+        )
+    };
+    let swap = SwapMessage {
+        first: signed_swap_leg(&a, 1, content()),
+        second: signed_swap_leg(&b, 1, content()),
+    };
+    bench.measure("swap", vec![message(Message::Swap(Box::new(swap)))]);
+
+    let [a, b] = bench.keys();
+    bench.setup(vec![
+        deposit(&a, &TOKEN, 1_000_000),
+        deposit(&a, &OTHER_TOKEN, 1_000_000),
+        deposit(&b, &TOKEN, 1_000_000),
+    ]);
+    let create = PoolContent::CreatePool {
+        token_a: token(),
+        amount_a: 100_000,
+        token_b: other_token(),
+        amount_b: 400_000,
+        fee: 30,
+    };
+    bench.measure("pool create", vec![message(signed_pool(&a, 1, create))]);
+    let add = PoolContent::AddLiquidity {
+        token_a: token(),
+        max_a: 10_000,
+        token_b: other_token(),
+        max_b: 40_000,
+        shares: 10_000,
+        deadline_level: u32::MAX,
+    };
+    bench.measure("pool add liquidity", vec![message(signed_pool(&a, 2, add))]);
+    let remove = PoolContent::RemoveLiquidity {
+        token_a: token(),
+        min_a: 0,
+        token_b: other_token(),
+        min_b: 0,
+        shares: 10_000,
+        deadline_level: u32::MAX,
+    };
+    bench.measure(
+        "pool remove liquidity",
+        vec![message(signed_pool(&a, 3, remove))],
+    );
+    let swap = PoolContent::Swap {
+        token_in: token(),
+        amount_in: 1_000,
+        token_out: other_token(),
+        min_out: 0,
+        deadline_level: u32::MAX,
+    };
+    bench.measure("pool swap", vec![message(signed_pool(&b, 1, swap))]);
+
+    let [a, b] = bench.keys();
+    bench.setup(vec![
+        deposit(&a, &TOKEN, 1_000_000),
+        deposit(&b, &OTHER_TOKEN, 1_000_000),
+    ]);
+    let place = |side, price| OrderContent::Place {
+        base: token(),
+        quote: other_token(),
+        side,
+        price,
+        amount: 1_000,
+    };
+    // Every placed order is given an identifier, even when it is filled right away
+    bench.next_order();
+    bench.measure(
+        "order place",
+        vec![message(signed_order(&a, 1, place(Side::Sell, 2)))],
+    );
+    bench.next_order();
+    bench.measure(
+        "order fill",
+        vec![message(signed_order(&b, 1, place(Side::Buy, 2)))],
+    );
+    let id = bench.next_order();
+    bench.setup(vec![message(signed_order(&a, 2, place(Side::Sell, 3)))]);
+    bench.measure(
+        "order cancel",
+        vec![message(signed_order(&a, 3, OrderContent::Cancel { id }))],
+    );
+
+    let [a, b] = bench.keys();
+    let vest = VestContent {
+        beneficiary: pkh(&b),
+        token: token(),
+        total: 10_000,
+        start_level: 0,
+        cliff_level: 0,
+        end_level: 1,
+    };
+    let (vest, id) = signed_vest(&a, 1, vest);
+    bench.setup(vec![deposit(&a, &TOKEN, 1_000_000)]);
+    bench.measure("vest", vec![message(vest)]);
+    bench.measure(
+        "claim vested",
+        vec![message(signed_claim_vested(&b, 1, &id))],
+    );
+
+    let [a, b] = bench.keys();
+    let open = OpenStreamContent {
+        recipient: pkh(&b),
+        token: token(),
+        rate_per_level: 10,
+        deposit: 10_000,
+    };
+    let (open, id) = signed_open_stream(&a, 1, open);
+    bench.setup(vec![deposit(&a, &TOKEN, 1_000_000)]);
+    bench.measure("stream open", vec![message(open)]);
+    bench.measure(
+        "stream withdraw",
+        vec![message(signed_withdraw_stream(&b, 1, &id))],
+    );
+    bench.measure(
+        "stream cancel",
+        vec![message(signed_cancel_stream(&a, 2, &id))],
+    );
+
+    let [a, b, relayer] = bench.keys();
+    bench.setup(vec![
+        deposit(&a, &TOKEN, 1_000_000),
+        deposit(&relayer, &TOKEN, 1_000_000),
+    ]);
+    let transfer = signed_transfer(&a, 1, &TOKEN, &address(&b), 1_000);
+    bench.measure(
+        "sponsored transfer",
+        vec![message(sponsored_with_fee(
+            &relayer, 1, transfer, &TOKEN, 10,
+        ))],
+    );
+
+    let [a, b, session] = bench.keys();
+    bench.setup(vec![deposit(&a, &TOKEN, 1_000_000)]);
+    bench.measure(
+        "authorize session key",
+        vec![message(signed_authorize_session_key(
+            &a,
+            1,
+            &address(&session),
+            &TOKEN,
+            10_000,
+            u32::MAX,
+        ))],
+    );
+    bench.measure(
+        "session transfer",
+        vec![message(signed_session_transfer(
+            &session,
+            &address(&a),
+            2,
+            &TOKEN,
+            &address(&b),
+            1_000,
+        ))],
+    );
+
+    let [a, b, new_key] = bench.keys();
+    bench.setup(vec![deposit(&a, &TOKEN, 1_000_000)]);
+    bench.measure(
+        "rotate key",
+        vec![message(signed_rotate_key(&a, None, 1, &new_key))],
+    );
+    bench.measure(
+        "transfer (rotated key)",
+        vec![message(signed_transfer_for(
+            &new_key,
+            &address(&a),
+            2,
+            &TOKEN,
+            &address(&b),
+            1_000,
+        ))],
+    );
+
+    let [a, b, c] = bench.keys();
+    let keys = [a.as_str(), b.as_str(), c.as_str()];
+    bench.setup(vec![Input::Deposit {
+        account: multisig_address(&keys, 2),
+        token: TOKEN.to_vec(),
+        amount: 1_000_000,
+        action: None,
+    }]);
+    let transfer = multisig_transfer(&TOKEN, &address(&c), 1_000);
+    bench.measure(
+        "multisig transfer (2 of 3)",
+        vec![message(signed_multisig(&keys, 2, &keys[..2], 1, transfer))],
+    );
+
+    // The admin is set in the mock host only, the PVM rejects its message unless /admin is
+    // written when the kernel is installed
+    bench.measure(
+        "admin pause",
+        vec![message(signed_admin(
+            CAROL,
+            1,
+            AdminContent::Pause(OperationKind::Deposit),
+        ))],
+    );
+    bench
+}
+
+fn add_input(host: &mut MockHost, input: &Input) {
+    match input {
+        Input::Deposit {
+            account,
+            token,
+            amount,
+            action: None,
+        } => add_deposit(host, account, token, *amount),
+        Input::Deposit {
+            account,
+            token,
+            amount,
+            action: Some(action),
+        } => add_deposit_with_action(host, account, token, *amount, action),
+        Input::DepositToContract { amount } => {
+            add_deposit_to_contract(host, &address(ALICE), CONTRACT, &TOKEN, *amount)
+        }
+        Input::Message(message) => add_message(host, message),
+    }
+}
+
+/// Collects the values under the path
+fn collect_values(host: &MockHost, path: &OwnedPath, values: &mut Vec<(OwnedPath, Vec<u8>)>) {
+    if host.store_has(path).unwrap().is_none() {
+        return;
+    }
+    if let Ok(size) = host.store_value_size(path) {
+        // Values are read by chunks of the maximal size of a read
+        let mut value = Vec::with_capacity(size);
+        while value.len() < size {
+            let max = (size - value.len()).min(2048);
+            value.extend(host.store_read(path, value.len(), max).unwrap());
+        }
+        values.push((path.clone(), value));
+    }
+    let subkeys = host.store_count_subkeys(path).unwrap_or_default();
+    for index in 0..subkeys as i64 {
+        if let Ok(subkey) = host.store_get_subkey(path, index) {
+            collect_values(host, &concat(path, &subkey).unwrap(), values);
+        }
+    }
+}
+
+/// Values written by the kernel, the level excepted
+fn durable_values(host: &MockHost) -> Vec<(OwnedPath, Vec<u8>)> {
+    let mut values = Vec::new();
+    for path in DURABLE_PATHS {
+        let path = OwnedPath::try_from(path.as_bytes().to_vec()).unwrap();
+        collect_values(host, &path, &mut values);
+    }
+    values
+}
+
+fn durable_size(values: &[(OwnedPath, Vec<u8>)]) -> isize {
+    values.iter().map(|(_, value)| value.len() as isize).sum()
+}
+
+struct Cost {
+    time: Duration,
+    storage: isize,
+    outbox: usize,
+}
+
+/// Runs the levels on the mock host, the cost of each level is averaged over the samples
+fn measure(bench: &Bench) -> Vec<Cost> {
+    let mut costs: Vec<Cost> = bench
+        .levels
+        .iter()
+        .map(|_| Cost {
+            time: Duration::ZERO,
+            storage: 0,
+            outbox: 0,
+        })
+        .collect();
+
+    for _ in 0..SAMPLES {
+        let mut host = MockHost::default();
+        set_admin(&mut host, CAROL);
+        for (level, cost) in bench.levels.iter().zip(costs.iter_mut()) {
+            for input in level.inputs.iter() {
+                add_input(&mut host, input);
+            }
+
+            let before = durable_values(&host);
+            let start = Instant::now();
+            let index = run_level(&mut host);
+            cost.time += start.elapsed();
+
+            let after = durable_values(&host);
+            let outbox = host.outbox_at(index).iter().map(Vec::len).sum::<usize>();
+            // A rejected message would only measure the cost of its rejection
+            if let Some(name) = level.name.filter(|_| !level.inputs.is_empty()) {
+                assert!(before != after || outbox > 0, "{} is rejected", name);
+            }
+            cost.storage += durable_size(&after) - durable_size(&before);
+            cost.outbox += outbox;
+        }
+    }
+
+    for cost in costs.iter_mut() {
+        cost.time /= SAMPLES;
+        cost.storage /= SAMPLES as isize;
+        cost.outbox /= SAMPLES as usize;
+    }
+    costs
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+/// Binary representation of an originated contract, as expected by the debugger
+fn contract_bytes(address: &str) -> String {
+    let kt1 = ContractKt1Hash::from_base58_check(address).unwrap();
+    format!("01{}00", hex(kt1.as_ref()))
+}
+
+/// Binary representation of an implicit account, as expected by the debugger
+fn implicit_bytes(address: &str) -> String {
+    let tz1 = ContractTz1Hash::from_base58_check(address).unwrap();
+    format!("0000{}", hex(tz1.as_ref()))
+}
+
+/// Input of the WASM debugger, the deposits are written as Michelson
+fn debugger_input(input: &Input) -> String {
+    let ticket = |token: &[u8], amount: u128| {
+        format!(
+            "Pair 0x{} (Pair 0x{} {})",
+            contract_bytes(L1_BRIDGE_CONTRACT_ADDRESS),
+            hex(token),
+            amount
+        )
+    };
+    let transfer = |payload: String| {
+        format!(
+            "{{ \"payload\": \"{}\", \"sender\": \"{}\" }}",
+            payload, L1_BRIDGE_CONTRACT_ADDRESS
+        )
+    };
+    match input {
+        Input::Deposit {
+            account,
+            token,
+            amount,
+            action: None,
+        } => transfer(format!(
+            "Pair ({}) 0x{}",
+            ticket(token, *amount),
+            implicit_bytes(account)
+        )),
+        Input::Deposit {
+            account,
+            token,
+            amount,
+            action: Some(action),
+        } => transfer(format!(
+            "Pair ({}) (Pair 0x{} 0x{})",
+            ticket(token, *amount),
+            implicit_bytes(account),
+            hex(&deposit_action(action).unwrap())
+        )),
+        Input::DepositToContract { amount } => transfer(format!(
+            "Pair ({}) 0x{}",
+            ticket(&TOKEN, *amount),
+            contract_bytes(CONTRACT)
+        )),
+        Input::Message(message) => {
+            format!(
+                "{{ \"external\": \"{}\" }}",
+                to_external_hex(message).unwrap()
+            )
+        }
+    }
+}
+
+/// Writes the inputs of the WASM debugger and the commands running each of their levels
+fn write_debugger_inputs(bench: &Bench) -> PathBuf {
+    let levels: Vec<String> = bench
+        .levels
+        .iter()
+        .map(|level| {
+            let inputs: Vec<String> = level.inputs.iter().map(debugger_input).collect();
+            match inputs.is_empty() {
+                true => "  []".to_string(),
+                false => format!("  [\n    {}\n  ]", inputs.join(",\n    ")),
+            }
+        })
+        .collect();
+
+    let dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../target/bench");
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(
+        dir.join("inputs.json"),
+        format!("[\n{}\n]\n", levels.join(",\n")),
+    )
+    .unwrap();
+    fs::write(
+        dir.join("commands"),
+        "load inputs\nstep inbox\n".repeat(bench.levels.len()),
+    )
+    .unwrap();
+    dir
+}
+
+fn main() {
+    let bench = operations();
+    let costs = measure(&bench);
+
+    println!(
+        "{:<28} {:>6} {:>12} {:>12} {:>12}",
+        "operation", "level", "time", "storage", "outbox"
+    );
+    for (index, (level, cost)) in bench.levels.iter().zip(costs).enumerate() {
+        if let Some(name) = level.name {
+            println!(
+                "{:<28} {:>6} {:>12.1?} {:>10} B {:>10} B",
+                name,
+                index + 1,
+                cost.time,
+                cost.storage,
+                cost.outbox
+            );
+        }
+    }
+
+    let dir = write_debugger_inputs(&bench);
+    println!("\nInputs of the WASM debugger written to {}", dir.display());
+}
@@ -0,0 +1,30 @@
+#![no_main]
+
+use kernel::core::error::Error;
+use kernel::core::message::{BridgeMessage, DepositPayload, TicketDeposit, TicketTransfer};
+use libfuzzer_sys::fuzz_target;
+use tezos_smart_rollup::michelson::{
+    MichelsonBytes, MichelsonNat, MichelsonOption, MichelsonPair, MichelsonString, MichelsonUnit,
+};
+
+fn bridge_message<Payload: TicketTransfer>(data: &[u8])
+where
+    BridgeMessage: TryFrom<Payload, Error = Error>,
+{
+    if let Ok((_, payload)) = Payload::nom_read(data) {
+        let _ = BridgeMessage::try_from(payload);
+    }
+}
+
+// Micheline payloads of the ticket transfers accepted by parse_input: the deposits of the bridge,
+// with their action, and the tickets of every content type the kernel decodes
+fuzz_target!(|data: &[u8]| {
+    bridge_message::<DepositPayload>(data);
+    bridge_message::<TicketDeposit<MichelsonBytes>>(data);
+    bridge_message::<TicketDeposit<MichelsonUnit>>(data);
+    bridge_message::<TicketDeposit<MichelsonString>>(data);
+    bridge_message::<TicketDeposit<MichelsonNat>>(data);
+    bridge_message::<TicketDeposit<MichelsonPair<MichelsonNat, MichelsonOption<MichelsonBytes>>>>(
+        data,
+    );
+});
@@ -0,0 +1,8 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+
+// Raw inbox messages: internal transfers and external messages
+fuzz_target!(|data: &[u8]| {
+    let _ = kernel::parse_input(0, data);
+});
@@ -0,0 +1,22 @@
+#![no_main]
+
+use kernel::core::public_key::PublicKey;
+use kernel::core::signature::Signature;
+use libfuzzer_sys::fuzz_target;
+use tezos_crypto_rs::hash::{Ed25519Signature, PublicKeyEd25519};
+
+// Input is the public key (32 bytes), the signature (64 bytes) and the signed message
+fuzz_target!(|data: &[u8]| {
+    if data.len() < 96 {
+        return;
+    }
+
+    let (pkey, rest) = data.split_at(32);
+    let (signature, message) = rest.split_at(64);
+    let pkey = PublicKeyEd25519::try_from(pkey.to_vec()).map(PublicKey::Ed25519);
+    let signature = Ed25519Signature::try_from(signature.to_vec()).map(Signature::Ed25519);
+
+    if let (Ok(pkey), Ok(signature)) = (pkey, signature) {
+        let _ = signature.verify(&pkey, message);
+    }
+});
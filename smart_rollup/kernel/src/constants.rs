@@ -4,3 +4,24 @@ pub const TEZOS_SIGNED_MESSAGE: &str = "Tezos Signed Message:";
 pub const DAPP_URL: &str = "https://rollup.plenty.network";
 pub const MICHELINE_EXPRESSION_BYTE: &str = "05";
 pub const MICHELINE_STRING_BYTE: &str = "01";
+pub const ADMIN_ADDRESS: &str = "tz1R1YEHNEjF8qY9uLwyCUbUrczP6VVa9qdp";
+pub const L1_RELEASE_ENTRYPOINT: &str = "release";
+/// Entrypoint of the L1 accounts receiving the tickets returned as is
+pub const L1_DEFAULT_ENTRYPOINT: &str = "default";
+/// Prefix of the tokens minted by the kernel, packed L1 tokens start with 0x05
+pub const SYNTHETIC_TOKEN_PREFIX: &[u8] = b"lp:";
+/// Bridge wrapping XTZ in unit tickets, which releases the tez in exchange of the tickets
+/// Originated from contracts/michelson/tez_bridge.tz, a ticket stands for 1 mutez
+pub const L1_TEZ_BRIDGE_CONTRACT_ADDRESS: &str = "KT1Ms8K7ab3eYFeQWwicznMcQQ16TrDJ71rg";
+/// Token of the tez deposited through the tez bridge
+pub const TEZ_TOKEN: &str = "tez";
+/// Deposits queued during a pause which are credited at each level, once deposits are resumed
+pub const MAX_PENDING_DEPOSITS_PER_LEVEL: u32 = 50;
+/// Resting orders an incoming order is matched against, the orders of frozen makers included
+pub const MAX_ORDER_MATCHES: usize = 32;
+/// Prices of a side of a market, read at once: 48 prices of 39 digits fit in a read of 2048 bytes
+pub const MAX_PRICE_LEVELS: usize = 48;
+/// Orders resting at a price, read at once: 64 identifiers of 20 digits fit in a read
+pub const MAX_ORDERS_PER_PRICE_LEVEL: usize = 64;
+/// Smallest amount of the base token of an order, so that the book can't be filled with dust
+pub const MIN_ORDER_AMOUNT: u128 = 1_000;
@@ -0,0 +1,160 @@
+use serde::{Deserialize, Serialize};
+
+use crate::core::error::{Error, Result};
+use crate::core::hash::Blake2b;
+use crate::core::public_key_hash::PublicKeyHash;
+use crate::core::token::Token;
+
+#[derive(Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+pub enum Side {
+    /// Buys the base token with the quote token
+    Buy,
+    /// Sells the base token for the quote token
+    Sell,
+}
+
+impl Side {
+    pub fn opposite(&self) -> Side {
+        match self {
+            Side::Buy => Side::Sell,
+            Side::Sell => Side::Buy,
+        }
+    }
+}
+
+impl ToString for Side {
+    fn to_string(&self) -> String {
+        let side = match self {
+            Side::Buy => "buy",
+            Side::Sell => "sell",
+        };
+        side.to_string()
+    }
+}
+
+/// Identifier of the market of the base token priced in the quote token
+pub fn market_id(base: &Token, quote: &Token) -> String {
+    let data = format!("market{}:{}", base.to_string(), quote.to_string());
+    Blake2b::from(data.as_bytes()).to_string()
+}
+
+/// Account escrowing the funds of the orders resting in the market
+pub fn market_account(market: &str) -> Result<PublicKeyHash> {
+    PublicKeyHash::derived(&format!("escrow{}", market))
+}
+
+/// Limit order, the part which is not filled rests in the book
+#[derive(Deserialize, Serialize)]
+pub struct Order {
+    pub owner: PublicKeyHash,
+    pub base: Token,
+    pub quote: Token,
+    pub side: Side,
+    /// Units of the quote token for one unit of the base token
+    pub price: u128,
+    /// Units of the base token not filled yet
+    pub remaining: u128,
+}
+
+impl Order {
+    pub fn market(&self) -> String {
+        market_id(&self.base, &self.quote)
+    }
+
+    /// Returns true if the order can be filled at the price of a resting order
+    pub fn crosses(&self, price: u128) -> bool {
+        match self.side {
+            Side::Buy => price <= self.price,
+            Side::Sell => price >= self.price,
+        }
+    }
+
+    /// Token and amount escrowed for the remaining part of the order
+    pub fn escrow(&self) -> Result<(&Token, u128)> {
+        match self.side {
+            Side::Buy => Ok((&self.quote, quote_amount(self.remaining, self.price)?)),
+            Side::Sell => Ok((&self.base, self.remaining)),
+        }
+    }
+}
+
+/// Amount of the quote token paid for an amount of the base token
+pub fn quote_amount(amount: u128, price: u128) -> Result<u128> {
+    amount.checked_mul(price).ok_or(Error::InvalidAmount)
+}
+
+/// Inserts a price level in the levels of a side, best price first
+pub fn insert_price(prices: &mut Vec<u128>, side: Side, price: u128) {
+    if prices.contains(&price) {
+        return;
+    }
+    let index = prices
+        .iter()
+        .position(|level| match side {
+            Side::Buy => *level < price,
+            Side::Sell => *level > price,
+        })
+        .unwrap_or(prices.len());
+    prices.insert(index, price);
+}
+
+/// Receipt of the fill of a resting order by an incoming one, at the price of the resting order
+#[derive(Deserialize, Serialize)]
+pub struct Fill {
+    pub maker_order: u64,
+    pub taker_order: u64,
+    pub maker: PublicKeyHash,
+    pub taker: PublicKeyHash,
+    pub base: Token,
+    pub quote: Token,
+    pub taker_side: Side,
+    pub price: u128,
+    pub amount: u128,
+    pub level: u32,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{insert_price, Order, Side};
+    use crate::core::public_key_hash::PublicKeyHash;
+    use crate::core::token::Token;
+
+    #[test]
+    fn test_insert_price() {
+        let mut bids = vec![];
+        for price in [10, 30, 20, 30] {
+            insert_price(&mut bids, Side::Buy, price);
+        }
+        assert_eq!(vec![30, 20, 10], bids);
+
+        let mut asks = vec![];
+        for price in [10, 30, 20, 10] {
+            insert_price(&mut asks, Side::Sell, price);
+        }
+        assert_eq!(vec![10, 20, 30], asks);
+    }
+
+    #[test]
+    fn test_escrow() {
+        let order = |side| Order {
+            owner: PublicKeyHash::from_b58("tz1Pe4aBjsW9ZGWaFXa47megxFD1LGGFAW3C").unwrap(),
+            base: Token::Bytes(vec![0x12]),
+            quote: Token::Bytes(vec![0x34]),
+            side,
+            price: 3,
+            remaining: 10,
+        };
+
+        let buy = order(Side::Buy);
+        let (token, amount) = buy.escrow().unwrap();
+        assert!(token == &Token::Bytes(vec![0x34]));
+        assert_eq!(30, amount);
+        assert!(buy.crosses(3) && buy.crosses(2) && !buy.crosses(4));
+
+        let sell = order(Side::Sell);
+        let (token, amount) = sell.escrow().unwrap();
+        assert!(token == &Token::Bytes(vec![0x12]));
+        assert_eq!(10, amount);
+        assert!(sell.crosses(3) && sell.crosses(4) && !sell.crosses(2));
+    }
+}
@@ -5,8 +5,6 @@ use tezos_smart_rollup::{host::RuntimeError, storage::path};
 pub enum ReadInputError {
     // Input message is not for the kernel
     NotForKernel,
-    // L1 transaction has not come from the token bridge
-    NotFromBridge,
     /// There is no more messages
     EndOfInbox,
     /// There is an error in the bytes to string deserialization
@@ -15,6 +13,8 @@ pub enum ReadInputError {
     SerdeJson(serde_json_wasm::de::Error),
     /// There is an error runtime
     Runtime(RuntimeError),
+    /// The ticket sent by the bridge cannot be credited
+    InvalidDeposit(Error),
 }
 
 /// Represents all the error of the kernel
@@ -30,6 +30,51 @@ pub enum Error {
     StateDeserializarion,
     BinError(tezos_data_encoding::enc::BinError),
     InvalidTransferAmount,
+    StateSerialization,
+    NotAdmin,
+    Paused,
+    OutboxMessage,
+    AccountFrozen,
+    InvalidAmount,
+    UnsafeAllowanceChange,
+    NotEnoughAllowance,
+    HtlcNotFound,
+    HtlcExpired,
+    HtlcNotExpired,
+    InvalidPreimage,
+    InvalidTimeout,
+    InvalidSwap,
+    SwapExpired,
+    InvalidPool,
+    PoolExists,
+    PoolNotFound,
+    Slippage,
+    DeadlineReached,
+    NotWithdrawable,
+    InvalidOrder,
+    OrderBelowMinimum,
+    OrderBookFull,
+    TooManyMatches,
+    OrderNotFound,
+    NotOrderOwner,
+    VestingNotFound,
+    InvalidSchedule,
+    NothingVested,
+    InvalidStream,
+    StreamNotFound,
+    NotStreamParty,
+    NothingStreamed,
+    InvalidSponsor,
+    NotSessionKey,
+    SessionKeyExpired,
+    SessionLimitReached,
+    KeyRotated,
+    InvalidMultisig,
+    NotEnoughSignatures,
+    HtlcExists,
+    InvalidTicket,
+    InvalidTicketer,
+    TicketerNotAllowed,
 }
 
 impl ToString for Error {
@@ -44,6 +89,51 @@ impl ToString for Error {
             Error::StateDeserializarion => "State deserialization",
             Error::BinError(_) => "Cannot serialize michelson to binary",
             Error::InvalidTransferAmount => "Transferring more than the actual balance",
+            Error::StateSerialization => "State serialization",
+            Error::NotAdmin => "Message is not signed by the admin",
+            Error::Paused => "Operation is paused",
+            Error::OutboxMessage => "Cannot build the outbox message",
+            Error::AccountFrozen => "Account is frozen",
+            Error::InvalidAmount => "Amount does not fit in 128 bits",
+            Error::UnsafeAllowanceChange => "Allowance has to be set to 0 before being changed",
+            Error::NotEnoughAllowance => "Transferring more than the allowance",
+            Error::HtlcNotFound => "Lock does not exist",
+            Error::HtlcExpired => "Lock has reached its timeout level",
+            Error::HtlcNotExpired => "Lock has not reached its timeout level",
+            Error::InvalidPreimage => "Preimage does not match the hashlock",
+            Error::InvalidTimeout => "Timeout level is already reached",
+            Error::InvalidSwap => "Legs of the swap do not match",
+            Error::SwapExpired => "Swap has reached its expiry level",
+            Error::InvalidPool => "Invalid pool",
+            Error::PoolExists => "Pool already exists",
+            Error::PoolNotFound => "Pool does not exist",
+            Error::Slippage => "Amounts are beyond the accepted slippage",
+            Error::DeadlineReached => "Deadline level is reached",
+            Error::NotWithdrawable => "Token minted by the kernel cannot be withdrawn",
+            Error::InvalidOrder => "Invalid order",
+            Error::OrderBelowMinimum => "Order amount is below the minimum",
+            Error::OrderBookFull => "Price level or side of the book is full",
+            Error::TooManyMatches => "Order crosses more resting orders than can be matched",
+            Error::OrderNotFound => "Order does not exist",
+            Error::NotOrderOwner => "Order is not owned by the signer",
+            Error::VestingNotFound => "Vesting schedule does not exist",
+            Error::InvalidSchedule => "Levels of the vesting schedule are not ordered",
+            Error::NothingVested => "No vested tokens to claim",
+            Error::InvalidStream => "Rate and deposit of the stream cannot be 0",
+            Error::StreamNotFound => "Stream does not exist",
+            Error::NotStreamParty => "Stream can only be cancelled by its sender or its recipient",
+            Error::NothingStreamed => "No streamed tokens to withdraw",
+            Error::InvalidSponsor => "Sponsor does not commit to the wrapped message",
+            Error::NotSessionKey => "Signer is not a session key of the source",
+            Error::SessionKeyExpired => "Session key has reached its expiry level",
+            Error::SessionLimitReached => "Transferring more than the limit of the session key",
+            Error::KeyRotated => "Key is not allowed to sign for the account",
+            Error::InvalidMultisig => "Keys or threshold of the multisig are invalid",
+            Error::NotEnoughSignatures => "Signed by less keys than the threshold of the multisig",
+            Error::HtlcExists => "Lock already exists",
+            Error::InvalidTicket => "Content of the ticket is not supported",
+            Error::InvalidTicketer => "Ticketer is not a KT1 address",
+            Error::TicketerNotAllowed => "Tickets of the ticketer are not allowed",
         };
         err.to_string()
     }
@@ -0,0 +1,111 @@
+use serde::{Deserialize, Serialize};
+
+use crate::core::hash::Blake2b;
+use crate::core::message::{Content, Inner};
+use crate::core::public_key_hash::PublicKeyHash;
+use crate::core::token::Token;
+
+/// Tokens escrowed by a lock, until they are claimed by the recipient
+/// or refunded to the sender once the timeout level is reached
+#[derive(Deserialize, Serialize)]
+pub struct Htlc {
+    pub sender: PublicKeyHash,
+    pub recipient: PublicKeyHash,
+    pub token: Token,
+    pub amount: u128,
+    /// Blake2b hash of the preimage, as the BLAKE2B instruction of Michelson
+    pub hashlock: Vec<u8>,
+    pub timeout_level: u32,
+}
+
+impl Htlc {
+    /// Returns true if the hash of the preimage is the hashlock
+    pub fn is_unlocked_by(&self, preimage: &[u8]) -> bool {
+        Blake2b::from(preimage).as_ref() == self.hashlock.as_slice()
+    }
+}
+
+/// Identifier of the lock, the hash of its sender and of its signed hash, in hex
+pub fn htlc_id<C: Content>(sender: &PublicKeyHash, inner: &Inner<C>) -> String {
+    let data = format!("{}{}", sender.to_string(), inner.hash().to_string());
+    Blake2b::from(data.as_bytes()).to_string()
+}
+
+/// Identifier of a lock funded by a deposit, which has no signed hash, in hex
+/// It only depends on the lock, the same lock can't be funded twice
+pub fn deposit_lock_id(htlc: &Htlc) -> String {
+    let hashlock: String = htlc
+        .hashlock
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect();
+    let data = format!(
+        "deposit{}{}{}{}{}{}",
+        htlc.sender.to_string(),
+        htlc.recipient.to_string(),
+        htlc.token.to_string(),
+        htlc.amount,
+        hashlock,
+        htlc.timeout_level
+    );
+    Blake2b::from(data.as_bytes()).to_string()
+}
+
+/// Returns true if the string can be the identifier of a lock
+pub fn is_htlc_id(id: &str) -> bool {
+    id.len() == 64
+        && id
+            .chars()
+            .all(|c| c.is_ascii_digit() || ('a'..='f').contains(&c))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{deposit_lock_id, is_htlc_id, Htlc};
+    use crate::core::hash::Blake2b;
+    use crate::core::public_key_hash::PublicKeyHash;
+    use crate::core::token::Token;
+
+    #[test]
+    fn test_is_unlocked_by() {
+        let account = "tz1Pe4aBjsW9ZGWaFXa47megxFD1LGGFAW3C";
+        let htlc = Htlc {
+            sender: PublicKeyHash::from_b58(account).unwrap(),
+            recipient: PublicKeyHash::from_b58(account).unwrap(),
+            token: Token::Bytes(vec![0x12, 0x34]),
+            amount: 10,
+            hashlock: Blake2b::from(b"secret".as_slice()).as_ref().to_vec(),
+            timeout_level: 10,
+        };
+
+        assert!(htlc.is_unlocked_by(b"secret"));
+        assert!(!htlc.is_unlocked_by(b"Secret"));
+    }
+
+    #[test]
+    fn test_deposit_lock_id() {
+        let account = "tz1Pe4aBjsW9ZGWaFXa47megxFD1LGGFAW3C";
+        let mut htlc = Htlc {
+            sender: PublicKeyHash::from_b58(account).unwrap(),
+            recipient: PublicKeyHash::from_b58(account).unwrap(),
+            token: Token::Bytes(vec![0x12, 0x34]),
+            amount: 10,
+            hashlock: Blake2b::from(b"secret".as_slice()).as_ref().to_vec(),
+            timeout_level: 10,
+        };
+
+        let id = deposit_lock_id(&htlc);
+        assert!(is_htlc_id(&id));
+        htlc.timeout_level = 11;
+        assert_ne!(id, deposit_lock_id(&htlc));
+    }
+
+    #[test]
+    fn test_is_htlc_id() {
+        let id = Blake2b::from(b"id".as_slice()).to_string();
+        assert!(is_htlc_id(&id));
+        assert!(!is_htlc_id(&id[1..]));
+        assert!(!is_htlc_id(&id.to_uppercase()));
+        assert!(!is_htlc_id(&format!("{}/{}", &id[..31], &id[32..])));
+    }
+}
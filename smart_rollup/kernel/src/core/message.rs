@@ -1,17 +1,49 @@
+use crate::constants::{
+    DAPP_URL, MICHELINE_EXPRESSION_BYTE, MICHELINE_STRING_BYTE, TEZOS_SIGNED_MESSAGE,
+};
+use crate::core::error::Error;
 use crate::core::hash::Blake2b;
 use crate::core::nonce::Nonce;
 use crate::core::public_key::PublicKey;
 use crate::core::public_key_hash::PublicKeyHash;
 use crate::core::signature::Signature;
 use serde::{Deserialize, Serialize};
-use tezos_smart_rollup::michelson::{ticket::BytesTicket, MichelsonContract, MichelsonPair};
+use tezos_smart_rollup::michelson::{
+    ticket::{BytesTicket, Ticket},
+    Michelson, MichelsonBytes, MichelsonContract, MichelsonNat, MichelsonOption, MichelsonPair,
+    MichelsonString, MichelsonUnit,
+};
 
-use super::token::Token;
+use super::book::Side;
+use super::token::{TicketContent, Token};
 
 #[derive(Deserialize, Serialize)]
 pub enum Message {
+    /// Deposit decoded from a ticket transfer of the L1, it can't be sent as an external message
+    #[serde(skip)]
     Bridge(BridgeMessage),
     Transfer(TransferMessage),
+    Withdraw(WithdrawMessage),
+    Admin(AdminMessage),
+    Approve(ApproveMessage),
+    TransferFrom(TransferFromMessage),
+    UpdateOperators(UpdateOperatorsMessage),
+    Htlc(HtlcMessage),
+    Swap(Box<SwapMessage>),
+    Pool(PoolMessage),
+    Order(OrderMessage),
+    Vesting(VestingMessage),
+    Stream(StreamMessage),
+    Sponsored(Box<SponsoredMessage>),
+    AuthorizeSessionKey(AuthorizeSessionKeyMessage),
+    RotateKey(RotateKeyMessage),
+    Multisig(MultisigMessage),
+    /// Deposit which can't be credited, decoded from a ticket transfer of the L1
+    #[serde(skip)]
+    Refund(RefundMessage),
+    /// Level of the inbox being read, set by the kernel when reading the InfoPerLevel message
+    #[serde(skip)]
+    Level(u32),
 }
 
 #[derive(Deserialize, Serialize)]
@@ -19,48 +51,856 @@ pub struct BridgeMessage {
     pub account: PublicKeyHash,
     pub token: Token,
     pub amount: u128,
+    /// Run with the deposited tokens once they are credited to the account
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub action: Option<DepositAction>,
+    /// L1 account which sent the ticket, the deposit is refunded to it when it can't be credited
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<PublicKeyHash>,
+    /// Set when the action sent alongside the ticket can't be decoded, the deposit is made without it
+    #[serde(skip)]
+    pub invalid_action: bool,
 }
 
-impl From<MichelsonPair<BytesTicket, MichelsonContract>> for BridgeMessage {
-    fn from(michelson_payload: MichelsonPair<BytesTicket, MichelsonContract>) -> Self {
-        BridgeMessage {
-            account: PublicKeyHash::from_b58(michelson_payload.1 .0.to_b58check().as_str())
-                .unwrap(),
-            token: Token::from(&michelson_payload.0.contents().0),
-            amount: michelson_payload.0.amount_as().unwrap(),
+impl BridgeMessage {
+    /// Returns the tickets of the deposit to the L1 account which sent them, if it is known
+    pub fn refund(self) -> Option<RefundMessage> {
+        Some(RefundMessage {
+            destination: self.source?,
+            token: self.token,
+            amount: self.amount,
+        })
+    }
+}
+
+/// Tickets of a deposit which can't be credited, as to a KT1, returned to the L1 account
+/// which sent them
+#[derive(Deserialize, Serialize)]
+pub struct RefundMessage {
+    pub destination: PublicKeyHash,
+    pub token: Token,
+    pub amount: u128,
+}
+
+/// Action run on behalf of the credited account, it can only spend the deposited tokens
+/// The tokens stay on the account when the action fails
+#[derive(Deserialize, Serialize)]
+pub enum DepositAction {
+    /// Forwards the deposit to another L2 account
+    Transfer { destination: PublicKeyHash },
+    /// Sells the deposit for at least min_out of token_out, in the pool of the pair
+    Swap { token_out: Token, min_out: u128 },
+    /// Escrows the deposit until the preimage of the hashlock is revealed, see deposit_lock_id
+    Lock {
+        recipient: PublicKeyHash,
+        hashlock: Vec<u8>,
+        timeout_level: u32,
+    },
+}
+
+/// Deposit naming its L2 destination and an action, as (ticket, (destination, action))
+/// The action is encoded in json, empty bytes for no action
+pub type DepositPayload =
+    MichelsonPair<BytesTicket, MichelsonPair<MichelsonContract, MichelsonBytes>>;
+
+/// Ticket sent to the rollup alongside the L1 account it is credited to
+pub type TicketDeposit<Expr> = MichelsonPair<Ticket<Expr>, MichelsonContract>;
+
+/// Michelson content of the tickets the kernel can decode
+pub trait TicketExpr: Michelson {
+    fn content(&self) -> Option<TicketContent>;
+}
+
+impl TicketExpr for MichelsonUnit {
+    fn content(&self) -> Option<TicketContent> {
+        Some(TicketContent::Unit)
+    }
+}
+
+impl TicketExpr for MichelsonBytes {
+    fn content(&self) -> Option<TicketContent> {
+        Some(TicketContent::Bytes(self.0.clone()))
+    }
+}
+
+impl TicketExpr for MichelsonString {
+    fn content(&self) -> Option<TicketContent> {
+        Some(TicketContent::String(self.0.clone()))
+    }
+}
+
+/// Nats which don't fit in a u128 are not supported
+fn nat_content(nat: &MichelsonNat) -> Option<u128> {
+    nat.as_ref().0.to_string().parse().ok()
+}
+
+impl TicketExpr for MichelsonNat {
+    fn content(&self) -> Option<TicketContent> {
+        nat_content(self).map(TicketContent::Nat)
+    }
+}
+
+impl TicketExpr for MichelsonPair<MichelsonNat, MichelsonOption<MichelsonBytes>> {
+    fn content(&self) -> Option<TicketContent> {
+        let metadata = self.1 .0.as_ref().map(|bytes| bytes.0.clone());
+        nat_content(&self.0).map(|nat| TicketContent::Pair(nat, metadata))
+    }
+}
+
+/// Payload of a ticket transfer to the rollup
+pub trait TicketTransfer: Michelson {
+    /// Token and amount of the transferred ticket, whatever its destination
+    fn ticket(&self) -> Result<(Token, u128), Error>;
+}
+
+fn ticket_token<Expr: TicketExpr>(ticket: &Ticket<Expr>) -> Result<(Token, u128), Error> {
+    let amount = ticket.amount_as().map_err(|_| Error::InvalidAmount)?;
+
+    // Whatever its content, a ticket is identified by its ticketer and its content
+    let content = ticket.contents().content().ok_or(Error::InvalidTicket)?;
+    let token = Token::from_ticket(ticket.creator().0.to_b58check(), content);
+    Ok((token, amount))
+}
+
+impl<Expr: TicketExpr> TicketTransfer for TicketDeposit<Expr> {
+    fn ticket(&self) -> Result<(Token, u128), Error> {
+        ticket_token(&self.0)
+    }
+}
+
+impl TicketTransfer for DepositPayload {
+    fn ticket(&self) -> Result<(Token, u128), Error> {
+        ticket_token(&self.0)
+    }
+}
+
+impl<Expr: TicketExpr> TryFrom<TicketDeposit<Expr>> for BridgeMessage {
+    type Error = Error;
+
+    fn try_from(michelson_payload: TicketDeposit<Expr>) -> Result<Self, Self::Error> {
+        let (token, amount) = michelson_payload.ticket()?;
+        let MichelsonPair(_, MichelsonContract(account)) = michelson_payload;
+
+        // Only implicit accounts can be credited on the rollup
+        let account = PublicKeyHash::from_b58(account.to_b58check().as_str())?;
+
+        Ok(BridgeMessage {
+            account,
+            token,
+            amount,
+            action: None,
+            source: None,
+            invalid_action: false,
+        })
+    }
+}
+
+impl TryFrom<DepositPayload> for BridgeMessage {
+    type Error = Error;
+
+    fn try_from(michelson_payload: DepositPayload) -> Result<Self, Self::Error> {
+        let MichelsonPair(ticket, MichelsonPair(destination, action)) = michelson_payload;
+        let mut message = BridgeMessage::try_from(MichelsonPair(ticket, destination))?;
+
+        // The ticket is already owned by the rollup, an invalid action does not prevent the deposit
+        if !action.0.is_empty() {
+            message.action = serde_json_wasm::from_slice(&action.0).ok();
+            message.invalid_action = message.action.is_none();
         }
+        Ok(message)
     }
 }
 
+/// Content of an inner, that can be hashed and signed by a client
+pub trait Content {
+    /// Encodes the content as it should be hashed alongside the nonce
+    fn encode(&self) -> String;
+}
+
 #[derive(Deserialize, Serialize)]
 pub struct TransferContent {
     pub token: Token,
     pub destination: PublicKeyHash,
     pub amount: u128,
+    /// Account the tokens are debited from, when the signer is one of its session keys
+    /// Omitted when the signer is the account, which keeps the messages signed without it valid
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<PublicKeyHash>,
+}
+
+impl Content for TransferContent {
+    fn encode(&self) -> String {
+        format!(
+            "{}{}{}{}",
+            self.token.to_string(),
+            self.destination.to_string(),
+            self.amount,
+            self.source
+                .as_ref()
+                .map(|source| format!("source{}", source.to_string()))
+                .unwrap_or_default()
+        )
+    }
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct WithdrawContent {
+    pub token: Token,
+    /// L1 account receiving the released tokens
+    pub destination: PublicKeyHash,
+    pub amount: u128,
+}
+
+impl Content for WithdrawContent {
+    fn encode(&self) -> String {
+        format!(
+            "withdraw{}{}{}",
+            self.token.to_string(),
+            self.destination.to_string(),
+            self.amount
+        )
+    }
+}
+
+/// Allows the spender to transfer up to the amount of tokens of the signer
+#[derive(Deserialize, Serialize)]
+pub struct ApproveContent {
+    pub spender: PublicKeyHash,
+    pub token: Token,
+    pub amount: u128,
+}
+
+impl Content for ApproveContent {
+    fn encode(&self) -> String {
+        format!(
+            "approve{}{}{}",
+            self.spender.to_string(),
+            self.token.to_string(),
+            self.amount
+        )
+    }
+}
+
+/// Transfer of the tokens of the owner, signed by a spender or by an operator of the owner
+#[derive(Deserialize, Serialize)]
+pub struct TransferFromContent {
+    pub owner: PublicKeyHash,
+    pub token: Token,
+    pub destination: PublicKeyHash,
+    pub amount: u128,
+}
+
+impl Content for TransferFromContent {
+    fn encode(&self) -> String {
+        format!(
+            "transferfrom{}{}{}{}",
+            self.owner.to_string(),
+            self.token.to_string(),
+            self.destination.to_string(),
+            self.amount
+        )
+    }
+}
+
+/// As in FA2, an operator can transfer any amount of a token of the owner
+#[derive(Deserialize, Serialize)]
+pub enum OperatorUpdate {
+    Add {
+        operator: PublicKeyHash,
+        token: Token,
+    },
+    Remove {
+        operator: PublicKeyHash,
+        token: Token,
+    },
+}
+
+/// Operators of the signer to add or remove, applied in order
+#[derive(Deserialize, Serialize)]
+pub struct UpdateOperatorsContent {
+    pub updates: Vec<OperatorUpdate>,
+}
+
+impl Content for UpdateOperatorsContent {
+    fn encode(&self) -> String {
+        let updates = self
+            .updates
+            .iter()
+            .map(|update| match update {
+                OperatorUpdate::Add { operator, token } => {
+                    format!("add{}{}", operator.to_string(), token.to_string())
+                }
+                OperatorUpdate::Remove { operator, token } => {
+                    format!("remove{}{}", operator.to_string(), token.to_string())
+                }
+            })
+            .collect::<String>();
+        format!("operators{}", updates)
+    }
+}
+
+/// Escrows tokens of the signer until the preimage of the hashlock is revealed
+#[derive(Deserialize, Serialize)]
+pub struct LockContent {
+    pub token: Token,
+    pub amount: u128,
+    pub recipient: PublicKeyHash,
+    pub hashlock: Vec<u8>,
+    /// Level from which the tokens can't be claimed anymore and can be refunded
+    pub timeout_level: u32,
+}
+
+#[derive(Deserialize, Serialize)]
+pub enum HtlcContent {
+    Lock(LockContent),
+    /// Releases the tokens to the recipient, anybody knowing the preimage can sign it
+    Claim {
+        id: String,
+        preimage: Vec<u8>,
+    },
+    /// Gives the tokens back to the sender after the timeout, anybody can sign it
+    Refund {
+        id: String,
+    },
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+impl Content for HtlcContent {
+    fn encode(&self) -> String {
+        match self {
+            HtlcContent::Lock(lock) => format!(
+                "lock{}{}{}{}{}",
+                lock.token.to_string(),
+                lock.amount,
+                lock.recipient.to_string(),
+                to_hex(&lock.hashlock),
+                lock.timeout_level
+            ),
+            HtlcContent::Claim { id, preimage } => format!("claim{}{}", id, to_hex(preimage)),
+            HtlcContent::Refund { id } => format!("refund{}", id),
+        }
+    }
+}
+
+/// Exchange of tokens between two accounts, the first one sends first_amount of first_token
+/// to the second one, which sends back second_amount of second_token
+/// Nothing is escrowed before both legs are applied, a party cancels its leg by consuming
+/// its nonce with any other message
+#[derive(Deserialize, Serialize, PartialEq, Eq)]
+pub struct SwapContent {
+    pub first: PublicKeyHash,
+    pub first_token: Token,
+    pub first_amount: u128,
+    pub second: PublicKeyHash,
+    pub second_token: Token,
+    pub second_amount: u128,
+    /// Level from which the legs can't be applied anymore
+    pub expires_level: u32,
+}
+
+impl Content for SwapContent {
+    fn encode(&self) -> String {
+        format!(
+            "swap{}{}{}{}{}{}:{}",
+            self.first.to_string(),
+            self.first_token.to_string(),
+            self.first_amount,
+            self.second.to_string(),
+            self.second_token.to_string(),
+            self.second_amount,
+            self.expires_level
+        )
+    }
+}
+
+/// Operations on the constant-product pools, a pool is identified by its pair of tokens
+/// The operations changing the reserves are rejected after their deadline level
+#[derive(Deserialize, Serialize)]
+pub enum PoolContent {
+    /// Creates the pool of the pair with its first liquidity, the fee is in basis points
+    CreatePool {
+        token_a: Token,
+        amount_a: u128,
+        token_b: Token,
+        amount_b: u128,
+        fee: u16,
+    },
+    /// Mints the shares for at most max_a and max_b of the tokens
+    AddLiquidity {
+        token_a: Token,
+        max_a: u128,
+        token_b: Token,
+        max_b: u128,
+        shares: u128,
+        deadline_level: u32,
+    },
+    /// Burns the shares for at least min_a and min_b of the tokens
+    RemoveLiquidity {
+        token_a: Token,
+        min_a: u128,
+        token_b: Token,
+        min_b: u128,
+        shares: u128,
+        deadline_level: u32,
+    },
+    /// Sells amount_in of token_in for at least min_out of token_out
+    Swap {
+        token_in: Token,
+        amount_in: u128,
+        token_out: Token,
+        min_out: u128,
+        deadline_level: u32,
+    },
+}
+
+impl Content for PoolContent {
+    fn encode(&self) -> String {
+        match self {
+            PoolContent::CreatePool {
+                token_a,
+                amount_a,
+                token_b,
+                amount_b,
+                fee,
+            } => format!(
+                "createpool{}:{}:{}:{}:{}",
+                token_a.to_string(),
+                amount_a,
+                token_b.to_string(),
+                amount_b,
+                fee
+            ),
+            PoolContent::AddLiquidity {
+                token_a,
+                max_a,
+                token_b,
+                max_b,
+                shares,
+                deadline_level,
+            } => format!(
+                "addliquidity{}:{}:{}:{}:{}:{}",
+                token_a.to_string(),
+                max_a,
+                token_b.to_string(),
+                max_b,
+                shares,
+                deadline_level
+            ),
+            PoolContent::RemoveLiquidity {
+                token_a,
+                min_a,
+                token_b,
+                min_b,
+                shares,
+                deadline_level,
+            } => format!(
+                "removeliquidity{}:{}:{}:{}:{}:{}",
+                token_a.to_string(),
+                min_a,
+                token_b.to_string(),
+                min_b,
+                shares,
+                deadline_level
+            ),
+            PoolContent::Swap {
+                token_in,
+                amount_in,
+                token_out,
+                min_out,
+                deadline_level,
+            } => format!(
+                "poolswap{}:{}:{}:{}:{}",
+                token_in.to_string(),
+                amount_in,
+                token_out.to_string(),
+                min_out,
+                deadline_level
+            ),
+        }
+    }
 }
 
+/// Tokens escrowed for the beneficiary, vested linearly from start_level to end_level
 #[derive(Deserialize, Serialize)]
-pub struct Inner {
+pub struct VestContent {
+    pub beneficiary: PublicKeyHash,
+    pub token: Token,
+    pub total: u128,
+    pub start_level: u32,
+    /// Level before which nothing can be claimed
+    pub cliff_level: u32,
+    pub end_level: u32,
+}
+
+#[derive(Deserialize, Serialize)]
+pub enum VestingContent {
+    Vest(VestContent),
+    /// Releases the vested tokens to the beneficiary, anybody can sign it
+    ClaimVested {
+        id: String,
+    },
+}
+
+impl Content for VestingContent {
+    fn encode(&self) -> String {
+        match self {
+            VestingContent::Vest(vest) => format!(
+                "vest{}{}{}:{}:{}:{}",
+                vest.beneficiary.to_string(),
+                vest.token.to_string(),
+                vest.total,
+                vest.start_level,
+                vest.cliff_level,
+                vest.end_level
+            ),
+            VestingContent::ClaimVested { id } => format!("claimvested{}", id),
+        }
+    }
+}
+
+/// Deposit paid to the recipient at rate_per_level from the next level
+#[derive(Deserialize, Serialize)]
+pub struct OpenStreamContent {
+    pub recipient: PublicKeyHash,
+    pub token: Token,
+    pub rate_per_level: u128,
+    pub deposit: u128,
+}
+
+#[derive(Deserialize, Serialize)]
+pub enum StreamContent {
+    OpenStream(OpenStreamContent),
+    /// Releases the accrued tokens to the recipient, anybody can sign it
+    WithdrawStream {
+        id: String,
+    },
+    /// Releases the accrued tokens to the recipient and refunds the rest to the sender
+    /// Signed by the sender or the recipient
+    CancelStream {
+        id: String,
+    },
+}
+
+impl Content for StreamContent {
+    fn encode(&self) -> String {
+        match self {
+            StreamContent::OpenStream(stream) => format!(
+                "openstream{}{}{}:{}",
+                stream.recipient.to_string(),
+                stream.token.to_string(),
+                stream.rate_per_level,
+                stream.deposit
+            ),
+            StreamContent::WithdrawStream { id } => format!("withdrawstream{}", id),
+            StreamContent::CancelStream { id } => format!("cancelstream{}", id),
+        }
+    }
+}
+
+/// Commitment of a relayer to the message signed by the account, and to the fee it pays for it
+/// The fee is paid to the admin from the balance of the relayer, whether the wrapped message
+/// is applied or not
+#[derive(Deserialize, Serialize)]
+pub struct SponsorContent {
+    pub account: PublicKeyHash,
+    /// Hash of the inner of the message signed by the account, in hex
+    pub hash: String,
+    pub fee_token: Token,
+    pub fee_amount: u128,
+}
+
+impl Content for SponsorContent {
+    fn encode(&self) -> String {
+        format!(
+            "sponsor{}{}:{}:{}",
+            self.account.to_string(),
+            self.hash,
+            self.fee_token.to_string(),
+            self.fee_amount
+        )
+    }
+}
+
+/// Message of an account wrapped by a relayer
+/// Both signatures are checked and both nonces are consumed
+#[derive(Deserialize, Serialize)]
+pub struct SponsoredMessage {
+    pub message: Message,
+    pub sponsor: SponsorMessage,
+}
+
+impl Message {
+    /// Returns the account which signed the message and the hash it signed
+    /// Deposits, swaps, sponsored and multisig messages are not signed by a single account
+    pub fn signed_hash(&self) -> Option<(PublicKeyHash, Blake2b)> {
+        fn signed<C: Content>(message: &SignedMessage<C>) -> (PublicKeyHash, Blake2b) {
+            let inner = message.inner();
+            let account = match &inner.account {
+                Some(account) => account.clone(),
+                None => PublicKeyHash::from(message.public_key()),
+            };
+            (account, inner.hash())
+        }
+
+        match self {
+            Message::Transfer(m) => Some(signed(m)),
+            Message::Withdraw(m) => Some(signed(m)),
+            Message::Admin(m) => Some(signed(m)),
+            Message::Approve(m) => Some(signed(m)),
+            Message::TransferFrom(m) => Some(signed(m)),
+            Message::UpdateOperators(m) => Some(signed(m)),
+            Message::Htlc(m) => Some(signed(m)),
+            Message::Pool(m) => Some(signed(m)),
+            Message::Order(m) => Some(signed(m)),
+            Message::Vesting(m) => Some(signed(m)),
+            Message::Stream(m) => Some(signed(m)),
+            Message::AuthorizeSessionKey(m) => Some(signed(m)),
+            Message::RotateKey(m) => Some(signed(m)),
+            Message::Bridge(_)
+            | Message::Swap(_)
+            | Message::Sponsored(_)
+            | Message::Multisig(_)
+            | Message::Refund(_)
+            | Message::Level(_) => None,
+        }
+    }
+}
+
+/// Allows the key to transfer up to max_amount of the token of the signer until the expiry level
+/// Authorizing a key again replaces its limit, a limit of 0 revokes it
+#[derive(Deserialize, Serialize)]
+pub struct AuthorizeSessionKeyContent {
+    /// tz1 of the session key
+    pub key: PublicKeyHash,
+    pub token: Token,
+    pub max_amount: u128,
+    pub expires_level: u32,
+}
+
+impl Content for AuthorizeSessionKeyContent {
+    fn encode(&self) -> String {
+        format!(
+            "sessionkey{}{}{}:{}",
+            self.key.to_string(),
+            self.token.to_string(),
+            self.max_amount,
+            self.expires_level
+        )
+    }
+}
+
+/// Replaces the key allowed to sign for the account, the account keeps its balances
+/// The messages signed with the new key name the account in their inner
+#[derive(Deserialize, Serialize)]
+pub struct RotateKeyContent {
+    pub key: PublicKey,
+}
+
+impl Content for RotateKeyContent {
+    fn encode(&self) -> String {
+        format!("rotatekey{}", self.key.to_b58())
+    }
+}
+
+/// Operations spending the tokens of a multisig account
+#[derive(Deserialize, Serialize)]
+pub enum MultisigContent {
+    Transfer(TransferContent),
+    Withdraw(WithdrawContent),
+}
+
+impl Content for MultisigContent {
+    fn encode(&self) -> String {
+        match self {
+            MultisigContent::Transfer(transfer) => format!("multisigtransfer{}", transfer.encode()),
+            MultisigContent::Withdraw(withdraw) => format!("multisigwithdraw{}", withdraw.encode()),
+        }
+    }
+}
+
+/// Signature of one of the keys of a multisig account
+#[derive(Deserialize, Serialize)]
+pub struct MultisigSignature {
+    pub pkey: PublicKey,
+    pub signature: Signature,
+    pub timestamp: String,
+}
+
+/// Operation of a multisig account, signed by at least threshold of its keys
+/// The nonce is the one of the address of the account
+#[derive(Deserialize, Serialize)]
+pub struct MultisigMessage {
+    pub keys: Vec<PublicKey>,
+    pub threshold: u8,
+    pub signatures: Vec<MultisigSignature>,
+    pub inner: Inner<MultisigContent>,
+}
+
+/// Operations on the limit order book of the markets
+#[derive(Deserialize, Serialize)]
+pub enum OrderContent {
+    /// Buys or sells amount of the base token at price units of the quote token or better
+    /// The part which is not filled right away rests in the book
+    Place {
+        base: Token,
+        quote: Token,
+        side: Side,
+        price: u128,
+        amount: u128,
+    },
+    /// Cancels a resting order and returns its escrow to its owner
+    Cancel { id: u64 },
+}
+
+impl Content for OrderContent {
+    fn encode(&self) -> String {
+        match self {
+            OrderContent::Place {
+                base,
+                quote,
+                side,
+                price,
+                amount,
+            } => format!(
+                "placeorder{}:{}:{}:{}:{}",
+                base.to_string(),
+                quote.to_string(),
+                side.to_string(),
+                price,
+                amount
+            ),
+            OrderContent::Cancel { id } => format!("cancelorder{}", id),
+        }
+    }
+}
+
+/// Kind of operation that can be paused by the admin
+#[derive(Deserialize, Serialize, Clone, Copy, PartialEq, Eq)]
+pub enum OperationKind {
+    Deposit,
+    Transfer,
+    Withdraw,
+}
+
+impl ToString for OperationKind {
+    fn to_string(&self) -> String {
+        let kind = match self {
+            OperationKind::Deposit => "deposit",
+            OperationKind::Transfer => "transfer",
+            OperationKind::Withdraw => "withdraw",
+        };
+        kind.to_string()
+    }
+}
+
+/// Account frozen by the admin, for a given token or for all of them when no token is provided
+#[derive(Deserialize, Serialize)]
+pub struct FreezeContent {
+    pub account: PublicKeyHash,
+    pub token: Option<Token>,
+}
+
+impl Content for FreezeContent {
+    fn encode(&self) -> String {
+        let token = self
+            .token
+            .as_ref()
+            .map(|token| token.to_string())
+            .unwrap_or_default();
+        format!("{}{}", self.account.to_string(), token)
+    }
+}
+
+#[derive(Deserialize, Serialize)]
+pub enum AdminContent {
+    Pause(OperationKind),
+    Unpause(OperationKind),
+    Freeze(FreezeContent),
+    Unfreeze(FreezeContent),
+    /// Allows the tickets of a ticketer (KT1...), the bridges are always allowed
+    AllowTicketer(String),
+    RevokeTicketer(String),
+}
+
+impl Content for AdminContent {
+    fn encode(&self) -> String {
+        match self {
+            AdminContent::Pause(kind) => format!("pause{}", kind.to_string()),
+            AdminContent::Unpause(kind) => format!("unpause{}", kind.to_string()),
+            AdminContent::Freeze(freeze) => format!("freeze{}", freeze.encode()),
+            AdminContent::Unfreeze(freeze) => format!("unfreeze{}", freeze.encode()),
+            AdminContent::AllowTicketer(ticketer) => format!("allowticketer{}", ticketer),
+            AdminContent::RevokeTicketer(ticketer) => format!("revoketicketer{}", ticketer),
+        }
+    }
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct Inner<C = TransferContent> {
     pub nonce: Nonce,
-    pub content: TransferContent,
+    /// Each lane of an account has its own sequence of nonces,
+    /// so that a message stuck in a lane does not block the other ones
+    /// The default lane is omitted, which keeps the messages signed without lanes valid
+    #[serde(default, skip_serializing_if = "is_default_lane")]
+    pub lane: u32,
+    /// Account signed for, when its key has been rotated to the signing key
+    /// Omitted when the signer is the account, which keeps the messages signed without it valid
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub account: Option<PublicKeyHash>,
+    pub content: C,
 }
 
-impl Inner {
+fn is_default_lane(lane: &u32) -> bool {
+    *lane == 0
+}
+
+impl<C> Inner<C> {
     /// Returns the nonce of the inner
     pub fn nonce(&self) -> &Nonce {
         &self.nonce
     }
+
+    /// Returns the lane of the nonce
+    pub fn lane(&self) -> u32 {
+        self.lane
+    }
 }
 
 #[derive(Deserialize, Serialize)]
-pub struct TransferMessage {
+pub struct SignedMessage<C = TransferContent> {
     pub pkey: PublicKey,
     pub signature: Signature,
     pub timestamp: String,
-    pub inner: Inner,
+    pub inner: Inner<C>,
 }
 
-impl TransferMessage {
+pub type TransferMessage = SignedMessage<TransferContent>;
+pub type WithdrawMessage = SignedMessage<WithdrawContent>;
+pub type AdminMessage = SignedMessage<AdminContent>;
+pub type ApproveMessage = SignedMessage<ApproveContent>;
+pub type TransferFromMessage = SignedMessage<TransferFromContent>;
+pub type UpdateOperatorsMessage = SignedMessage<UpdateOperatorsContent>;
+pub type HtlcMessage = SignedMessage<HtlcContent>;
+pub type SwapLeg = SignedMessage<SwapContent>;
+pub type PoolMessage = SignedMessage<PoolContent>;
+pub type OrderMessage = SignedMessage<OrderContent>;
+pub type VestingMessage = SignedMessage<VestingContent>;
+pub type StreamMessage = SignedMessage<StreamContent>;
+pub type SponsorMessage = SignedMessage<SponsorContent>;
+pub type AuthorizeSessionKeyMessage = SignedMessage<AuthorizeSessionKeyContent>;
+pub type RotateKeyMessage = SignedMessage<RotateKeyContent>;
+
+/// Both legs of a swap, signed by each party, applied atomically
+#[derive(Deserialize, Serialize)]
+pub struct SwapMessage {
+    pub first: SwapLeg,
+    pub second: SwapLeg,
+}
+
+impl<C> SignedMessage<C> {
     /// Returns the public key of the message
     pub fn public_key(&self) -> &PublicKey {
         &self.pkey
@@ -72,24 +912,119 @@ impl TransferMessage {
     }
 
     /// Returns the inner of the message
-    pub fn inner(&self) -> &Inner {
+    pub fn inner(&self) -> &Inner<C> {
         &self.inner
     }
 }
 
-impl Inner {
+impl<C: Content> Inner<C> {
     /// Hash of the message
     /// This hash is what the client should signed
     pub fn hash(&self) -> Blake2b {
-        // The nonce, and content should be hashed
-        let Inner { nonce, content } = &self;
+        // The nonce, the lane, and content should be hashed
+        // The default lane is not hashed so that the hash of a message without lane does not change
+        // Neither is a missing account
+        // The lane and the account end with a colon, otherwise the digits of the lane could run
+        // into the hex of a token, and two different messages would have the same hash
+        let Inner {
+            nonce,
+            lane,
+            account,
+            content,
+        } = &self;
+        let lane = match lane {
+            0 => String::new(),
+            lane => format!("lane{}:", lane),
+        };
+        let account = account
+            .as_ref()
+            .map(|account| format!("account{}:", account.to_string()))
+            .unwrap_or_default();
         let string = format!(
             "{}{}{}{}",
             nonce.to_string(),
-            content.token.to_hex_string(),
-            content.destination.to_string(),
-            content.amount
+            lane,
+            account,
+            content.encode()
         );
         Blake2b::from(string.as_bytes())
     }
+
+    /// Bytes signed by the client, a Micheline string
+    /// "Tezos Signed Message: ${dapp-url} ${timestamp} ${hash}"
+    pub fn signing_payload(&self, timestamp: &str) -> Vec<u8> {
+        let bytes = [
+            TEZOS_SIGNED_MESSAGE.to_string(),
+            DAPP_URL.to_string(),
+            timestamp.to_string(),
+            self.hash().to_string(),
+        ]
+        .join(" ")
+        .as_bytes()
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect::<String>();
+        let bytes_length = format!("{:08x}", (bytes.len() / 2));
+
+        let data = [
+            MICHELINE_EXPRESSION_BYTE.to_string(),
+            MICHELINE_STRING_BYTE.to_string(),
+            bytes_length,
+            bytes,
+        ]
+        .join("");
+
+        (0..data.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&data[i..i + 2], 16).unwrap())
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Content, Inner, TransferContent};
+    use crate::core::hash::Blake2b;
+    use crate::core::nonce::Nonce;
+    use crate::core::public_key_hash::PublicKeyHash;
+    use crate::core::token::Token;
+
+    const DESTINATION: &str = "tz1QFD9WqLWZmmAuqnnTPPUjfauitYEWdshv";
+
+    fn transfer(lane: u32, token: Vec<u8>) -> Inner {
+        Inner {
+            nonce: Nonce(1),
+            lane,
+            account: None,
+            content: TransferContent {
+                token: Token::Bytes(token),
+                destination: PublicKeyHash::from_b58(DESTINATION).unwrap(),
+                amount: 10,
+                source: None,
+            },
+        }
+    }
+
+    #[test]
+    fn test_lane_is_delimited_from_content() {
+        // Without the delimiter both are hashed as "00000001lane12345tz1..."
+        let first = transfer(1, vec![0x23, 0x45]);
+        let second = transfer(123, vec![0x45]);
+        assert_eq!(
+            format!("lane1{}", first.content.token.to_string()),
+            format!("lane123{}", second.content.token.to_string())
+        );
+
+        assert_ne!(first.hash().to_string(), second.hash().to_string());
+    }
+
+    #[test]
+    fn test_default_lane_is_not_hashed() {
+        let inner = transfer(0, vec![0x23, 0x45]);
+        let string = format!("00000001{}", inner.content.encode());
+        assert_eq!(
+            Blake2b::from(string.as_bytes()).to_string(),
+            inner.hash().to_string()
+        );
+    }
 }
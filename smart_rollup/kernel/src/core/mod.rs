@@ -1,8 +1,15 @@
+pub mod book;
 pub mod error;
 pub mod hash;
+pub mod htlc;
 pub mod message;
+pub mod multisig;
 pub mod nonce;
+pub mod pool;
 pub mod public_key;
 pub mod public_key_hash;
+pub mod session;
 pub mod signature;
+pub mod stream;
 pub mod token;
+pub mod vesting;
@@ -0,0 +1,104 @@
+use serde::{Deserialize, Serialize};
+
+use crate::core::error::{Error, Result};
+use crate::core::message::{Content, Inner, MultisigSignature};
+use crate::core::public_key::PublicKey;
+use crate::core::public_key_hash::PublicKeyHash;
+
+/// Account controlled by threshold of its keys
+/// Its address is derived from its keys, sorted, and from its threshold
+#[derive(Deserialize, Serialize)]
+pub struct Multisig {
+    keys: Vec<PublicKey>,
+    threshold: u8,
+}
+
+impl Multisig {
+    /// The keys are sorted, so that their order does not change the address
+    pub fn new(mut keys: Vec<PublicKey>, threshold: u8) -> Result<Self> {
+        keys.sort_by_key(|key| key.to_b58());
+        let duplicated = keys.windows(2).any(|pair| pair[0] == pair[1]);
+        if duplicated || threshold == 0 || threshold as usize > keys.len() {
+            return Err(Error::InvalidMultisig);
+        }
+        Ok(Multisig { keys, threshold })
+    }
+
+    pub fn keys(&self) -> &[PublicKey] {
+        &self.keys
+    }
+
+    pub fn threshold(&self) -> u8 {
+        self.threshold
+    }
+
+    pub fn address(&self) -> Result<PublicKeyHash> {
+        let keys: Vec<String> = self.keys.iter().map(PublicKey::to_b58).collect();
+        PublicKeyHash::derived(&format!("multisig{}:{}", self.threshold, keys.join(",")))
+    }
+
+    /// Checks that enough distinct keys of the account signed the inner
+    pub fn verify<C: Content>(
+        &self,
+        signatures: &[MultisigSignature],
+        inner: &Inner<C>,
+    ) -> Result<()> {
+        let mut signers: Vec<&PublicKey> = Vec::with_capacity(signatures.len());
+        for MultisigSignature {
+            pkey,
+            signature,
+            timestamp,
+        } in signatures
+        {
+            if !self.keys.contains(pkey) || signers.contains(&pkey) {
+                return Err(Error::InvalidSignature);
+            }
+            signature.verify(pkey, &inner.signing_payload(timestamp))?;
+            signers.push(pkey);
+        }
+
+        if signers.len() < self.threshold as usize {
+            return Err(Error::NotEnoughSignatures);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Multisig;
+    use crate::core::public_key::PublicKey;
+
+    fn keys() -> Vec<PublicKey> {
+        [
+            "edpkuDMUm7Y53wp4gxeLBXuiAhXZrLn8XB1R83ksvvesH8Lp8bmCfK",
+            "edpkuBknW28nW72KG6RoHtYW7p12T6GKc7nAbwYX5m8Wd9sDVC9yav",
+        ]
+        .iter()
+        .map(|key| PublicKey::from_b58(key).unwrap())
+        .collect()
+    }
+
+    #[test]
+    fn test_address_does_not_depend_on_the_order_of_the_keys() {
+        let mut reversed = keys();
+        reversed.reverse();
+
+        let address = Multisig::new(keys(), 2).unwrap().address().unwrap();
+        let other = Multisig::new(reversed, 2).unwrap().address().unwrap();
+        assert!(address == other);
+
+        let one_of_two = Multisig::new(keys(), 1).unwrap().address().unwrap();
+        assert!(address != one_of_two);
+    }
+
+    #[test]
+    fn test_invalid_multisig() {
+        assert!(Multisig::new(keys(), 0).is_err());
+        assert!(Multisig::new(keys(), 3).is_err());
+
+        let mut duplicated = keys();
+        duplicated.push(duplicated[0].clone());
+        assert!(Multisig::new(duplicated, 2).is_err());
+    }
+}
@@ -0,0 +1,221 @@
+use serde::{Deserialize, Serialize};
+
+use crate::constants::SYNTHETIC_TOKEN_PREFIX;
+use crate::core::error::{Error, Result};
+use crate::core::hash::Blake2b;
+use crate::core::public_key_hash::PublicKeyHash;
+use crate::core::token::Token;
+
+/// Fees are expressed in basis points
+pub const FEE_DENOMINATOR: u128 = 10_000;
+/// Highest fee of a pool, 10%
+pub const MAX_FEE: u16 = 1_000;
+
+/// Constant-product pool between two tokens
+/// The reserves are the balances of the account of the pool in the ledger
+#[derive(Deserialize, Serialize)]
+pub struct Pool {
+    pub token_a: Token,
+    pub token_b: Token,
+    /// Fee taken on the input of a swap, in basis points
+    pub fee: u16,
+    /// Total supply of the LP token
+    pub shares: u128,
+}
+
+/// Identifier of the pool of a pair of tokens, whatever their order
+pub fn pool_id(token_a: &Token, token_b: &Token) -> String {
+    let (token_a, token_b) = if token_a <= token_b {
+        (token_a, token_b)
+    } else {
+        (token_b, token_a)
+    };
+    let data = format!("pool{}:{}", token_a.to_string(), token_b.to_string());
+    Blake2b::from(data.as_bytes()).to_string()
+}
+
+impl Pool {
+    /// Pool without liquidity, its tokens are ordered by their bytes, tez last
+    pub fn new(token_a: &Token, token_b: &Token, fee: u16) -> Self {
+        let (token_a, token_b) = if token_a <= token_b {
+            (token_a, token_b)
+        } else {
+            (token_b, token_a)
+        };
+        Pool {
+            token_a: token_a.clone(),
+            token_b: token_b.clone(),
+            fee,
+            shares: 0,
+        }
+    }
+
+    pub fn id(&self) -> String {
+        pool_id(&self.token_a, &self.token_b)
+    }
+
+    /// Account holding the reserves, derived from the identifier of the pool
+    pub fn account(&self) -> Result<PublicKeyHash> {
+        PublicKeyHash::derived(&format!("account{}", self.id()))
+    }
+
+    /// Synthetic token of the shares of the pool, it can't be withdrawn to L1
+    pub fn lp_token(&self) -> Token {
+        Token::Bytes([SYNTHETIC_TOKEN_PREFIX, self.id().as_bytes()].concat())
+    }
+
+    /// Orders the amounts given for the token and for the other token of the pool
+    pub fn ordered(&self, token: &Token, amount: u128, other_amount: u128) -> (u128, u128) {
+        if token == &self.token_a {
+            (amount, other_amount)
+        } else {
+            (other_amount, amount)
+        }
+    }
+
+    /// Returns the amounts of each token to deposit to get the shares, rounded up
+    pub fn deposit_amounts(
+        &self,
+        shares: u128,
+        reserve_a: u128,
+        reserve_b: u128,
+    ) -> Result<(u128, u128)> {
+        if self.shares == 0 {
+            return Err(Error::InvalidPool);
+        }
+        let amount_a = mul_div_up(shares, reserve_a, self.shares)?;
+        let amount_b = mul_div_up(shares, reserve_b, self.shares)?;
+        Ok((amount_a, amount_b))
+    }
+
+    /// Returns the amounts of each token the shares can be redeemed for, rounded down
+    pub fn withdrawn_amounts(
+        &self,
+        shares: u128,
+        reserve_a: u128,
+        reserve_b: u128,
+    ) -> Result<(u128, u128)> {
+        if shares > self.shares {
+            return Err(Error::InvalidTransferAmount);
+        }
+        let amount_a = mul_div(shares, reserve_a, self.shares)?;
+        let amount_b = mul_div(shares, reserve_b, self.shares)?;
+        Ok((amount_a, amount_b))
+    }
+
+    /// Returns the output of a swap, the fee stays in the reserves
+    pub fn swap_output(
+        &self,
+        amount_in: u128,
+        reserve_in: u128,
+        reserve_out: u128,
+    ) -> Result<u128> {
+        // Scaled by the fee denominator so that the fee is not rounded
+        let amount_in = amount_in
+            .checked_mul(FEE_DENOMINATOR - self.fee as u128)
+            .ok_or(Error::InvalidAmount)?;
+        let reserve_in = reserve_in
+            .checked_mul(FEE_DENOMINATOR)
+            .and_then(|reserve_in| reserve_in.checked_add(amount_in))
+            .ok_or(Error::InvalidAmount)?;
+        mul_div(amount_in, reserve_out, reserve_in)
+    }
+}
+
+/// Shares minted for the first liquidity of a pool, the geometric mean of the amounts
+pub fn initial_shares(amount_a: u128, amount_b: u128) -> Result<u128> {
+    let product = amount_a.checked_mul(amount_b).ok_or(Error::InvalidAmount)?;
+    Ok(sqrt(product))
+}
+
+/// Computes a * b / c rounded down
+fn mul_div(a: u128, b: u128, c: u128) -> Result<u128> {
+    let product = a.checked_mul(b).ok_or(Error::InvalidAmount)?;
+    product.checked_div(c).ok_or(Error::InvalidAmount)
+}
+
+/// Computes a * b / c rounded up
+fn mul_div_up(a: u128, b: u128, c: u128) -> Result<u128> {
+    let product = a.checked_mul(b).ok_or(Error::InvalidAmount)?;
+    let quotient = product.checked_div(c).ok_or(Error::InvalidAmount)?;
+    if product % c == 0 {
+        Ok(quotient)
+    } else {
+        Ok(quotient + 1)
+    }
+}
+
+/// Integer square root, rounded down
+fn sqrt(n: u128) -> u128 {
+    if n < 2 {
+        return n;
+    }
+    let mut x = n;
+    let mut y = n / 2;
+    while y < x {
+        x = y;
+        y = (x + n / x) / 2;
+    }
+    x
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{initial_shares, pool_id, sqrt, Pool};
+    use crate::core::token::Token;
+
+    fn pool(shares: u128) -> Pool {
+        let mut pool = Pool::new(&Token::Bytes(vec![0x12]), &Token::Bytes(vec![0x34]), 30);
+        pool.shares = shares;
+        pool
+    }
+
+    #[test]
+    fn test_sqrt() {
+        assert_eq!(0, sqrt(0));
+        assert_eq!(1, sqrt(3));
+        assert_eq!(2, sqrt(4));
+        assert_eq!(1_000_000, sqrt(1_000_000_000_000));
+        assert_eq!(u64::MAX as u128, sqrt(u128::MAX));
+    }
+
+    #[test]
+    fn test_pool_does_not_depend_on_the_order_of_the_tokens() {
+        let (a, b) = (Token::Bytes(vec![0x34]), Token::Bytes(vec![0x12]));
+        assert_eq!(pool_id(&a, &b), pool_id(&b, &a));
+
+        let pool = Pool::new(&a, &b, 30);
+        assert!(pool.token_a == b);
+        assert_eq!((2, 1), pool.ordered(&a, 1, 2));
+
+        let pool = Pool::new(&Token::Tez, &a, 30);
+        assert!(pool.token_a == a && pool.token_b == Token::Tez);
+    }
+
+    #[test]
+    fn test_initial_shares() {
+        assert_eq!(2000, initial_shares(1000, 4000).unwrap());
+        assert!(initial_shares(u128::MAX, 2).is_err());
+    }
+
+    #[test]
+    fn test_deposit_amounts_are_rounded_up() {
+        let pool = pool(1000);
+        assert_eq!((11, 34), pool.deposit_amounts(10, 1001, 3333).unwrap());
+    }
+
+    #[test]
+    fn test_withdrawn_amounts_are_rounded_down() {
+        let pool = pool(1000);
+        assert_eq!((10, 33), pool.withdrawn_amounts(10, 1001, 3333).unwrap());
+        assert!(pool.withdrawn_amounts(1001, 1001, 3333).is_err());
+    }
+
+    #[test]
+    fn test_swap_output() {
+        let pool = pool(1000);
+        // 997 * 2000 / (1000 + 997), the fee being taken on the input
+        assert_eq!(998, pool.swap_output(1000, 1000, 2000).unwrap());
+        assert_eq!(0, pool.swap_output(0, 1000, 2000).unwrap());
+    }
+}
@@ -4,7 +4,7 @@ use tezos_crypto_rs::hash::ContractTz1Hash;
 use crate::core::public_key::PublicKey;
 use crate::core::{error::Error, hash::Blake2b20};
 
-#[derive(Deserialize, Serialize)]
+#[derive(Deserialize, Serialize, Clone, PartialEq, Eq)]
 pub enum PublicKeyHash {
     Tz1(ContractTz1Hash),
 }
@@ -18,6 +18,15 @@ impl ToString for PublicKeyHash {
 }
 
 impl PublicKeyHash {
+    /// Account derived from a seed, as the accounts owned by the kernel
+    /// Nobody knows a key hashing to it, so only the kernel can move its tokens
+    pub fn derived(seed: &str) -> Result<Self, Error> {
+        let hash = Blake2b20::from(seed.as_bytes());
+        ContractTz1Hash::try_from(hash.as_ref())
+            .map(PublicKeyHash::Tz1)
+            .map_err(|_| Error::StateDeserializarion)
+    }
+
     pub fn from_b58(data: &str) -> Result<Self, Error> {
         let tz1 = ContractTz1Hash::from_base58_check(data).ok();
         match tz1 {
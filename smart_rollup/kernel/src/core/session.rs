@@ -0,0 +1,45 @@
+use serde::{Deserialize, Serialize};
+
+use crate::core::error::{Error, Result};
+
+/// Limit of a session key on a token of the account which authorized it
+#[derive(Deserialize, Serialize)]
+pub struct SessionKey {
+    /// Amount the key can still transfer
+    pub remaining: u128,
+    /// Level from which the key can't transfer anymore
+    pub expires_level: u32,
+}
+
+impl SessionKey {
+    /// Debits the limit of the key, at the level
+    pub fn spend(&mut self, amount: u128, level: u32) -> Result<()> {
+        if level >= self.expires_level {
+            return Err(Error::SessionKeyExpired);
+        }
+        if amount > self.remaining {
+            return Err(Error::SessionLimitReached);
+        }
+        self.remaining -= amount;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SessionKey;
+
+    #[test]
+    fn test_spend() {
+        let mut session = SessionKey {
+            remaining: 100,
+            expires_level: 10,
+        };
+
+        assert!(session.spend(60, 9).is_ok());
+        assert_eq!(40, session.remaining);
+        assert!(session.spend(41, 9).is_err());
+        assert!(session.spend(40, 10).is_err());
+        assert_eq!(40, session.remaining);
+    }
+}
@@ -27,12 +27,26 @@ impl Signature {
             }
         }
     }
+
+    pub fn to_b58(&self) -> String {
+        match self {
+            Signature::Ed25519(sig) => sig.to_base58_check(),
+        }
+    }
+
+    pub fn from_b58(data: &str) -> std::result::Result<Self, &'static str> {
+        let ed25519 = Ed25519Signature::from_base58_check(data).ok();
+        match ed25519 {
+            Some(pkey) => Ok(Signature::Ed25519(pkey)),
+            None => Err("Cannot decode b58"),
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use tezos_core::types::encoded::{self, Encoded};
-    use tezos_crypto_rs::hash::{Ed25519Signature, SeedEd25519};
+    use tezos_crypto_rs::hash::SeedEd25519;
 
     use super::Signature;
     use crate::constants::{
@@ -44,22 +58,6 @@ mod tests {
     use crate::core::public_key_hash::PublicKeyHash;
     use crate::core::token::Token;
 
-    impl Signature {
-        pub fn to_b58(&self) -> String {
-            match self {
-                Signature::Ed25519(sig) => sig.to_base58_check(),
-            }
-        }
-
-        pub fn from_b58(data: &str) -> std::result::Result<Self, &'static str> {
-            let ed25519 = Ed25519Signature::from_base58_check(data).ok();
-            match ed25519 {
-                Some(pkey) => Ok(Signature::Ed25519(pkey)),
-                None => Err("Cannot decode b58"),
-            }
-        }
-    }
-
     #[test]
     fn test_generate_and_verify() {
         let (pk, sk) = SeedEd25519::from_base58_check(
@@ -77,11 +75,14 @@ mod tests {
 
         let inner = Inner {
             nonce: Nonce(1),
+            lane: 0,
+            account: None,
             content: TransferContent {
-                token: Token(vec![0x12, 0x34]),
+                token: Token::Bytes(vec![0x12, 0x34]),
                 destination: PublicKeyHash::from_b58("tz1Pe4aBjsW9ZGWaFXa47megxFD1LGGFAW3C")
                     .unwrap(),
                 amount: 10000000,
+                source: None,
             },
         };
 
@@ -0,0 +1,81 @@
+use serde::{Deserialize, Serialize};
+
+use crate::core::hash::Blake2b;
+use crate::core::message::{Content, Inner};
+use crate::core::public_key_hash::PublicKeyHash;
+use crate::core::token::Token;
+
+/// Deposit paid to the recipient at a fixed rate from the level the stream is opened
+/// Only the levels are stored, what is accrued is computed when the stream is touched
+#[derive(Deserialize, Serialize)]
+pub struct Stream {
+    pub sender: PublicKeyHash,
+    pub recipient: PublicKeyHash,
+    pub token: Token,
+    pub rate_per_level: u128,
+    pub deposit: u128,
+    /// Amount already released to the recipient
+    pub withdrawn: u128,
+    pub start_level: u32,
+}
+
+impl Stream {
+    /// Amount accrued to the recipient at the level, whether it has been withdrawn or not
+    pub fn accrued(&self, level: u32) -> u128 {
+        let elapsed = level.saturating_sub(self.start_level) as u128;
+        self.rate_per_level
+            .saturating_mul(elapsed)
+            .min(self.deposit)
+    }
+
+    /// Amount accrued at the level and not withdrawn yet
+    pub fn withdrawable(&self, level: u32) -> u128 {
+        self.accrued(level) - self.withdrawn
+    }
+
+    /// Returns true if the account can cancel the stream
+    pub fn is_party(&self, account: &PublicKeyHash) -> bool {
+        &self.sender == account || &self.recipient == account
+    }
+}
+
+/// Identifier of the stream, the hash of its sender and of its signed hash, in hex
+pub fn stream_id<C: Content>(sender: &PublicKeyHash, inner: &Inner<C>) -> String {
+    let data = format!("stream{}{}", sender.to_string(), inner.hash().to_string());
+    Blake2b::from(data.as_bytes()).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Stream;
+    use crate::core::public_key_hash::PublicKeyHash;
+    use crate::core::token::Token;
+
+    fn stream(rate_per_level: u128) -> Stream {
+        let account = "tz1Pe4aBjsW9ZGWaFXa47megxFD1LGGFAW3C";
+        Stream {
+            sender: PublicKeyHash::from_b58(account).unwrap(),
+            recipient: PublicKeyHash::from_b58(account).unwrap(),
+            token: Token::Bytes(vec![0x12, 0x34]),
+            rate_per_level,
+            deposit: 1000,
+            withdrawn: 0,
+            start_level: 100,
+        }
+    }
+
+    #[test]
+    fn test_accrued() {
+        let stream = stream(30);
+        assert_eq!(0, stream.accrued(99));
+        assert_eq!(0, stream.accrued(100));
+        assert_eq!(300, stream.accrued(110));
+        assert_eq!(1000, stream.accrued(200));
+    }
+
+    #[test]
+    fn test_accrued_does_not_overflow() {
+        let stream = stream(u128::MAX);
+        assert_eq!(1000, stream.accrued(u32::MAX));
+    }
+}
@@ -1,14 +1,232 @@
 use serde::{Deserialize, Serialize};
+use tezos_crypto_rs::hash::ContractKt1Hash;
 
-#[derive(Deserialize, Serialize, PartialEq, Eq, Default)]
-pub struct Token(pub Vec<u8>);
+use crate::constants::{
+    L1_BRIDGE_CONTRACT_ADDRESS, L1_TEZ_BRIDGE_CONTRACT_ADDRESS, SYNTHETIC_TOKEN_PREFIX, TEZ_TOKEN,
+};
+use crate::core::hash::Blake2b20;
+
+/// Token held on the rollup
+/// In json, the bytes of a token are an array of numbers, tez is the "tez" string,
+/// and a ticket is an object with its ticketer and its content
+/// Tokens are ordered by their bytes, tez, then tickets
+#[derive(Deserialize, Serialize, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(try_from = "TokenRepr", into = "TokenRepr")]
+pub enum Token {
+    /// Packed token of types.mligo, the content of the tickets of the bridge
+    Bytes(Vec<u8>),
+    /// Tez wrapped in the unit tickets of the tez bridge
+    Tez,
+    /// Ticket minted by any other contract, identified by its ticketer (KT1...) and its content
+    Ticket {
+        ticketer: String,
+        content: TicketContent,
+    },
+}
+
+/// Contents of the tickets the kernel can decode
+#[derive(Deserialize, Serialize, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum TicketContent {
+    Unit,
+    Bytes(Vec<u8>),
+    String(String),
+    Nat(#[serde(with = "nat")] u128),
+    /// (pair nat (option bytes)), the token id and the metadata of the TZIP-style tickets
+    Pair(#[serde(with = "nat")] u128, Option<Vec<u8>>),
+}
+
+/// Nats are strings in json, as serde_json_wasm writes u128, which the untagged TokenRepr
+/// can't read back on its own
+mod nat {
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(nat: &u128, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&nat.to_string())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u128, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(D::Error::custom)
+    }
+}
+
+#[derive(Deserialize, Serialize)]
+#[serde(untagged)]
+enum TokenRepr {
+    Bytes(Vec<u8>),
+    Name(String),
+    Ticket {
+        ticketer: String,
+        content: TicketContent,
+    },
+}
+
+impl TryFrom<TokenRepr> for Token {
+    type Error = String;
+
+    fn try_from(repr: TokenRepr) -> Result<Self, Self::Error> {
+        match repr {
+            TokenRepr::Bytes(bytes) => Ok(Token::Bytes(bytes)),
+            TokenRepr::Name(name) if name == TEZ_TOKEN => Ok(Token::Tez),
+            TokenRepr::Name(name) => Err(format!("Unknown token: {}", name)),
+            // The ticketer is part of the storage paths of the token
+            TokenRepr::Ticket { ticketer, content } => {
+                ContractKt1Hash::from_base58_check(&ticketer)
+                    .map_err(|_| format!("Invalid ticketer: {}", ticketer))?;
+                Ok(Token::from_ticket(ticketer, content))
+            }
+        }
+    }
+}
+
+impl From<Token> for TokenRepr {
+    fn from(token: Token) -> Self {
+        match token {
+            Token::Bytes(bytes) => TokenRepr::Bytes(bytes),
+            Token::Tez => TokenRepr::Name(TEZ_TOKEN.to_string()),
+            Token::Ticket { ticketer, content } => TokenRepr::Ticket { ticketer, content },
+        }
+    }
+}
 
 impl Token {
-    pub fn from(v: &Vec<u8>) -> Self {
-        Self(v.clone())
+    /// Token of a ticket, the tickets minted by the bridges are their tokens
+    pub fn from_ticket(ticketer: String, content: TicketContent) -> Self {
+        match content {
+            TicketContent::Bytes(bytes) if ticketer == L1_BRIDGE_CONTRACT_ADDRESS => {
+                Token::Bytes(bytes)
+            }
+            TicketContent::Unit if ticketer == L1_TEZ_BRIDGE_CONTRACT_ADDRESS => Token::Tez,
+            content => Token::Ticket { ticketer, content },
+        }
+    }
+
+    /// Tokens minted by the kernel have no ticket on L1
+    pub fn is_synthetic(&self) -> bool {
+        match self {
+            Token::Bytes(bytes) => bytes.starts_with(SYNTHETIC_TOKEN_PREFIX),
+            Token::Tez | Token::Ticket { .. } => false,
+        }
+    }
+
+    /// Key of the token in the storage paths
+    /// The content of a ticket is as long as its ticketer wants, a path is at most 250 bytes,
+    /// so a ticket is keyed on the hash of its ticketer and its content
+    /// The key is never read back, the token is always known when its path is built
+    pub fn key(&self) -> String {
+        match self {
+            Token::Bytes(_) | Token::Tez => self.to_string(),
+            Token::Ticket { .. } => {
+                let hash = Blake2b20::from(self.to_string().as_bytes());
+                format!("ticket.{}", hash.to_string())
+            }
+        }
     }
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+impl ToString for Token {
+    /// Encoding of the token in the signed contents
+    /// Bytes are encoded in hex, so that they can't be mistaken with "tez" or with a ticketer
+    /// Only made of characters allowed in a storage path
+    fn to_string(&self) -> String {
+        match self {
+            Token::Bytes(bytes) => to_hex(bytes),
+            Token::Tez => TEZ_TOKEN.to_string(),
+            Token::Ticket { ticketer, content } => format!("{}.{}", ticketer, content.to_string()),
+        }
+    }
+}
+
+impl ToString for TicketContent {
+    fn to_string(&self) -> String {
+        match self {
+            TicketContent::Unit => "unit".to_string(),
+            TicketContent::Bytes(bytes) => format!("bytes.{}", to_hex(bytes)),
+            TicketContent::String(string) => format!("string.{}", to_hex(string.as_bytes())),
+            TicketContent::Nat(nat) => format!("nat.{}", nat),
+            TicketContent::Pair(nat, None) => format!("pair.{}", nat),
+            TicketContent::Pair(nat, Some(bytes)) => format!("pair.{}.{}", nat, to_hex(bytes)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{TicketContent, Token};
+    use crate::constants::L1_BRIDGE_CONTRACT_ADDRESS;
+
+    const TICKETER: &str = "KT1PWx2mnDueood7fEmfbBDKx1D9BAnnXitn";
+
+    #[test]
+    fn test_json() {
+        let bytes = Token::Bytes(vec![0x12, 0x34]);
+        assert_eq!("[18,52]", serde_json_wasm::to_string(&bytes).unwrap());
+        assert!(serde_json_wasm::from_str::<Token>("[18,52]").unwrap() == bytes);
+
+        assert_eq!("\"tez\"", serde_json_wasm::to_string(&Token::Tez).unwrap());
+        assert!(serde_json_wasm::from_str::<Token>("\"tez\"").unwrap() == Token::Tez);
+        assert!(serde_json_wasm::from_str::<Token>("\"mutez\"").is_err());
+
+        let ticket = Token::from_ticket(TICKETER.to_string(), TicketContent::Nat(5));
+        let json = format!(
+            "{{\"ticketer\":\"{}\",\"content\":{{\"Nat\":\"5\"}}}}",
+            TICKETER
+        );
+        assert_eq!(json, serde_json_wasm::to_string(&ticket).unwrap());
+        assert!(serde_json_wasm::from_str::<Token>(&json).unwrap() == ticket);
+
+        let json = "{\"ticketer\":\"KT1/../ledger\",\"content\":\"Unit\"}";
+        assert!(serde_json_wasm::from_str::<Token>(json).is_err());
+    }
+
+    #[test]
+    fn test_tickets_of_the_bridge_are_its_tokens() {
+        let bridge = L1_BRIDGE_CONTRACT_ADDRESS.to_string();
+        let token = Token::from_ticket(bridge.clone(), TicketContent::Bytes(vec![0x12]));
+        assert!(token == Token::Bytes(vec![0x12]));
+
+        let token = Token::from_ticket(bridge, TicketContent::String("12".to_string()));
+        assert!(matches!(token, Token::Ticket { .. }));
+    }
+
+    #[test]
+    fn test_to_string() {
+        assert_eq!("1234", Token::Bytes(vec![0x12, 0x34]).to_string());
+        assert_eq!("tez", Token::Tez.to_string());
+
+        let ticket = |content| Token::from_ticket(TICKETER.to_string(), content).to_string();
+        assert_eq!(format!("{}.unit", TICKETER), ticket(TicketContent::Unit));
+        assert_eq!(
+            format!("{}.string.6162", TICKETER),
+            ticket(TicketContent::String("ab".to_string()))
+        );
+        assert_eq!(
+            format!("{}.pair.3", TICKETER),
+            ticket(TicketContent::Pair(3, None))
+        );
+        assert_eq!(
+            format!("{}.pair.3.", TICKETER),
+            ticket(TicketContent::Pair(3, Some(vec![])))
+        );
+    }
+
+    #[test]
+    fn test_key() {
+        assert_eq!("1234", Token::Bytes(vec![0x12, 0x34]).key());
+        assert_eq!("tez", Token::Tez.key());
 
-    pub fn to_hex_string(&self) -> String {
-        self.0.iter().map(|b| format!("{:02x}", b)).collect()
+        // The key of a ticket has the same length whatever its content
+        let key = |content| Token::from_ticket(TICKETER.to_string(), content).key();
+        let short = key(TicketContent::Pair(3, None));
+        let long = key(TicketContent::Pair(3, Some(vec![0xab; 200])));
+        assert!(short.starts_with("ticket."));
+        assert_eq!(47, short.len());
+        assert_eq!(short.len(), long.len());
+        assert_ne!(short, long);
     }
 }
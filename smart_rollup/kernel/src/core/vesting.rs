@@ -0,0 +1,106 @@
+use serde::{Deserialize, Serialize};
+
+use crate::core::hash::Blake2b;
+use crate::core::message::{Content, Inner};
+use crate::core::public_key_hash::PublicKeyHash;
+use crate::core::token::Token;
+
+/// Tokens escrowed for a beneficiary, released linearly between the start and the end levels
+/// Nothing can be claimed before the cliff level
+#[derive(Deserialize, Serialize)]
+pub struct Vesting {
+    pub sender: PublicKeyHash,
+    pub beneficiary: PublicKeyHash,
+    pub token: Token,
+    pub total: u128,
+    /// Amount already released to the beneficiary
+    pub claimed: u128,
+    pub start_level: u32,
+    pub cliff_level: u32,
+    pub end_level: u32,
+}
+
+impl Vesting {
+    /// Returns true if the levels of the schedule are ordered
+    pub fn is_valid_schedule(&self) -> bool {
+        self.start_level <= self.cliff_level
+            && self.cliff_level <= self.end_level
+            && self.start_level < self.end_level
+    }
+
+    /// Amount vested at the level, whether it has been claimed or not
+    pub fn vested(&self, level: u32) -> u128 {
+        if level < self.cliff_level {
+            return 0;
+        }
+        if level >= self.end_level {
+            return self.total;
+        }
+
+        // Split so that total * elapsed can't overflow
+        let elapsed = (level - self.start_level) as u128;
+        let duration = (self.end_level - self.start_level) as u128;
+        self.total / duration * elapsed + self.total % duration * elapsed / duration
+    }
+
+    /// Amount vested at the level and not claimed yet
+    pub fn claimable(&self, level: u32) -> u128 {
+        self.vested(level) - self.claimed
+    }
+}
+
+/// Identifier of the schedule, the hash of its sender and of its signed hash, in hex
+pub fn vesting_id<C: Content>(sender: &PublicKeyHash, inner: &Inner<C>) -> String {
+    let data = format!("vesting{}{}", sender.to_string(), inner.hash().to_string());
+    Blake2b::from(data.as_bytes()).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Vesting;
+    use crate::core::public_key_hash::PublicKeyHash;
+    use crate::core::token::Token;
+
+    fn vesting(total: u128) -> Vesting {
+        let account = "tz1Pe4aBjsW9ZGWaFXa47megxFD1LGGFAW3C";
+        Vesting {
+            sender: PublicKeyHash::from_b58(account).unwrap(),
+            beneficiary: PublicKeyHash::from_b58(account).unwrap(),
+            token: Token::Bytes(vec![0x12, 0x34]),
+            total,
+            claimed: 0,
+            start_level: 100,
+            cliff_level: 110,
+            end_level: 200,
+        }
+    }
+
+    #[test]
+    fn test_vested() {
+        let vesting = vesting(1000);
+        assert_eq!(0, vesting.vested(100));
+        assert_eq!(0, vesting.vested(109));
+        assert_eq!(100, vesting.vested(110));
+        assert_eq!(550, vesting.vested(155));
+        assert_eq!(1000, vesting.vested(200));
+        assert_eq!(1000, vesting.vested(u32::MAX));
+    }
+
+    #[test]
+    fn test_vested_does_not_overflow() {
+        let vesting = vesting(u128::MAX);
+        assert_eq!(u128::MAX / 2, vesting.vested(150));
+    }
+
+    #[test]
+    fn test_is_valid_schedule() {
+        let mut vesting = vesting(1000);
+        assert!(vesting.is_valid_schedule());
+        vesting.cliff_level = 99;
+        assert!(!vesting.is_valid_schedule());
+        vesting.cliff_level = 200;
+        assert!(vesting.is_valid_schedule());
+        vesting.start_level = 200;
+        assert!(!vesting.is_valid_schedule());
+    }
+}
@@ -1,13 +1,24 @@
 use crate::core::error::ReadInputError;
 use crate::core::message::Message;
 use tezos_smart_rollup::{host::Runtime, kernel_entry};
-use utils::{process_bridge_message, process_transfer_message, read_input};
+use utils::{
+    process_admin_message, process_approve_message, process_authorize_session_key_message,
+    process_bridge_message, process_htlc_message, process_level, process_multisig_message,
+    process_order_message, process_pool_message, process_refund_message,
+    process_rotate_key_message, process_sponsored_message, process_stream_message,
+    process_swap_message, process_transfer_from_message, process_transfer_message,
+    process_update_operators_message, process_vesting_message, process_withdraw_message,
+    read_input,
+};
 
-mod constants;
-mod core;
+pub mod constants;
+pub mod core;
+mod outbox;
 mod storage;
 mod utils;
 
+pub use utils::parse_input;
+
 pub fn entry<Host: Runtime>(host: &mut Host) {
     execute(host);
 }
@@ -15,16 +26,72 @@ pub fn entry<Host: Runtime>(host: &mut Host) {
 fn execute<Host: Runtime>(host: &mut Host) {
     match read_input(host) {
         Ok(message) => {
-            match message {
-                Message::Bridge(b) => process_bridge_message(host, b).unwrap_or(()),
-                Message::Transfer(t) => process_transfer_message(host, t).unwrap_or(()),
-            }
+            process_message(host, message);
 
             execute(host)
         }
         Err(ReadInputError::EndOfInbox) => (),
+        // The ticket is kept by the rollup, it can only be traced in the logs
+        Err(ReadInputError::InvalidDeposit(err)) => {
+            host.write_debug(&format!(
+                "Deposit cannot be credited: {}\n",
+                err.to_string()
+            ));
+            execute(host)
+        }
         Err(_) => execute(host),
     }
 }
 
+fn process_message<Host: Runtime>(host: &mut Host, message: Message) {
+    match message {
+        Message::Bridge(b) => {
+            if let Err(err) = process_bridge_message(host, b) {
+                host.write_debug(&format!(
+                    "Deposit cannot be credited nor refunded: {}\n",
+                    err.to_string()
+                ));
+            }
+        }
+        Message::Transfer(t) => process_transfer_message(host, t).unwrap_or(()),
+        Message::Withdraw(w) => process_withdraw_message(host, w).unwrap_or(()),
+        Message::Admin(a) => process_admin_message(host, a).unwrap_or(()),
+        Message::Approve(a) => process_approve_message(host, a).unwrap_or(()),
+        Message::TransferFrom(t) => process_transfer_from_message(host, t).unwrap_or(()),
+        Message::UpdateOperators(u) => process_update_operators_message(host, u).unwrap_or(()),
+        Message::Htlc(h) => process_htlc_message(host, h).unwrap_or(()),
+        Message::Swap(s) => process_swap_message(host, *s).unwrap_or(()),
+        Message::Pool(p) => process_pool_message(host, p).unwrap_or(()),
+        Message::Order(o) => process_order_message(host, o).unwrap_or(()),
+        Message::Vesting(v) => process_vesting_message(host, v).unwrap_or(()),
+        Message::Stream(s) => process_stream_message(host, s).unwrap_or(()),
+        Message::Sponsored(s) => {
+            if let Ok(message) = process_sponsored_message(host, *s) {
+                process_message(host, message)
+            }
+        }
+        Message::AuthorizeSessionKey(a) => {
+            process_authorize_session_key_message(host, a).unwrap_or(())
+        }
+        Message::RotateKey(r) => process_rotate_key_message(host, r).unwrap_or(()),
+        Message::Multisig(m) => process_multisig_message(host, m).unwrap_or(()),
+        Message::Refund(r) => {
+            if let Err(err) = process_refund_message(host, r) {
+                host.write_debug(&format!(
+                    "Deposit cannot be refunded: {}\n",
+                    err.to_string()
+                ));
+            }
+        }
+        Message::Level(l) => {
+            if let Err(err) = process_level(host, l) {
+                host.write_debug(&format!(
+                    "Queued deposit cannot be credited nor refunded: {}\n",
+                    err.to_string()
+                ));
+            }
+        }
+    }
+}
+
 kernel_entry!(entry);
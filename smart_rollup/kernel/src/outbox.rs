@@ -0,0 +1,151 @@
+use crate::constants::{
+    L1_BRIDGE_CONTRACT_ADDRESS, L1_DEFAULT_ENTRYPOINT, L1_RELEASE_ENTRYPOINT,
+    L1_TEZ_BRIDGE_CONTRACT_ADDRESS,
+};
+use crate::core::{
+    error::{Error, Result},
+    public_key_hash::PublicKeyHash,
+    token::{TicketContent, Token},
+};
+use tezos_data_encoding::enc::BinWriter;
+use tezos_smart_rollup::{
+    host::Runtime,
+    michelson::{
+        ticket::Ticket, Michelson, MichelsonBytes, MichelsonContract, MichelsonNat,
+        MichelsonOption, MichelsonPair, MichelsonString, MichelsonUnit, Zarith,
+    },
+    outbox::{OutboxMessage, OutboxMessageTransaction},
+    types::{Contract, Entrypoint},
+};
+
+/// Writes an outbox message calling the `release` entrypoint of the ticketer of the token,
+/// the bridges unlock the tokens, or the tez, on L1 in exchange of the ticket
+pub fn write_release<Host: Runtime>(
+    host: &mut Host,
+    destination: &PublicKeyHash,
+    token: &Token,
+    amount: u128,
+) -> Result<()> {
+    write_ticket(host, token, amount, Target::Release(contract(destination)?))
+}
+
+/// Writes an outbox message sending the ticket of the token back to the L1 account as is,
+/// the `release` entrypoint of its ticketer is not called
+pub fn write_return<Host: Runtime>(
+    host: &mut Host,
+    destination: &PublicKeyHash,
+    token: &Token,
+    amount: u128,
+) -> Result<()> {
+    write_ticket(host, token, amount, Target::Return(contract(destination)?))
+}
+
+fn contract(account: &PublicKeyHash) -> Result<Contract> {
+    Contract::from_b58check(account.to_string().as_str()).map_err(|_| Error::OutboxMessage)
+}
+
+/// Where the ticket of an outbox message is sent
+enum Target {
+    /// The ticketer, which releases what the ticket stands for to the L1 account
+    Release(Contract),
+    /// The L1 account, which receives the ticket itself
+    Return(Contract),
+}
+
+fn write_ticket<Host: Runtime>(
+    host: &mut Host,
+    token: &Token,
+    amount: u128,
+    target: Target,
+) -> Result<()> {
+    // The ticket has been minted by its ticketer, which is the only one able to release it
+    match token {
+        Token::Bytes(bytes) => send_ticket(
+            host,
+            L1_BRIDGE_CONTRACT_ADDRESS,
+            MichelsonBytes(bytes.clone()),
+            amount,
+            target,
+        ),
+        Token::Tez => send_ticket(
+            host,
+            L1_TEZ_BRIDGE_CONTRACT_ADDRESS,
+            MichelsonUnit,
+            amount,
+            target,
+        ),
+        Token::Ticket { ticketer, content } => match content {
+            TicketContent::Unit => send_ticket(host, ticketer, MichelsonUnit, amount, target),
+            TicketContent::Bytes(bytes) => send_ticket(
+                host,
+                ticketer,
+                MichelsonBytes(bytes.clone()),
+                amount,
+                target,
+            ),
+            TicketContent::String(string) => send_ticket(
+                host,
+                ticketer,
+                MichelsonString(string.clone()),
+                amount,
+                target,
+            ),
+            TicketContent::Nat(nat) => send_ticket(host, ticketer, nat_expr(*nat)?, amount, target),
+            TicketContent::Pair(nat, metadata) => {
+                let metadata = MichelsonOption(metadata.clone().map(MichelsonBytes));
+                let content = MichelsonPair(nat_expr(*nat)?, metadata);
+                send_ticket(host, ticketer, content, amount, target)
+            }
+        },
+    }
+}
+
+fn nat_expr(nat: u128) -> Result<MichelsonNat> {
+    MichelsonNat::new(Zarith(nat.into())).ok_or(Error::OutboxMessage)
+}
+
+/// Sends the ticket to its ticketer alongside the L1 account receiving what it stands for,
+/// or to the L1 account itself
+fn send_ticket<Host: Runtime, Expr: Michelson>(
+    host: &mut Host,
+    ticketer: &str,
+    content: Expr,
+    amount: u128,
+    target: Target,
+) -> Result<()> {
+    let ticketer = Contract::from_b58check(ticketer).map_err(|_| Error::OutboxMessage)?;
+    let ticket =
+        Ticket::new(ticketer.clone(), content, amount).map_err(|_| Error::OutboxMessage)?;
+    match target {
+        Target::Release(destination) => write_transaction(
+            host,
+            ticketer,
+            L1_RELEASE_ENTRYPOINT,
+            MichelsonPair(ticket, MichelsonContract(destination)),
+        ),
+        Target::Return(destination) => {
+            write_transaction(host, destination, L1_DEFAULT_ENTRYPOINT, ticket)
+        }
+    }
+}
+
+fn write_transaction<Host: Runtime, Expr: Michelson>(
+    host: &mut Host,
+    destination: Contract,
+    entrypoint: &str,
+    parameters: Expr,
+) -> Result<()> {
+    let entrypoint =
+        Entrypoint::try_from(entrypoint.to_string()).map_err(|_| Error::OutboxMessage)?;
+
+    let transaction = OutboxMessageTransaction {
+        parameters,
+        destination,
+        entrypoint,
+    };
+    let message = OutboxMessage::AtomicTransactionBatch(vec![transaction].into());
+
+    let mut output = Vec::default();
+    message.bin_write(&mut output).map_err(Error::from)?;
+    host.write_output(&output).map_err(Error::from)
+}
@@ -1,32 +1,266 @@
+use crate::constants::{ADMIN_ADDRESS, L1_BRIDGE_CONTRACT_ADDRESS, L1_TEZ_BRIDGE_CONTRACT_ADDRESS};
 use crate::core::{
+    book::{Fill, Order, Side},
     error::{Error, Result},
+    htlc::{is_htlc_id, Htlc},
+    message::{BridgeMessage, OperationKind},
+    pool::Pool,
+    public_key::PublicKey,
     public_key_hash::PublicKeyHash,
+    session::SessionKey,
+    stream::Stream,
     token::Token,
+    vesting::Vesting,
 };
+use serde::{de::DeserializeOwned, Serialize};
+use tezos_crypto_rs::hash::ContractKt1Hash;
 use tezos_smart_rollup::{host::Runtime, storage::path::*};
 
 const LEDGER: RefPath = RefPath::assert_from(b"/ledger");
+const TEZ_LEDGER: RefPath = RefPath::assert_from(b"/tez");
 const NONCE: RefPath = RefPath::assert_from(b"/nonce");
+const LANE: RefPath = RefPath::assert_from(b"/lane");
+const FROZEN: RefPath = RefPath::assert_from(b"/frozen");
+const PAUSED: RefPath = RefPath::assert_from(b"/paused");
+const ALLOWANCE: RefPath = RefPath::assert_from(b"/allowance");
+const OPERATOR: RefPath = RefPath::assert_from(b"/operator");
+const HTLC: RefPath = RefPath::assert_from(b"/htlc");
+const POOL: RefPath = RefPath::assert_from(b"/pool");
+const LEVEL: RefPath = RefPath::assert_from(b"/level");
+const VESTING: RefPath = RefPath::assert_from(b"/vesting");
+const STREAM: RefPath = RefPath::assert_from(b"/stream");
+const SESSION: RefPath = RefPath::assert_from(b"/session");
+const PUBKEY: RefPath = RefPath::assert_from(b"/pubkey");
+const BOOK: RefPath = RefPath::assert_from(b"/book");
+const ORDERS: RefPath = RefPath::assert_from(b"/book/orders");
+const ORDERS_LENGTH: RefPath = RefPath::assert_from(b"/book/length");
+const FILLS: RefPath = RefPath::assert_from(b"/receipts/fills");
+const FILLS_LENGTH: RefPath = RefPath::assert_from(b"/receipts/length");
+const PENDING_DEPOSITS: RefPath = RefPath::assert_from(b"/pending/deposits");
+const PENDING_DEPOSITS_LENGTH: RefPath = RefPath::assert_from(b"/pending/length");
+const PENDING_DEPOSITS_HEAD: RefPath = RefPath::assert_from(b"/pending/head");
+const ADMIN: RefPath = RefPath::assert_from(b"/admin");
+const TICKETER: RefPath = RefPath::assert_from(b"/ticketer");
 
 fn get_account_ledger_path(public_key_hash: &PublicKeyHash, token: &Token) -> Result<OwnedPath> {
     let public_key_hash: Vec<u8> = format!("/{}", public_key_hash.to_string()).into();
     let public_key_hash = OwnedPath::try_from(public_key_hash).map_err(Error::from)?;
 
-    let token: Vec<u8> = format!("/{}", token.to_hex_string()).into();
+    let token = match token {
+        // Stored as /tez/${tz1-account}/<balance>
+        Token::Tez => return concat(&TEZ_LEDGER, &public_key_hash).map_err(Error::from),
+        Token::Bytes(_) | Token::Ticket { .. } => token.key(),
+    };
+    let token: Vec<u8> = format!("/{}", token).into();
     let token = OwnedPath::try_from(token).map_err(Error::from)?;
 
     let ledger_key = concat(&public_key_hash, &token).map_err(Error::from)?;
 
     // Stored as /ledger/${tz1-account}/${token-bytes}/<balance>
+    // or /ledger/${tz1-account}/ticket.${ticket-hash}/<balance>
     concat(&LEDGER, &ledger_key).map_err(Error::from)
 }
 
-fn get_nonce_path(public_key_hash: &PublicKeyHash) -> Result<OwnedPath> {
+fn get_nonce_path(public_key_hash: &PublicKeyHash, lane: u32) -> Result<OwnedPath> {
     let public_key_hash: Vec<u8> = format!("/{}", public_key_hash.to_string()).into();
     let public_key_hash = OwnedPath::try_from(public_key_hash).map_err(Error::from)?;
 
-    // Stored as /nonce/${tz1-account}
-    concat(&NONCE, &public_key_hash).map_err(Error::from)
+    if lane == 0 {
+        // Stored as /nonce/${tz1-account}
+        return concat(&NONCE, &public_key_hash).map_err(Error::from);
+    }
+
+    let lane: Vec<u8> = format!("/{}", lane).into();
+    let lane = OwnedPath::try_from(lane).map_err(Error::from)?;
+
+    let lane_key = concat(&public_key_hash, &lane).map_err(Error::from)?;
+
+    // Stored as /lane/${tz1-account}/${lane}
+    concat(&LANE, &lane_key).map_err(Error::from)
+}
+
+fn get_frozen_path(public_key_hash: &PublicKeyHash, token: Option<&Token>) -> Result<OwnedPath> {
+    let public_key_hash: Vec<u8> = format!("/{}", public_key_hash.to_string()).into();
+    let public_key_hash = OwnedPath::try_from(public_key_hash).map_err(Error::from)?;
+
+    // "all" can't be mistaken with the hex representation of a token, nor with "tez"
+    let token = token
+        .map(|token| token.key())
+        .unwrap_or_else(|| "all".to_string());
+    let token: Vec<u8> = format!("/{}", token).into();
+    let token = OwnedPath::try_from(token).map_err(Error::from)?;
+
+    let frozen_key = concat(&public_key_hash, &token).map_err(Error::from)?;
+
+    // Stored as /frozen/${tz1-account}/${token-key | all}
+    concat(&FROZEN, &frozen_key).map_err(Error::from)
+}
+
+fn get_ticketer_path(ticketer: &str) -> Result<OwnedPath> {
+    ContractKt1Hash::from_base58_check(ticketer).map_err(|_| Error::InvalidTicketer)?;
+
+    let ticketer: Vec<u8> = format!("/{}", ticketer).into();
+    let ticketer = OwnedPath::try_from(ticketer).map_err(Error::from)?;
+
+    // Stored as /ticketer/${KT1-ticketer}
+    concat(&TICKETER, &ticketer).map_err(Error::from)
+}
+
+fn get_paused_path(kind: &OperationKind) -> Result<OwnedPath> {
+    let kind: Vec<u8> = format!("/{}", kind.to_string()).into();
+    let kind = OwnedPath::try_from(kind).map_err(Error::from)?;
+
+    // Stored as /paused/${operation-kind}
+    concat(&PAUSED, &kind).map_err(Error::from)
+}
+
+fn get_allowance_path(
+    owner: &PublicKeyHash,
+    spender: &PublicKeyHash,
+    token: &Token,
+) -> Result<OwnedPath> {
+    let allowance_key: Vec<u8> = format!(
+        "/{}/{}/{}",
+        owner.to_string(),
+        spender.to_string(),
+        token.key()
+    )
+    .into();
+    let allowance_key = OwnedPath::try_from(allowance_key).map_err(Error::from)?;
+
+    // Stored as /allowance/${tz1-owner}/${tz1-spender}/${token-key}
+    concat(&ALLOWANCE, &allowance_key).map_err(Error::from)
+}
+
+fn get_operator_path(
+    owner: &PublicKeyHash,
+    operator: &PublicKeyHash,
+    token: &Token,
+) -> Result<OwnedPath> {
+    let operator_key: Vec<u8> = format!(
+        "/{}/{}/{}",
+        owner.to_string(),
+        operator.to_string(),
+        token.key()
+    )
+    .into();
+    let operator_key = OwnedPath::try_from(operator_key).map_err(Error::from)?;
+
+    // Stored as /operator/${tz1-owner}/${tz1-operator}/${token-key}
+    concat(&OPERATOR, &operator_key).map_err(Error::from)
+}
+
+fn get_htlc_path(id: &str) -> Result<OwnedPath> {
+    // The id is given by the user and should not escape /htlc
+    if !is_htlc_id(id) {
+        return Err(Error::HtlcNotFound);
+    }
+
+    let id: Vec<u8> = format!("/{}", id).into();
+    let id = OwnedPath::try_from(id).map_err(Error::from)?;
+
+    // Stored as /htlc/${id}
+    concat(&HTLC, &id).map_err(Error::from)
+}
+
+fn get_pool_path(id: &str) -> Result<OwnedPath> {
+    let id: Vec<u8> = format!("/{}", id).into();
+    let id = OwnedPath::try_from(id).map_err(Error::from)?;
+
+    // Stored as /pool/${id}
+    concat(&POOL, &id).map_err(Error::from)
+}
+
+fn get_vesting_path(id: &str) -> Result<OwnedPath> {
+    // Identifiers of the schedules have the format of the identifiers of the locks
+    if !is_htlc_id(id) {
+        return Err(Error::VestingNotFound);
+    }
+
+    let id: Vec<u8> = format!("/{}", id).into();
+    let id = OwnedPath::try_from(id).map_err(Error::from)?;
+
+    // Stored as /vesting/${id}
+    concat(&VESTING, &id).map_err(Error::from)
+}
+
+fn get_stream_path(id: &str) -> Result<OwnedPath> {
+    // Identifiers of the streams have the format of the identifiers of the locks
+    if !is_htlc_id(id) {
+        return Err(Error::StreamNotFound);
+    }
+
+    let id: Vec<u8> = format!("/{}", id).into();
+    let id = OwnedPath::try_from(id).map_err(Error::from)?;
+
+    // Stored as /stream/${id}
+    concat(&STREAM, &id).map_err(Error::from)
+}
+
+fn get_session_key_path(
+    account: &PublicKeyHash,
+    key: &PublicKeyHash,
+    token: &Token,
+) -> Result<OwnedPath> {
+    let session_key: Vec<u8> = format!(
+        "/{}/{}/{}",
+        account.to_string(),
+        key.to_string(),
+        token.key()
+    )
+    .into();
+    let session_key = OwnedPath::try_from(session_key).map_err(Error::from)?;
+
+    // Stored as /session/${tz1-account}/${tz1-key}/${token-key}
+    concat(&SESSION, &session_key).map_err(Error::from)
+}
+
+fn get_public_key_path(account: &PublicKeyHash) -> Result<OwnedPath> {
+    let account: Vec<u8> = format!("/{}", account.to_string()).into();
+    let account = OwnedPath::try_from(account).map_err(Error::from)?;
+
+    // Stored as /pubkey/${tz1-account}
+    concat(&PUBKEY, &account).map_err(Error::from)
+}
+
+fn get_order_path(id: u64) -> Result<OwnedPath> {
+    let id: Vec<u8> = format!("/{}", id).into();
+    let id = OwnedPath::try_from(id).map_err(Error::from)?;
+
+    // Stored as /book/orders/${id}
+    concat(&ORDERS, &id).map_err(Error::from)
+}
+
+fn get_prices_path(market: &str, side: Side) -> Result<OwnedPath> {
+    let prices: Vec<u8> = format!("/{}/prices/{}", market, side.to_string()).into();
+    let prices = OwnedPath::try_from(prices).map_err(Error::from)?;
+
+    // Stored as /book/${market}/prices/${side}
+    concat(&BOOK, &prices).map_err(Error::from)
+}
+
+fn get_price_level_path(market: &str, side: Side, price: u128) -> Result<OwnedPath> {
+    let level: Vec<u8> = format!("/{}/levels/{}/{}", market, side.to_string(), price).into();
+    let level = OwnedPath::try_from(level).map_err(Error::from)?;
+
+    // Stored as /book/${market}/levels/${side}/${price}
+    concat(&BOOK, &level).map_err(Error::from)
+}
+
+fn get_fill_path(index: u64) -> Result<OwnedPath> {
+    let index: Vec<u8> = format!("/{}", index).into();
+    let index = OwnedPath::try_from(index).map_err(Error::from)?;
+
+    // Stored as /receipts/fills/${index}
+    concat(&FILLS, &index).map_err(Error::from)
+}
+
+fn get_pending_deposit_path(index: u64) -> Result<OwnedPath> {
+    let index: Vec<u8> = format!("/{}", index).into();
+    let index = OwnedPath::try_from(index).map_err(Error::from)?;
+
+    // Stored as /pending/deposits/${index}
+    concat(&PENDING_DEPOSITS, &index).map_err(Error::from)
 }
 
 pub fn exists<R: Runtime>(host: &mut R, path: &impl Path) -> Result<bool> {
@@ -36,6 +270,13 @@ pub fn exists<R: Runtime>(host: &mut R, path: &impl Path) -> Result<bool> {
     Ok(exists)
 }
 
+fn delete<Host: Runtime, P: Path>(host: &mut Host, path: &P) -> Result<()> {
+    if exists(host, path)? {
+        host.store_delete(path).map_err(Error::from)?;
+    }
+    Ok(())
+}
+
 fn store_u128<Host: Runtime, P: Path>(host: &mut Host, path: &P, data: &u128) -> Result<()> {
     let data = data.to_be_bytes();
     let data = data.as_slice();
@@ -80,6 +321,39 @@ fn read_u64<Host: Runtime, P: Path>(host: &mut Host, path: &P) -> Result<Option<
     }
 }
 
+fn store_json<Host: Runtime, P: Path, T: Serialize>(
+    host: &mut Host,
+    path: &P,
+    data: &T,
+) -> Result<()> {
+    let data = serde_json_wasm::to_vec(data).map_err(|_| Error::StateSerialization)?;
+
+    // Writing does not truncate the previous value
+    if exists(host, path)? {
+        host.store_delete(path).map_err(Error::from)?;
+    }
+
+    host.store_write(path, &data, 0)
+        .map_err(Error::from)
+        .map(|_| ())
+}
+
+fn read_json<Host: Runtime, P: Path, T: DeserializeOwned>(
+    host: &mut Host,
+    path: &P,
+) -> Result<Option<T>> {
+    let is_exists = exists(host, path)?;
+    if !is_exists {
+        return Ok(None);
+    }
+
+    let size = host.store_value_size(path).map_err(Error::from)?;
+    let data = host.store_read(path, 0, size).map_err(Error::from)?;
+    serde_json_wasm::from_slice(&data)
+        .map(Some)
+        .map_err(|_| Error::StateDeserializarion)
+}
+
 pub fn read_balance<Host: Runtime>(
     host: &mut Host,
     account: &PublicKeyHash,
@@ -100,17 +374,404 @@ pub fn store_balance<Host: Runtime>(
     Ok(())
 }
 
-pub fn read_nonce<Host: Runtime>(host: &mut Host, account: &PublicKeyHash) -> Result<u64> {
-    let path = get_nonce_path(account)?;
+pub fn read_nonce<Host: Runtime>(
+    host: &mut Host,
+    account: &PublicKeyHash,
+    lane: u32,
+) -> Result<u64> {
+    let path = get_nonce_path(account, lane)?;
     Ok(read_u64(host, &path)?.unwrap_or_default())
 }
 
 pub fn store_nonce<Host: Runtime>(
     host: &mut Host,
     account: &PublicKeyHash,
+    lane: u32,
     nonce: &u64,
 ) -> Result<()> {
-    let path = get_nonce_path(account)?;
+    let path = get_nonce_path(account, lane)?;
     store_u64(host, &path, nonce)?;
     Ok(())
 }
+
+pub fn read_allowance<Host: Runtime>(
+    host: &mut Host,
+    owner: &PublicKeyHash,
+    spender: &PublicKeyHash,
+    token: &Token,
+) -> Result<u128> {
+    let path = get_allowance_path(owner, spender, token)?;
+    Ok(read_u128(host, &path)?.unwrap_or_default())
+}
+
+pub fn store_allowance<Host: Runtime>(
+    host: &mut Host,
+    owner: &PublicKeyHash,
+    spender: &PublicKeyHash,
+    token: &Token,
+    allowance: &u128,
+) -> Result<()> {
+    let path = get_allowance_path(owner, spender, token)?;
+
+    // An exhausted allowance does not take space in the storage
+    if *allowance == 0 {
+        if exists(host, &path)? {
+            host.store_delete(&path).map_err(Error::from)?;
+        }
+        return Ok(());
+    }
+    store_u128(host, &path, allowance)
+}
+
+pub fn is_operator<Host: Runtime>(
+    host: &mut Host,
+    owner: &PublicKeyHash,
+    operator: &PublicKeyHash,
+    token: &Token,
+) -> Result<bool> {
+    let path = get_operator_path(owner, operator, token)?;
+    exists(host, &path)
+}
+
+pub fn store_operator<Host: Runtime>(
+    host: &mut Host,
+    owner: &PublicKeyHash,
+    operator: &PublicKeyHash,
+    token: &Token,
+    is_operator: bool,
+) -> Result<()> {
+    let path = get_operator_path(owner, operator, token)?;
+    if is_operator {
+        host.store_write(&path, &[1], 0).map_err(Error::from)
+    } else if exists(host, &path)? {
+        host.store_delete(&path).map_err(Error::from)
+    } else {
+        Ok(())
+    }
+}
+
+/// Returns the level of the inbox being read
+pub fn read_level<Host: Runtime>(host: &mut Host) -> Result<u32> {
+    let level = read_u64(host, &LEVEL)?.unwrap_or_default();
+    Ok(level as u32)
+}
+
+pub fn store_level<Host: Runtime>(host: &mut Host, level: u32) -> Result<()> {
+    store_u64(host, &LEVEL, &(level as u64))
+}
+
+pub fn read_htlc<Host: Runtime>(host: &mut Host, id: &str) -> Result<Option<Htlc>> {
+    let path = get_htlc_path(id)?;
+    read_json(host, &path)
+}
+
+pub fn store_htlc<Host: Runtime>(host: &mut Host, id: &str, htlc: &Htlc) -> Result<()> {
+    let path = get_htlc_path(id)?;
+    store_json(host, &path, htlc)
+}
+
+pub fn delete_htlc<Host: Runtime>(host: &mut Host, id: &str) -> Result<()> {
+    let path = get_htlc_path(id)?;
+    host.store_delete(&path).map_err(Error::from)
+}
+
+pub fn read_pool<Host: Runtime>(host: &mut Host, id: &str) -> Result<Option<Pool>> {
+    let path = get_pool_path(id)?;
+    read_json(host, &path)
+}
+
+/// Stores a pool, or deletes it once all its shares are burned so that it can be created again
+/// No minimum liquidity is locked: the shares added are exact and paid with amounts rounded up
+/// within max_a and max_b, so inflating the value of a share makes a deposit fail, not lose value
+pub fn store_pool<Host: Runtime>(host: &mut Host, pool: &Pool) -> Result<()> {
+    let path = get_pool_path(&pool.id())?;
+    if pool.shares == 0 {
+        delete(host, &path)
+    } else {
+        store_json(host, &path, pool)
+    }
+}
+
+pub fn read_vesting<Host: Runtime>(host: &mut Host, id: &str) -> Result<Option<Vesting>> {
+    let path = get_vesting_path(id)?;
+    read_json(host, &path)
+}
+
+/// Stores a schedule, or deletes it once everything has been claimed
+pub fn store_vesting<Host: Runtime>(
+    host: &mut Host,
+    id: &str,
+    vesting: Option<&Vesting>,
+) -> Result<()> {
+    let path = get_vesting_path(id)?;
+    match vesting {
+        Some(vesting) => store_json(host, &path, vesting),
+        None => delete(host, &path),
+    }
+}
+
+pub fn read_stream<Host: Runtime>(host: &mut Host, id: &str) -> Result<Option<Stream>> {
+    let path = get_stream_path(id)?;
+    read_json(host, &path)
+}
+
+/// Stores a stream, or deletes it when it is cancelled
+pub fn store_stream<Host: Runtime>(
+    host: &mut Host,
+    id: &str,
+    stream: Option<&Stream>,
+) -> Result<()> {
+    let path = get_stream_path(id)?;
+    match stream {
+        Some(stream) => store_json(host, &path, stream),
+        None => delete(host, &path),
+    }
+}
+
+pub fn read_session_key<Host: Runtime>(
+    host: &mut Host,
+    account: &PublicKeyHash,
+    key: &PublicKeyHash,
+    token: &Token,
+) -> Result<Option<SessionKey>> {
+    let path = get_session_key_path(account, key, token)?;
+    read_json(host, &path)
+}
+
+/// Stores the limit of a session key, an exhausted key does not take space in the storage
+pub fn store_session_key<Host: Runtime>(
+    host: &mut Host,
+    account: &PublicKeyHash,
+    key: &PublicKeyHash,
+    token: &Token,
+    session: &SessionKey,
+) -> Result<()> {
+    let path = get_session_key_path(account, key, token)?;
+    if session.remaining == 0 {
+        delete(host, &path)
+    } else {
+        store_json(host, &path, session)
+    }
+}
+
+/// Returns the key allowed to sign for the account, if it has been rotated
+/// An account without registered key is signed for by the key it is derived from
+pub fn read_public_key<Host: Runtime>(
+    host: &mut Host,
+    account: &PublicKeyHash,
+) -> Result<Option<PublicKey>> {
+    let path = get_public_key_path(account)?;
+    read_json(host, &path)
+}
+
+pub fn store_public_key<Host: Runtime>(
+    host: &mut Host,
+    account: &PublicKeyHash,
+    key: &PublicKey,
+) -> Result<()> {
+    let path = get_public_key_path(account)?;
+    store_json(host, &path, key)
+}
+
+/// Returns the identifier of the next order
+pub fn read_orders_length<Host: Runtime>(host: &mut Host) -> Result<u64> {
+    Ok(read_u64(host, &ORDERS_LENGTH)?.unwrap_or_default())
+}
+
+pub fn store_orders_length<Host: Runtime>(host: &mut Host, length: u64) -> Result<()> {
+    store_u64(host, &ORDERS_LENGTH, &length)
+}
+
+pub fn read_order<Host: Runtime>(host: &mut Host, id: u64) -> Result<Option<Order>> {
+    let path = get_order_path(id)?;
+    read_json(host, &path)
+}
+
+/// Stores a resting order, or deletes it when it has been filled or cancelled
+pub fn store_order<Host: Runtime>(host: &mut Host, id: u64, order: Option<&Order>) -> Result<()> {
+    let path = get_order_path(id)?;
+    match order {
+        Some(order) => store_json(host, &path, order),
+        None => delete(host, &path),
+    }
+}
+
+/// Returns the price levels of a side of the market, best price first
+pub fn read_prices<Host: Runtime>(host: &mut Host, market: &str, side: Side) -> Result<Vec<u128>> {
+    let path = get_prices_path(market, side)?;
+    Ok(read_json(host, &path)?.unwrap_or_default())
+}
+
+pub fn store_prices<Host: Runtime>(
+    host: &mut Host,
+    market: &str,
+    side: Side,
+    prices: &Vec<u128>,
+) -> Result<()> {
+    let path = get_prices_path(market, side)?;
+    if prices.is_empty() {
+        delete(host, &path)
+    } else {
+        store_json(host, &path, prices)
+    }
+}
+
+/// Returns the identifiers of the orders resting at a price, oldest first
+pub fn read_price_level<Host: Runtime>(
+    host: &mut Host,
+    market: &str,
+    side: Side,
+    price: u128,
+) -> Result<Vec<u64>> {
+    let path = get_price_level_path(market, side, price)?;
+    Ok(read_json(host, &path)?.unwrap_or_default())
+}
+
+pub fn store_price_level<Host: Runtime>(
+    host: &mut Host,
+    market: &str,
+    side: Side,
+    price: u128,
+    orders: &Vec<u64>,
+) -> Result<()> {
+    let path = get_price_level_path(market, side, price)?;
+    if orders.is_empty() {
+        delete(host, &path)
+    } else {
+        store_json(host, &path, orders)
+    }
+}
+
+/// Appends the receipt of a fill
+pub fn push_fill<Host: Runtime>(host: &mut Host, fill: &Fill) -> Result<()> {
+    let length = read_u64(host, &FILLS_LENGTH)?.unwrap_or_default();
+    let path = get_fill_path(length)?;
+    store_json(host, &path, fill)?;
+    store_u64(host, &FILLS_LENGTH, &(length + 1))
+}
+
+pub fn is_paused<Host: Runtime>(host: &mut Host, kind: &OperationKind) -> Result<bool> {
+    let path = get_paused_path(kind)?;
+    exists(host, &path)
+}
+
+pub fn store_paused<Host: Runtime>(
+    host: &mut Host,
+    kind: &OperationKind,
+    paused: bool,
+) -> Result<()> {
+    let path = get_paused_path(kind)?;
+    if paused {
+        host.store_write(&path, &[1], 0).map_err(Error::from)
+    } else if exists(host, &path)? {
+        host.store_delete(&path).map_err(Error::from)
+    } else {
+        Ok(())
+    }
+}
+
+/// Queues a deposit received while deposits are paused
+pub fn push_pending_deposit<Host: Runtime>(host: &mut Host, deposit: &BridgeMessage) -> Result<()> {
+    let length = read_u64(host, &PENDING_DEPOSITS_LENGTH)?.unwrap_or_default();
+    let path = get_pending_deposit_path(length)?;
+    store_json(host, &path, deposit)?;
+    store_u64(host, &PENDING_DEPOSITS_LENGTH, &(length + 1))
+}
+
+/// Returns the oldest deposit queued while deposits were paused, without removing it
+pub fn read_pending_deposit<Host: Runtime>(host: &mut Host) -> Result<Option<BridgeMessage>> {
+    let head = read_u64(host, &PENDING_DEPOSITS_HEAD)?.unwrap_or_default();
+    let length = read_u64(host, &PENDING_DEPOSITS_LENGTH)?.unwrap_or_default();
+    if head >= length {
+        return Ok(None);
+    }
+
+    let path = get_pending_deposit_path(head)?;
+    match read_json(host, &path)? {
+        Some(deposit) => Ok(Some(deposit)),
+        None => Err(Error::StateDeserializarion),
+    }
+}
+
+/// Removes the oldest queued deposit, once it has been credited
+pub fn pop_pending_deposit<Host: Runtime>(host: &mut Host) -> Result<()> {
+    let head = read_u64(host, &PENDING_DEPOSITS_HEAD)?.unwrap_or_default();
+    let length = read_u64(host, &PENDING_DEPOSITS_LENGTH)?.unwrap_or_default();
+    if head >= length {
+        return Ok(());
+    }
+
+    delete(host, &get_pending_deposit_path(head)?)?;
+    if head + 1 == length {
+        // The queue is empty, its indexes start over
+        delete(host, &PENDING_DEPOSITS_HEAD)?;
+        delete(host, &PENDING_DEPOSITS_LENGTH)
+    } else {
+        store_u64(host, &PENDING_DEPOSITS_HEAD, &(head + 1))
+    }
+}
+
+/// Returns the admin, which can be written at /admin when the kernel is installed
+/// ADMIN_ADDRESS is the admin otherwise
+pub fn read_admin<Host: Runtime>(host: &mut Host) -> Result<PublicKeyHash> {
+    if !exists(host, &ADMIN)? {
+        return PublicKeyHash::from_b58(ADMIN_ADDRESS);
+    }
+
+    let size = host.store_value_size(&ADMIN).map_err(Error::from)?;
+    let data = host.store_read(&ADMIN, 0, size).map_err(Error::from)?;
+    let admin = String::from_utf8(data).map_err(Error::FromUtf8)?;
+    PublicKeyHash::from_b58(&admin)
+}
+
+/// Returns true if the tickets of the ticketer can be deposited and withdrawn
+/// The bridges are allowed without being stored
+pub fn is_ticketer_allowed<Host: Runtime>(host: &mut Host, ticketer: &str) -> Result<bool> {
+    if ticketer == L1_BRIDGE_CONTRACT_ADDRESS || ticketer == L1_TEZ_BRIDGE_CONTRACT_ADDRESS {
+        return Ok(true);
+    }
+    let path = get_ticketer_path(ticketer)?;
+    exists(host, &path)
+}
+
+pub fn store_ticketer_allowed<Host: Runtime>(
+    host: &mut Host,
+    ticketer: &str,
+    allowed: bool,
+) -> Result<()> {
+    let path = get_ticketer_path(ticketer)?;
+    if allowed {
+        host.store_write(&path, &[1], 0).map_err(Error::from)
+    } else if exists(host, &path)? {
+        host.store_delete(&path).map_err(Error::from)
+    } else {
+        Ok(())
+    }
+}
+
+/// Returns true if the account is frozen for all the tokens or for the given one
+pub fn is_frozen<Host: Runtime>(
+    host: &mut Host,
+    account: &PublicKeyHash,
+    token: &Token,
+) -> Result<bool> {
+    let global = get_frozen_path(account, None)?;
+    let per_token = get_frozen_path(account, Some(token))?;
+    Ok(exists(host, &global)? || exists(host, &per_token)?)
+}
+
+pub fn store_frozen<Host: Runtime>(
+    host: &mut Host,
+    account: &PublicKeyHash,
+    token: Option<&Token>,
+    frozen: bool,
+) -> Result<()> {
+    let path = get_frozen_path(account, token)?;
+    if frozen {
+        host.store_write(&path, &[1], 0).map_err(Error::from)
+    } else if exists(host, &path)? {
+        host.store_delete(&path).map_err(Error::from)
+    } else {
+        Ok(())
+    }
+}
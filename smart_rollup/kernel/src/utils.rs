@@ -1,143 +1,1391 @@
 use crate::constants::{
-    DAPP_URL, EXTERNAL_MESSAGE_REP, L1_BRIDGE_CONTRACT_ADDRESS, MICHELINE_EXPRESSION_BYTE,
-    MICHELINE_STRING_BYTE, TEZOS_SIGNED_MESSAGE,
+    EXTERNAL_MESSAGE_REP, MAX_ORDERS_PER_PRICE_LEVEL, MAX_ORDER_MATCHES,
+    MAX_PENDING_DEPOSITS_PER_LEVEL, MAX_PRICE_LEVELS, MIN_ORDER_AMOUNT,
 };
-use crate::core::message::{BridgeMessage, TransferContent, TransferMessage};
+use crate::core::book::{insert_price, market_account, quote_amount, Fill, Order, Side};
+use crate::core::htlc::{deposit_lock_id, htlc_id, Htlc};
+use crate::core::message::{
+    AdminContent, AdminMessage, ApproveContent, ApproveMessage, AuthorizeSessionKeyMessage,
+    BridgeMessage, Content, DepositAction, DepositPayload, HtlcContent, HtlcMessage, Inner,
+    MultisigContent, MultisigMessage, OperationKind, OperatorUpdate, OrderContent, OrderMessage,
+    PoolContent, PoolMessage, RefundMessage, RotateKeyMessage, SignedMessage, SponsorContent,
+    SponsoredMessage, StreamContent, StreamMessage, SwapContent, SwapMessage, TicketDeposit,
+    TicketTransfer, TransferContent, TransferFromContent, TransferFromMessage, TransferMessage,
+    UpdateOperatorsMessage, VestingContent, VestingMessage, WithdrawContent, WithdrawMessage,
+};
+use crate::core::multisig::Multisig;
 use crate::core::nonce::Nonce;
+use crate::core::pool::{initial_shares, pool_id, Pool, MAX_FEE};
 use crate::core::public_key_hash::PublicKeyHash;
+use crate::core::session::SessionKey;
+use crate::core::stream::{stream_id, Stream};
+use crate::core::token::Token;
+use crate::core::vesting::{vesting_id, Vesting};
 use crate::core::{error::*, message::Message};
-use crate::storage::{read_balance, read_nonce, store_balance, store_nonce};
+use crate::outbox::{write_release, write_return};
+use crate::storage::{
+    delete_htlc, is_frozen, is_operator, is_paused, is_ticketer_allowed, pop_pending_deposit,
+    push_fill, push_pending_deposit, read_admin, read_allowance, read_balance, read_htlc,
+    read_level, read_nonce, read_order, read_orders_length, read_pending_deposit, read_pool,
+    read_price_level, read_prices, read_public_key, read_session_key, read_stream, read_vesting,
+    store_allowance, store_balance, store_frozen, store_htlc, store_level, store_nonce,
+    store_operator, store_order, store_orders_length, store_paused, store_pool, store_price_level,
+    store_prices, store_public_key, store_session_key, store_stream, store_ticketer_allowed,
+    store_vesting,
+};
 use tezos_smart_rollup::{
     host::Runtime,
     inbox::{InboxMessage, InternalInboxMessage},
-    michelson::{ticket::BytesTicket, MichelsonContract, MichelsonPair},
+    michelson::{
+        MichelsonBytes, MichelsonNat, MichelsonOption, MichelsonPair, MichelsonString,
+        MichelsonUnit,
+    },
 };
 
 pub fn read_input<Host: Runtime>(host: &mut Host) -> std::result::Result<Message, ReadInputError> {
     let input = host.read_input().map_err(ReadInputError::Runtime)?;
     match input {
         None => Err(ReadInputError::EndOfInbox),
-        Some(msg) => {
-            match <InboxMessage<MichelsonPair<BytesTicket, MichelsonContract>>>::parse(msg.as_ref())
-            {
-                Ok((_, parsed_message)) => {
-                    match parsed_message {
-                        // Internal bridge transfer
-                        InboxMessage::Internal(t) => {
-                            match t {
-                                InternalInboxMessage::Transfer(transfer) => {
-                                    if transfer.sender.to_string() != L1_BRIDGE_CONTRACT_ADDRESS {
-                                        Err(ReadInputError::NotFromBridge)
-                                    } else {
-                                        Ok(Message::Bridge(BridgeMessage::from(transfer.payload)))
-                                    }
-                                }
-                                // Other internal messages can be ignored
-                                _ => Err(ReadInputError::NotForKernel),
-                            }
-                        }
-                        // External transfer transaction
-                        InboxMessage::External(data) => {
-                            match data {
-                                [EXTERNAL_MESSAGE_REP, ..] => {
-                                    let transfer_bytes = data.iter().skip(1).copied().collect();
-                                    let str = String::from_utf8(transfer_bytes)
-                                        .map_err(ReadInputError::FromUtf8Error)?;
-                                    let msg = serde_json_wasm::from_str(&str)
-                                        .map_err(ReadInputError::SerdeJson)?;
-                                    Ok(msg)
-                                }
-                                _ => Err(ReadInputError::NotForKernel), // TODO: this can be more specific
-                            }
+        Some(msg) => parse_input(msg.level, msg.as_ref()),
+    }
+}
+
+/// Decodes the bytes of an inbox message, read at the given level, to a message of the kernel
+pub fn parse_input(level: u32, input: &[u8]) -> std::result::Result<Message, ReadInputError> {
+    // The shapes of the deposits can't be mistaken with each other, they are tried in turn
+    let deposit = parse_deposit::<DepositPayload>(input)
+        .or_else(|| parse_deposit::<TicketDeposit<MichelsonBytes>>(input))
+        .or_else(|| parse_deposit::<TicketDeposit<MichelsonUnit>>(input))
+        .or_else(|| parse_deposit::<TicketDeposit<MichelsonString>>(input))
+        .or_else(|| parse_deposit::<TicketDeposit<MichelsonNat>>(input))
+        .or_else(|| {
+            parse_deposit::<
+                TicketDeposit<MichelsonPair<MichelsonNat, MichelsonOption<MichelsonBytes>>>,
+            >(input)
+        });
+    if let Some(deposit) = deposit {
+        return deposit;
+    }
+
+    match <InboxMessage<MichelsonUnit>>::parse(input) {
+        Ok((_, parsed_message)) => {
+            match parsed_message {
+                InboxMessage::Internal(t) => {
+                    match t {
+                        // The level is kept for the operations with a timeout
+                        InternalInboxMessage::InfoPerLevel(_) => Ok(Message::Level(level)),
+                        // Other internal messages, as unsupported tickets, can be ignored
+                        _ => Err(ReadInputError::NotForKernel),
+                    }
+                }
+                // External transfer transaction
+                InboxMessage::External(data) => {
+                    match data {
+                        [EXTERNAL_MESSAGE_REP, ..] => {
+                            let transfer_bytes = data.iter().skip(1).copied().collect();
+                            let str = String::from_utf8(transfer_bytes)
+                                .map_err(ReadInputError::FromUtf8Error)?;
+                            let msg = serde_json_wasm::from_str(&str)
+                                .map_err(ReadInputError::SerdeJson)?;
+                            Ok(msg)
                         }
+                        _ => Err(ReadInputError::NotForKernel), // TODO: this can be more specific
                     }
                 }
-                Err(_) => Err(ReadInputError::NotForKernel),
             }
         }
+        Err(_) => Err(ReadInputError::NotForKernel),
     }
 }
 
-pub fn process_bridge_message<Host: Runtime>(
+/// Decodes the transfer of a ticket to a deposit, None if the input is not such a transfer
+/// Any contract can deposit tickets, they are credited by ticketer and content once the
+/// ticketer is allowed, see check_ticketer
+/// A ticket which can't be credited is refunded to the L1 account which sent it
+fn parse_deposit<Payload: TicketTransfer>(
+    input: &[u8],
+) -> Option<std::result::Result<Message, ReadInputError>>
+where
+    BridgeMessage: TryFrom<Payload, Error = Error>,
+{
+    match <InboxMessage<Payload>>::parse(input) {
+        Ok((_, InboxMessage::Internal(InternalInboxMessage::Transfer(transfer)))) => {
+            let source = PublicKeyHash::from_b58(&transfer.source.to_b58check()).ok();
+            let refund = transfer.payload.ticket().and_then(|(token, amount)| {
+                Ok(RefundMessage {
+                    destination: source.clone().ok_or(Error::StateDeserializarion)?,
+                    token,
+                    amount,
+                })
+            });
+            let deposit = match BridgeMessage::try_from(transfer.payload) {
+                Ok(deposit) => Ok(Message::Bridge(BridgeMessage { source, ..deposit })),
+                Err(err) => refund
+                    .map(Message::Refund)
+                    .map_err(|_| ReadInputError::InvalidDeposit(err)),
+            };
+            Some(deposit)
+        }
+        _ => None,
+    }
+}
+
+/// Verifies the signature of a message and returns the account that signed it
+/// The account is the one named in the inner, or the one derived from the signing key
+fn verify_signature<Host: Runtime, C: Content>(
     host: &mut Host,
-    message: BridgeMessage,
+    message: &SignedMessage<C>,
+) -> Result<PublicKeyHash> {
+    let sig = message.signature();
+    let pk = message.public_key();
+    let data = message.inner().signing_payload(&message.timestamp);
+
+    sig.verify(pk, &data)?;
+
+    let signer = PublicKeyHash::from(pk);
+    let account = message
+        .inner()
+        .account
+        .clone()
+        .unwrap_or_else(|| signer.clone());
+
+    // Once rotated, only the registered key can sign for the account, not even the one it is derived from
+    match read_public_key(host, &account)? {
+        Some(key) if &key == pk => Ok(account),
+        None if account == signer => Ok(account),
+        _ => Err(Error::KeyRotated),
+    }
+}
+
+/// Checks that the nonce of the inner follows the one of the account on the same lane
+fn check_nonce<Host: Runtime, C>(
+    host: &mut Host,
+    account: &PublicKeyHash,
+    inner: &Inner<C>,
 ) -> Result<()> {
+    let nonce = Nonce(read_nonce(host, account, inner.lane())?);
+
+    if &nonce.next() != inner.nonce() {
+        return Err(Error::InvalidNonce);
+    }
+    Ok(())
+}
+
+/// Checks that the nonce of the inner follows the one of the account on the same lane and stores it
+fn update_nonce<Host: Runtime, C>(
+    host: &mut Host,
+    account: &PublicKeyHash,
+    inner: &Inner<C>,
+) -> Result<()> {
+    check_nonce(host, account, inner)?;
+
+    // Update the nonce
+    store_nonce(host, account, inner.lane(), &inner.nonce().0)
+}
+
+/// Credits the account of a deposit, then runs its action
+/// A frozen account can't be credited, the deposit is then refunded to its L1 sender
+fn apply_deposit<Host: Runtime>(host: &mut Host, message: &BridgeMessage) -> Result<()> {
+    if is_frozen(host, &message.account, &message.token)? {
+        return Err(Error::AccountFrozen);
+    }
+
     // Simply update the existing balance of the account
     let current_balance = read_balance(host, &message.account, &message.token)?;
-    store_balance(
-        host,
-        &message.account,
-        &message.token,
-        &(current_balance + message.amount),
-    )
+    let balance = current_balance
+        .checked_add(message.amount)
+        .ok_or(Error::InvalidAmount)?;
+    store_balance(host, &message.account, &message.token, &balance)?;
+
+    // The deposit is kept by the account when its action fails
+    if let Some(action) = &message.action {
+        if let Err(err) = apply_deposit_action(host, message, action) {
+            host.write_debug(&format!(
+                "Deposit action cannot be applied: {}\n",
+                err.to_string()
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Spends the deposited tokens on behalf of the credited account
+fn apply_deposit_action<Host: Runtime>(
+    host: &mut Host,
+    message: &BridgeMessage,
+    action: &DepositAction,
+) -> Result<()> {
+    if is_paused(host, &OperationKind::Transfer)? {
+        return Err(Error::Paused);
+    }
+
+    let BridgeMessage {
+        account,
+        token,
+        amount,
+        ..
+    } = message;
+    match action {
+        DepositAction::Transfer { destination } => {
+            move_balance(host, account, destination, token, *amount)
+        }
+        DepositAction::Swap { token_out, min_out } => {
+            swap(host, account, token, *amount, token_out, *min_out)
+        }
+        DepositAction::Lock {
+            recipient,
+            hashlock,
+            timeout_level,
+        } => {
+            let htlc = Htlc {
+                sender: account.clone(),
+                recipient: recipient.clone(),
+                token: token.clone(),
+                amount: *amount,
+                hashlock: hashlock.clone(),
+                timeout_level: *timeout_level,
+            };
+            let id = deposit_lock_id(&htlc);
+            if read_htlc(host, &id)?.is_some() {
+                return Err(Error::HtlcExists);
+            }
+            escrow(host, &id, &htlc)
+        }
+    }
+}
+
+/// Tickets are only credited and released once the admin allowed their ticketer
+/// The release entrypoint of any other contract is not trusted with the tickets
+fn check_ticketer<Host: Runtime>(host: &mut Host, token: &Token) -> Result<()> {
+    match token {
+        Token::Ticket { ticketer, .. } if !is_ticketer_allowed(host, ticketer)? => {
+            Err(Error::TicketerNotAllowed)
+        }
+        _ => Ok(()),
+    }
+}
+
+/// A deposit which can't be credited, as a ticket of a ticketer which is not allowed,
+/// is refunded to the L1 account which sent it
+pub fn process_bridge_message<Host: Runtime>(
+    host: &mut Host,
+    message: BridgeMessage,
+) -> Result<()> {
+    if message.invalid_action {
+        host.write_debug("Deposit action cannot be decoded, it is ignored\n");
+    }
+    if check_ticketer(host, &message.token).is_err() {
+        return refund_deposit(host, message);
+    }
+
+    // The ticket has already been received by the rollup, so the deposit is queued
+    // until deposits are resumed, see apply_pending_deposits
+    let credited = if is_paused(host, &OperationKind::Deposit)? {
+        push_pending_deposit(host, &message)
+    } else {
+        apply_deposit(host, &message)
+    };
+    credited.or_else(|_| refund_deposit(host, message))
 }
 
 pub fn process_transfer_message<Host: Runtime>(
     host: &mut Host,
     message: TransferMessage,
 ) -> Result<()> {
-    let sig = message.signature();
-    let pk = message.public_key();
+    if is_paused(host, &OperationKind::Transfer)? {
+        return Err(Error::Paused);
+    }
+
+    let pkh = verify_signature(host, &message)?;
     let inner = message.inner();
-    let hash = inner.hash();
-
-    let bytes = vec![
-        TEZOS_SIGNED_MESSAGE.to_string(),
-        DAPP_URL.to_string(),
-        message.timestamp.clone(),
-        hash.to_string(),
-    ]
-    .join(" ")
-    .as_bytes()
-    .iter()
-    .map(|byte| format!("{:02x}", byte))
-    .collect::<String>();
-    let bytes_length = format!("{:08x}", (bytes.len() / 2));
-
-    let data = vec![
-        MICHELINE_EXPRESSION_BYTE.to_string(),
-        MICHELINE_STRING_BYTE.to_string(),
-        bytes_length,
-        bytes,
-    ]
-    .join("");
-
-    fn hex_to_string(hex: &str) -> Vec<u8> {
-        let bytes = (0..hex.len())
-            .step_by(2)
-            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
-            .collect::<Vec<u8>>();
-        bytes
-    }
-
-    sig.verify(pk, &hex_to_string(data.as_str()))?;
-
-    let pkh = PublicKeyHash::from(pk);
-    let nonce = Nonce(read_nonce(host, &pkh)?);
+    let TransferContent {
+        token,
+        destination,
+        amount,
+        source,
+    } = &inner.content;
 
-    if &nonce.next() != inner.nonce() {
-        return Err(Error::InvalidNonce);
+    // A session key signs with the nonces of the account it transfers from
+    let source = source.as_ref().unwrap_or(&pkh);
+    if source == &pkh {
+        update_nonce(host, &pkh, inner)?;
+        return move_balance(host, &pkh, destination, token, *amount);
     }
 
-    // Update the nonce
-    store_nonce(host, &pkh, &inner.nonce().0)?;
+    // The key is checked before the nonce, so that any key can't consume the nonces of the account
+    let mut session = read_session_key(host, source, &pkh, token)?.ok_or(Error::NotSessionKey)?;
+    session.spend(*amount, read_level(host)?)?;
+    update_nonce(host, source, inner)?;
 
-    let TransferContent {
+    check_move(host, source, destination, token, *amount)?;
+    store_session_key(host, source, &pkh, token, &session)?;
+    move_balance(host, source, destination, token, *amount)
+}
+
+pub fn process_multisig_message<Host: Runtime>(
+    host: &mut Host,
+    message: MultisigMessage,
+) -> Result<()> {
+    let MultisigMessage {
+        keys,
+        threshold,
+        signatures,
+        inner,
+    } = message;
+
+    let kind = match &inner.content {
+        MultisigContent::Transfer(_) => OperationKind::Transfer,
+        MultisigContent::Withdraw(_) => OperationKind::Withdraw,
+    };
+    if is_paused(host, &kind)? {
+        return Err(Error::Paused);
+    }
+
+    // The address is derived from the keys, and signed as the account of the inner
+    // Otherwise the signatures of a key would be valid for every multisig sharing it
+    let multisig = Multisig::new(keys, threshold)?;
+    let address = multisig.address()?;
+    if inner.account.as_ref() != Some(&address) {
+        return Err(Error::InvalidSignature);
+    }
+    // A multisig only spends from its own address
+    if let MultisigContent::Transfer(TransferContent {
+        source: Some(_), ..
+    }) = &inner.content
+    {
+        return Err(Error::InvalidSignature);
+    }
+    multisig.verify(&signatures, &inner)?;
+
+    update_nonce(host, &address, &inner)?;
+
+    match &inner.content {
+        MultisigContent::Transfer(TransferContent {
+            token,
+            destination,
+            amount,
+            ..
+        }) => move_balance(host, &address, destination, token, *amount),
+        MultisigContent::Withdraw(content) => withdraw(host, &address, content),
+    }
+}
+
+pub fn process_rotate_key_message<Host: Runtime>(
+    host: &mut Host,
+    message: RotateKeyMessage,
+) -> Result<()> {
+    let account = verify_signature(host, &message)?;
+    let inner = message.inner();
+    update_nonce(host, &account, inner)?;
+
+    store_public_key(host, &account, &inner.content.key)
+}
+
+pub fn process_authorize_session_key_message<Host: Runtime>(
+    host: &mut Host,
+    message: AuthorizeSessionKeyMessage,
+) -> Result<()> {
+    let pkh = verify_signature(host, &message)?;
+    let inner = message.inner();
+    update_nonce(host, &pkh, inner)?;
+
+    let content = &inner.content;
+    let session = SessionKey {
+        remaining: content.max_amount,
+        expires_level: content.expires_level,
+    };
+    store_session_key(host, &pkh, &content.key, &content.token, &session)
+}
+
+/// Checks that tokens can be moved between two L2 accounts, none of them can be frozen
+fn check_move<Host: Runtime>(
+    host: &mut Host,
+    source: &PublicKeyHash,
+    destination: &PublicKeyHash,
+    token: &Token,
+    amount: u128,
+) -> Result<()> {
+    if is_frozen(host, source, token)? || is_frozen(host, destination, token)? {
+        return Err(Error::AccountFrozen);
+    }
+
+    if read_balance(host, source, token)? < amount {
+        return Err(Error::InvalidTransferAmount);
+    }
+    // The balance of the destination can't overflow, a transfer to oneself leaves it unchanged
+    if source != destination {
+        read_balance(host, destination, token)?
+            .checked_add(amount)
+            .ok_or(Error::InvalidAmount)?;
+    }
+    Ok(())
+}
+
+/// Moves tokens between two L2 accounts, none of them can be frozen
+fn move_balance<Host: Runtime>(
+    host: &mut Host,
+    source: &PublicKeyHash,
+    destination: &PublicKeyHash,
+    token: &Token,
+    amount: u128,
+) -> Result<()> {
+    check_move(host, source, destination, token, amount)?;
+
+    // Update balances by making a transfer
+    let source_balance = read_balance(host, source, token)?;
+    store_balance(host, source, token, &(source_balance - amount))?;
+    // Read after the debit so that a transfer to oneself leaves the balance unchanged
+    let destination_balance = read_balance(host, destination, token)?
+        .checked_add(amount)
+        .ok_or(Error::InvalidAmount)?;
+    store_balance(host, destination, token, &destination_balance)
+}
+
+pub fn process_approve_message<Host: Runtime>(
+    host: &mut Host,
+    message: ApproveMessage,
+) -> Result<()> {
+    let pkh = verify_signature(host, &message)?;
+    let inner = message.inner();
+    update_nonce(host, &pkh, inner)?;
+
+    let ApproveContent {
+        spender,
+        token,
+        amount,
+    } = &inner.content;
+
+    // As in FA1.2, an allowance has to be reset before being changed,
+    // so that the spender can't use both the previous and the new one
+    let allowance = read_allowance(host, &pkh, spender, token)?;
+    if allowance > 0 && *amount > 0 {
+        return Err(Error::UnsafeAllowanceChange);
+    }
+
+    store_allowance(host, &pkh, spender, token, amount)
+}
+
+pub fn process_transfer_from_message<Host: Runtime>(
+    host: &mut Host,
+    message: TransferFromMessage,
+) -> Result<()> {
+    if is_paused(host, &OperationKind::Transfer)? {
+        return Err(Error::Paused);
+    }
+
+    // The message is signed by the spender or by an operator
+    let pkh = verify_signature(host, &message)?;
+    let inner = message.inner();
+    update_nonce(host, &pkh, inner)?;
+
+    let TransferFromContent {
+        owner,
+        token,
+        destination,
+        amount,
+    } = &inner.content;
+
+    // Neither the owner nor its operators need any allowance
+    if &pkh == owner || is_operator(host, owner, &pkh, token)? {
+        return move_balance(host, owner, destination, token, *amount);
+    }
+
+    let allowance = read_allowance(host, owner, &pkh, token)?;
+    if allowance < *amount {
+        return Err(Error::NotEnoughAllowance);
+    }
+
+    move_balance(host, owner, destination, token, *amount)?;
+    store_allowance(host, owner, &pkh, token, &(allowance - amount))
+}
+
+pub fn process_withdraw_message<Host: Runtime>(
+    host: &mut Host,
+    message: WithdrawMessage,
+) -> Result<()> {
+    if is_paused(host, &OperationKind::Withdraw)? {
+        return Err(Error::Paused);
+    }
+
+    let pkh = verify_signature(host, &message)?;
+    let inner = message.inner();
+    update_nonce(host, &pkh, inner)?;
+
+    withdraw(host, &pkh, &inner.content)
+}
+
+/// Burns the tokens of the account and releases them to the L1 destination
+fn withdraw<Host: Runtime>(
+    host: &mut Host,
+    account: &PublicKeyHash,
+    content: &WithdrawContent,
+) -> Result<()> {
+    let WithdrawContent {
         token,
         destination,
         amount,
+    } = content;
+
+    if token.is_synthetic() {
+        return Err(Error::NotWithdrawable);
+    }
+    check_ticketer(host, token)?;
+
+    // A frozen account can't withdraw, its funds stay on L2
+    // Withdrawals are written to the outbox right away, so those made before the freeze are out:
+    // once written, an outbox message can be executed on L1 and the freeze can't revoke it
+    if is_frozen(host, account, token)? {
+        return Err(Error::AccountFrozen);
+    }
+
+    let balance = read_balance(host, account, token)?;
+    if balance < *amount {
+        return Err(Error::InvalidTransferAmount);
+    }
+
+    // Release the tokens on L1 and burn the L2 balance, in this order so that
+    // a release which can't be written, as when the outbox is full, burns nothing
+    write_release(host, destination, token, *amount)?;
+    store_balance(host, account, token, &(balance - amount))
+}
+
+pub fn process_update_operators_message<Host: Runtime>(
+    host: &mut Host,
+    message: UpdateOperatorsMessage,
+) -> Result<()> {
+    let pkh = verify_signature(host, &message)?;
+    let inner = message.inner();
+    update_nonce(host, &pkh, inner)?;
+
+    // Only the operators of the signer can be updated
+    for update in inner.content.updates.iter() {
+        match update {
+            OperatorUpdate::Add { operator, token } => {
+                store_operator(host, &pkh, operator, token, true)?
+            }
+            OperatorUpdate::Remove { operator, token } => {
+                store_operator(host, &pkh, operator, token, false)?
+            }
+        }
+    }
+    Ok(())
+}
+
+pub fn process_swap_message<Host: Runtime>(host: &mut Host, message: SwapMessage) -> Result<()> {
+    if is_paused(host, &OperationKind::Transfer)? {
+        return Err(Error::Paused);
+    }
+
+    let SwapMessage { first, second } = &message;
+
+    // Both parties sign the same swap, each one with its own nonce
+    let content = &first.inner().content;
+    if content != &second.inner().content {
+        return Err(Error::InvalidSwap);
+    }
+    let SwapContent {
+        first: party_a,
+        first_token,
+        first_amount,
+        second: party_b,
+        second_token,
+        second_amount,
+        expires_level,
+    } = content;
+    if party_a == party_b {
+        return Err(Error::InvalidSwap);
+    }
+    if read_level(host)? >= *expires_level {
+        return Err(Error::SwapExpired);
+    }
+
+    if &verify_signature(host, first)? != party_a || &verify_signature(host, second)? != party_b {
+        return Err(Error::InvalidSignature);
+    }
+
+    // None of the nonces is consumed unless both are valid
+    check_nonce(host, party_a, first.inner())?;
+    check_nonce(host, party_b, second.inner())?;
+    update_nonce(host, party_a, first.inner())?;
+    update_nonce(host, party_b, second.inner())?;
+
+    // Both legs are checked before any of them is applied
+    check_move(host, party_a, party_b, first_token, *first_amount)?;
+    check_move(host, party_b, party_a, second_token, *second_amount)?;
+    move_balance(host, party_a, party_b, first_token, *first_amount)?;
+    move_balance(host, party_b, party_a, second_token, *second_amount)
+}
+
+/// Mints tokens of the kernel, as the shares of a pool
+fn mint<Host: Runtime>(
+    host: &mut Host,
+    account: &PublicKeyHash,
+    token: &Token,
+    amount: u128,
+) -> Result<()> {
+    let balance = read_balance(host, account, token)?;
+    let balance = balance.checked_add(amount).ok_or(Error::InvalidAmount)?;
+    store_balance(host, account, token, &balance)
+}
+
+/// Burns tokens of the kernel, as the shares of a pool
+fn burn<Host: Runtime>(
+    host: &mut Host,
+    account: &PublicKeyHash,
+    token: &Token,
+    amount: u128,
+) -> Result<()> {
+    let balance = read_balance(host, account, token)?;
+    if balance < amount {
+        return Err(Error::InvalidTransferAmount);
+    }
+    store_balance(host, account, token, &(balance - amount))
+}
+
+fn find_pool<Host: Runtime>(host: &mut Host, token_a: &Token, token_b: &Token) -> Result<Pool> {
+    read_pool(host, &pool_id(token_a, token_b))?.ok_or(Error::PoolNotFound)
+}
+
+pub fn process_pool_message<Host: Runtime>(host: &mut Host, message: PoolMessage) -> Result<()> {
+    if is_paused(host, &OperationKind::Transfer)? {
+        return Err(Error::Paused);
+    }
+
+    let pkh = verify_signature(host, &message)?;
+    let inner = message.inner();
+    update_nonce(host, &pkh, inner)?;
+
+    let level = read_level(host)?;
+
+    match &inner.content {
+        PoolContent::CreatePool {
+            token_a,
+            amount_a,
+            token_b,
+            amount_b,
+            fee,
+        } => {
+            if token_a == token_b
+                || token_a.is_synthetic()
+                || token_b.is_synthetic()
+                || *fee > MAX_FEE
+            {
+                return Err(Error::InvalidPool);
+            }
+
+            let mut pool = Pool::new(token_a, token_b, *fee);
+            if read_pool(host, &pool.id())?.is_some() {
+                return Err(Error::PoolExists);
+            }
+
+            let (amount_a, amount_b) = pool.ordered(token_a, *amount_a, *amount_b);
+            let shares = initial_shares(amount_a, amount_b)?;
+            if shares == 0 {
+                return Err(Error::InvalidAmount);
+            }
+
+            let account = pool.account()?;
+            check_move(host, &pkh, &account, &pool.token_a, amount_a)?;
+            check_move(host, &pkh, &account, &pool.token_b, amount_b)?;
+            move_balance(host, &pkh, &account, &pool.token_a, amount_a)?;
+            move_balance(host, &pkh, &account, &pool.token_b, amount_b)?;
+
+            mint(host, &pkh, &pool.lp_token(), shares)?;
+            pool.shares = shares;
+            store_pool(host, &pool)
+        }
+        PoolContent::AddLiquidity {
+            token_a,
+            max_a,
+            token_b,
+            max_b,
+            shares,
+            deadline_level,
+        } => {
+            if level > *deadline_level {
+                return Err(Error::DeadlineReached);
+            }
+
+            let mut pool = find_pool(host, token_a, token_b)?;
+            let account = pool.account()?;
+            let reserve_a = read_balance(host, &account, &pool.token_a)?;
+            let reserve_b = read_balance(host, &account, &pool.token_b)?;
+
+            let (amount_a, amount_b) = pool.deposit_amounts(*shares, reserve_a, reserve_b)?;
+            let (max_a, max_b) = pool.ordered(token_a, *max_a, *max_b);
+            if amount_a > max_a || amount_b > max_b {
+                return Err(Error::Slippage);
+            }
+            let total_shares = pool
+                .shares
+                .checked_add(*shares)
+                .ok_or(Error::InvalidAmount)?;
+
+            check_move(host, &pkh, &account, &pool.token_a, amount_a)?;
+            check_move(host, &pkh, &account, &pool.token_b, amount_b)?;
+            move_balance(host, &pkh, &account, &pool.token_a, amount_a)?;
+            move_balance(host, &pkh, &account, &pool.token_b, amount_b)?;
+
+            mint(host, &pkh, &pool.lp_token(), *shares)?;
+            pool.shares = total_shares;
+            store_pool(host, &pool)
+        }
+        PoolContent::RemoveLiquidity {
+            token_a,
+            min_a,
+            token_b,
+            min_b,
+            shares,
+            deadline_level,
+        } => {
+            if level > *deadline_level {
+                return Err(Error::DeadlineReached);
+            }
+
+            let mut pool = find_pool(host, token_a, token_b)?;
+            let account = pool.account()?;
+            let reserve_a = read_balance(host, &account, &pool.token_a)?;
+            let reserve_b = read_balance(host, &account, &pool.token_b)?;
+
+            let (amount_a, amount_b) = pool.withdrawn_amounts(*shares, reserve_a, reserve_b)?;
+            let (min_a, min_b) = pool.ordered(token_a, *min_a, *min_b);
+            if amount_a < min_a || amount_b < min_b {
+                return Err(Error::Slippage);
+            }
+
+            check_move(host, &account, &pkh, &pool.token_a, amount_a)?;
+            check_move(host, &account, &pkh, &pool.token_b, amount_b)?;
+            burn(host, &pkh, &pool.lp_token(), *shares)?;
+            move_balance(host, &account, &pkh, &pool.token_a, amount_a)?;
+            move_balance(host, &account, &pkh, &pool.token_b, amount_b)?;
+
+            pool.shares -= shares;
+            store_pool(host, &pool)
+        }
+        PoolContent::Swap {
+            token_in,
+            amount_in,
+            token_out,
+            min_out,
+            deadline_level,
+        } => {
+            if level > *deadline_level {
+                return Err(Error::DeadlineReached);
+            }
+
+            swap(host, &pkh, token_in, *amount_in, token_out, *min_out)
+        }
+    }
+}
+
+/// Sells amount_in of token_in of the account for at least min_out of token_out
+fn swap<Host: Runtime>(
+    host: &mut Host,
+    pkh: &PublicKeyHash,
+    token_in: &Token,
+    amount_in: u128,
+    token_out: &Token,
+    min_out: u128,
+) -> Result<()> {
+    if token_in == token_out {
+        return Err(Error::InvalidPool);
+    }
+
+    let pool = find_pool(host, token_in, token_out)?;
+    let account = pool.account()?;
+    let reserve_in = read_balance(host, &account, token_in)?;
+    let reserve_out = read_balance(host, &account, token_out)?;
+
+    let amount_out = pool.swap_output(amount_in, reserve_in, reserve_out)?;
+    if amount_out == 0 || amount_out < min_out {
+        return Err(Error::Slippage);
+    }
+
+    check_move(host, pkh, &account, token_in, amount_in)?;
+    check_move(host, &account, pkh, token_out, amount_out)?;
+    move_balance(host, pkh, &account, token_in, amount_in)?;
+    move_balance(host, &account, pkh, token_out, amount_out)
+}
+
+/// Drops the orders which are not resting anymore from a price level
+/// The price is removed from its side of the book once no order rests at it
+fn prune_price_level<Host: Runtime>(
+    host: &mut Host,
+    market: &str,
+    side: Side,
+    price: u128,
+) -> Result<()> {
+    let mut orders = read_price_level(host, market, side, price)?;
+    let mut resting = Vec::with_capacity(orders.len());
+    for id in orders.drain(..) {
+        if read_order(host, id)?.is_some() {
+            resting.push(id);
+        }
+    }
+    store_price_level(host, market, side, price, &resting)?;
+
+    if resting.is_empty() {
+        let mut prices = read_prices(host, market, side)?;
+        prices.retain(|level| *level != price);
+        store_prices(host, market, side, &prices)?;
+    }
+    Ok(())
+}
+
+/// Matches an incoming order against the resting orders of the other side of its market
+/// Resting orders are filled best price first, then oldest first, at their own price
+/// The orders of frozen makers are cancelled instead of filled
+/// At most MAX_ORDER_MATCHES resting orders are visited, an order which still crosses the book
+/// after them is rejected before any token is moved, rather than left resting in a crossed book
+fn place_order<Host: Runtime>(host: &mut Host, mut order: Order) -> Result<()> {
+    if order.base == order.quote || order.price == 0 || order.remaining == 0 {
+        return Err(Error::InvalidOrder);
+    }
+    if order.remaining < MIN_ORDER_AMOUNT {
+        return Err(Error::OrderBelowMinimum);
+    }
+    let taker = order.owner.clone();
+    if is_frozen(host, &taker, &order.base)? || is_frozen(host, &taker, &order.quote)? {
+        return Err(Error::AccountFrozen);
+    }
+
+    let id = read_orders_length(host)?;
+    let market = order.market();
+    let escrow = market_account(&market)?;
+    let maker_side = order.side.opposite();
+
+    // Plans the fills before moving any token
+    let mut fills = Vec::new();
+    let mut cancelled = Vec::new();
+    let mut cost = 0_u128;
+    for price in read_prices(host, &market, maker_side)? {
+        if order.remaining == 0 || !order.crosses(price) {
+            break;
+        }
+        for maker_id in read_price_level(host, &market, maker_side, price)? {
+            if order.remaining == 0 {
+                break;
+            }
+            if fills.len() + cancelled.len() == MAX_ORDER_MATCHES {
+                return Err(Error::TooManyMatches);
+            }
+            let maker = read_order(host, maker_id)?.ok_or(Error::OrderNotFound)?;
+            if is_frozen(host, &maker.owner, &order.base)?
+                || is_frozen(host, &maker.owner, &order.quote)?
+            {
+                cancelled.push((maker_id, maker));
+                continue;
+            }
+            let amount = maker.remaining.min(order.remaining);
+            order.remaining -= amount;
+            cost = cost
+                .checked_add(quote_amount(amount, price)?)
+                .ok_or(Error::InvalidAmount)?;
+            fills.push((maker_id, maker, amount));
+        }
+    }
+
+    // The taker pays for the fills and escrows what is left of its order
+    let (token, needed) = match order.side {
+        Side::Buy => (&order.quote, cost.checked_add(order.escrow()?.1)),
+        Side::Sell => (
+            &order.base,
+            order.remaining.checked_add(fills_amount(&fills)),
+        ),
+    };
+    let needed = needed.ok_or(Error::InvalidAmount)?;
+    if read_balance(host, &taker, token)? < needed {
+        return Err(Error::InvalidTransferAmount);
+    }
+
+    // The values of the book are read at once, so their lengths are bounded
+    let mut resting_prices = read_prices(host, &market, order.side)?;
+    let mut resting_orders = read_price_level(host, &market, order.side, order.price)?;
+    if order.remaining > 0
+        && (resting_orders.len() >= MAX_ORDERS_PER_PRICE_LEVEL
+            || (resting_orders.is_empty() && resting_prices.len() >= MAX_PRICE_LEVELS))
+    {
+        return Err(Error::OrderBookFull);
+    }
+
+    let mut prices = Vec::new();
+    for (maker_id, maker) in cancelled {
+        // The maker is frozen, its escrow is credited back without a transfer
+        let (token, amount) = maker.escrow()?;
+        burn(host, &escrow, token, amount)?;
+        credit(host, &maker.owner, token, amount)?;
+        store_order(host, maker_id, None)?;
+
+        if !prices.contains(&maker.price) {
+            prices.push(maker.price);
+        }
+    }
+
+    let level = read_level(host)?;
+    for (maker_id, mut maker, amount) in fills {
+        let quote = quote_amount(amount, maker.price)?;
+        match order.side {
+            Side::Buy => {
+                move_balance(host, &taker, &maker.owner, &order.quote, quote)?;
+                move_balance(host, &escrow, &taker, &order.base, amount)?;
+            }
+            Side::Sell => {
+                move_balance(host, &taker, &maker.owner, &order.base, amount)?;
+                move_balance(host, &escrow, &taker, &order.quote, quote)?;
+            }
+        }
+
+        maker.remaining -= amount;
+        let resting = if maker.remaining == 0 {
+            None
+        } else {
+            Some(&maker)
+        };
+        store_order(host, maker_id, resting)?;
+
+        let fill = Fill {
+            maker_order: maker_id,
+            taker_order: id,
+            maker: maker.owner.clone(),
+            taker: taker.clone(),
+            base: order.base.clone(),
+            quote: order.quote.clone(),
+            taker_side: order.side,
+            price: maker.price,
+            amount,
+            level,
+        };
+        push_fill(host, &fill)?;
+
+        if !prices.contains(&maker.price) {
+            prices.push(maker.price);
+        }
+    }
+    for price in prices {
+        prune_price_level(host, &market, maker_side, price)?;
+    }
+
+    if order.remaining > 0 {
+        let (token, amount) = order.escrow()?;
+        move_balance(host, &taker, &escrow, token, amount)?;
+        store_order(host, id, Some(&order))?;
+
+        insert_price(&mut resting_prices, order.side, order.price);
+        store_prices(host, &market, order.side, &resting_prices)?;
+
+        resting_orders.push(id);
+        store_price_level(host, &market, order.side, order.price, &resting_orders)?;
+    }
+
+    store_orders_length(host, id + 1)
+}
+
+/// Amount of the base token filled by the planned fills
+fn fills_amount(fills: &[(u64, Order, u128)]) -> u128 {
+    fills.iter().map(|(_, _, amount)| amount).sum()
+}
+
+/// Returns the escrow of a resting order to its owner
+fn cancel_order<Host: Runtime>(host: &mut Host, owner: &PublicKeyHash, id: u64) -> Result<()> {
+    let order = read_order(host, id)?.ok_or(Error::OrderNotFound)?;
+    if &order.owner != owner {
+        return Err(Error::NotOrderOwner);
+    }
+
+    let market = order.market();
+    let escrow = market_account(&market)?;
+    let (token, amount) = order.escrow()?;
+    move_balance(host, &escrow, owner, token, amount)?;
+
+    store_order(host, id, None)?;
+    prune_price_level(host, &market, order.side, order.price)
+}
+
+pub fn process_order_message<Host: Runtime>(host: &mut Host, message: OrderMessage) -> Result<()> {
+    if is_paused(host, &OperationKind::Transfer)? {
+        return Err(Error::Paused);
+    }
+
+    let pkh = verify_signature(host, &message)?;
+    let inner = message.inner();
+    update_nonce(host, &pkh, inner)?;
+
+    match &inner.content {
+        OrderContent::Place {
+            base,
+            quote,
+            side,
+            price,
+            amount,
+        } => {
+            let order = Order {
+                owner: pkh,
+                base: base.clone(),
+                quote: quote.clone(),
+                side: *side,
+                price: *price,
+                remaining: *amount,
+            };
+            place_order(host, order)
+        }
+        OrderContent::Cancel { id } => cancel_order(host, &pkh, *id),
+    }
+}
+
+/// Checks the relayer of a sponsored message and charges its fee, returns the message it wraps
+/// The wrapped message is then processed as if it was sent on its own
+pub fn process_sponsored_message<Host: Runtime>(
+    host: &mut Host,
+    sponsored: SponsoredMessage,
+) -> Result<Message> {
+    let SponsoredMessage { message, sponsor } = sponsored;
+
+    let relayer = verify_signature(host, &sponsor)?;
+    let inner = sponsor.inner();
+    let SponsorContent {
+        account,
+        hash,
+        fee_token,
+        fee_amount,
     } = &inner.content;
 
-    // Initial balances
-    let source_balance = read_balance(host, &pkh, token)?;
-    let destination_balance = read_balance(host, destination, token)?;
+    let (signer, signed_hash) = message.signed_hash().ok_or(Error::InvalidSponsor)?;
+    if &signer != account || &signed_hash.to_string() != hash {
+        return Err(Error::InvalidSponsor);
+    }
+
+    // The nonce of the relayer is only consumed if it can pay the fee
+    let fee_account = read_admin(host)?;
+    check_nonce(host, &relayer, inner)?;
+    check_move(host, &relayer, &fee_account, fee_token, *fee_amount)?;
+    update_nonce(host, &relayer, inner)?;
+    move_balance(host, &relayer, &fee_account, fee_token, *fee_amount)?;
+    Ok(message)
+}
 
-    if source_balance < *amount {
-        Err(Error::InvalidTransferAmount)
+/// Returns the tickets of a deposit which can't be credited to the L1 account which sent them
+/// The tickets of an allowed ticketer are released by it, the other ones are sent back as is,
+/// since the release entrypoint of their ticketer is not trusted
+pub fn process_refund_message<Host: Runtime>(
+    host: &mut Host,
+    message: RefundMessage,
+) -> Result<()> {
+    let RefundMessage {
+        destination,
+        token,
+        amount,
+    } = &message;
+    if check_ticketer(host, token).is_ok() {
+        write_release(host, destination, token, *amount)
     } else {
-        // Update balances by making a transfer
-        store_balance(host, &pkh, token, &(source_balance - amount))?;
-        store_balance(host, destination, token, &(destination_balance + amount))?;
-        Ok(())
+        write_return(host, destination, token, *amount)
+    }
+}
+
+/// Keeps the level of the inbox being read, and credits the deposits queued during a pause
+pub fn process_level<Host: Runtime>(host: &mut Host, level: u32) -> Result<()> {
+    store_level(host, level)?;
+    apply_pending_deposits(host)
+}
+
+/// Credits the oldest deposits queued during a pause, a bounded number of them at each level
+/// A deposit leaves the queue before being credited, and is refunded if it can't be,
+/// so that a deposit which always fails does not hold back the ones queued after it
+fn apply_pending_deposits<Host: Runtime>(host: &mut Host) -> Result<()> {
+    if is_paused(host, &OperationKind::Deposit)? {
+        return Ok(());
+    }
+
+    for _ in 0..MAX_PENDING_DEPOSITS_PER_LEVEL {
+        match read_pending_deposit(host)? {
+            Some(deposit) => {
+                pop_pending_deposit(host)?;
+                if apply_deposit(host, &deposit).is_err() {
+                    refund_deposit(host, deposit)?;
+                }
+            }
+            None => break,
+        }
+    }
+    Ok(())
+}
+
+/// Returns the tickets of a deposit which can't be credited to the L1 account which sent them
+fn refund_deposit<Host: Runtime>(host: &mut Host, deposit: BridgeMessage) -> Result<()> {
+    let refund = deposit.refund().ok_or(Error::OutboxMessage)?;
+    process_refund_message(host, refund)
+}
+
+/// Credits the account of tokens released by a lock
+fn release_htlc<Host: Runtime>(
+    host: &mut Host,
+    account: &PublicKeyHash,
+    htlc: &Htlc,
+) -> Result<()> {
+    if is_frozen(host, account, &htlc.token)? {
+        return Err(Error::AccountFrozen);
+    }
+
+    let balance = read_balance(host, account, &htlc.token)?
+        .checked_add(htlc.amount)
+        .ok_or(Error::InvalidAmount)?;
+    store_balance(host, account, &htlc.token, &balance)
+}
+
+pub fn process_htlc_message<Host: Runtime>(host: &mut Host, message: HtlcMessage) -> Result<()> {
+    if is_paused(host, &OperationKind::Transfer)? {
+        return Err(Error::Paused);
+    }
+
+    let pkh = verify_signature(host, &message)?;
+    let inner = message.inner();
+    update_nonce(host, &pkh, inner)?;
+
+    let level = read_level(host)?;
+
+    match &inner.content {
+        HtlcContent::Lock(lock) => {
+            let htlc = Htlc {
+                sender: pkh,
+                recipient: lock.recipient.clone(),
+                token: lock.token.clone(),
+                amount: lock.amount,
+                hashlock: lock.hashlock.clone(),
+                timeout_level: lock.timeout_level,
+            };
+            escrow(host, &htlc_id(&htlc.sender, inner), &htlc)
+        }
+        HtlcContent::Claim { id, preimage } => {
+            let htlc = read_htlc(host, id)?.ok_or(Error::HtlcNotFound)?;
+            if htlc.timeout_level <= level {
+                return Err(Error::HtlcExpired);
+            }
+            if !htlc.is_unlocked_by(preimage) {
+                return Err(Error::InvalidPreimage);
+            }
+
+            release_htlc(host, &htlc.recipient, &htlc)?;
+            delete_htlc(host, id)
+        }
+        HtlcContent::Refund { id } => {
+            let htlc = read_htlc(host, id)?.ok_or(Error::HtlcNotFound)?;
+            if htlc.timeout_level > level {
+                return Err(Error::HtlcNotExpired);
+            }
+
+            release_htlc(host, &htlc.sender, &htlc)?;
+            delete_htlc(host, id)
+        }
+    }
+}
+
+/// Escrows the tokens of the sender in the lock
+fn escrow<Host: Runtime>(host: &mut Host, id: &str, htlc: &Htlc) -> Result<()> {
+    if htlc.timeout_level <= read_level(host)? {
+        return Err(Error::InvalidTimeout);
+    }
+    if is_frozen(host, &htlc.sender, &htlc.token)? {
+        return Err(Error::AccountFrozen);
+    }
+
+    let balance = read_balance(host, &htlc.sender, &htlc.token)?;
+    if balance < htlc.amount {
+        return Err(Error::InvalidTransferAmount);
+    }
+    store_balance(host, &htlc.sender, &htlc.token, &(balance - htlc.amount))?;
+    store_htlc(host, id, htlc)
+}
+
+pub fn process_vesting_message<Host: Runtime>(
+    host: &mut Host,
+    message: VestingMessage,
+) -> Result<()> {
+    if is_paused(host, &OperationKind::Transfer)? {
+        return Err(Error::Paused);
+    }
+
+    let pkh = verify_signature(host, &message)?;
+    let inner = message.inner();
+    update_nonce(host, &pkh, inner)?;
+
+    match &inner.content {
+        VestingContent::Vest(vest) => {
+            let vesting = Vesting {
+                sender: pkh,
+                beneficiary: vest.beneficiary.clone(),
+                token: vest.token.clone(),
+                total: vest.total,
+                claimed: 0,
+                start_level: vest.start_level,
+                cliff_level: vest.cliff_level,
+                end_level: vest.end_level,
+            };
+            if !vesting.is_valid_schedule() {
+                return Err(Error::InvalidSchedule);
+            }
+            if vesting.total == 0 {
+                return Err(Error::InvalidAmount);
+            }
+            if is_frozen(host, &vesting.sender, &vesting.token)? {
+                return Err(Error::AccountFrozen);
+            }
+
+            // The tokens are escrowed in the schedule
+            let balance = read_balance(host, &vesting.sender, &vesting.token)?;
+            if balance < vesting.total {
+                return Err(Error::InvalidTransferAmount);
+            }
+            store_balance(
+                host,
+                &vesting.sender,
+                &vesting.token,
+                &(balance - vesting.total),
+            )?;
+            store_vesting(host, &vesting_id(&vesting.sender, inner), Some(&vesting))
+        }
+        VestingContent::ClaimVested { id } => {
+            let mut vesting = read_vesting(host, id)?.ok_or(Error::VestingNotFound)?;
+            let amount = vesting.claimable(read_level(host)?);
+            if amount == 0 {
+                return Err(Error::NothingVested);
+            }
+            if is_frozen(host, &vesting.beneficiary, &vesting.token)? {
+                return Err(Error::AccountFrozen);
+            }
+
+            let claimed = vesting
+                .claimed
+                .checked_add(amount)
+                .ok_or(Error::InvalidAmount)?;
+            credit(host, &vesting.beneficiary, &vesting.token, amount)?;
+            vesting.claimed = claimed;
+            if vesting.claimed == vesting.total {
+                store_vesting(host, id, None)
+            } else {
+                store_vesting(host, id, Some(&vesting))
+            }
+        }
+    }
+}
+
+/// Credits the account, used to release escrowed tokens
+fn credit<Host: Runtime>(
+    host: &mut Host,
+    account: &PublicKeyHash,
+    token: &Token,
+    amount: u128,
+) -> Result<()> {
+    let balance = read_balance(host, account, token)?
+        .checked_add(amount)
+        .ok_or(Error::InvalidAmount)?;
+    store_balance(host, account, token, &balance)
+}
+
+pub fn process_stream_message<Host: Runtime>(
+    host: &mut Host,
+    message: StreamMessage,
+) -> Result<()> {
+    if is_paused(host, &OperationKind::Transfer)? {
+        return Err(Error::Paused);
+    }
+
+    let pkh = verify_signature(host, &message)?;
+    let inner = message.inner();
+    update_nonce(host, &pkh, inner)?;
+
+    let level = read_level(host)?;
+
+    match &inner.content {
+        StreamContent::OpenStream(open) => {
+            if open.rate_per_level == 0 || open.deposit == 0 {
+                return Err(Error::InvalidStream);
+            }
+            if is_frozen(host, &pkh, &open.token)? {
+                return Err(Error::AccountFrozen);
+            }
+
+            // The deposit is escrowed in the stream
+            let balance = read_balance(host, &pkh, &open.token)?;
+            if balance < open.deposit {
+                return Err(Error::InvalidTransferAmount);
+            }
+            store_balance(host, &pkh, &open.token, &(balance - open.deposit))?;
+
+            let stream = Stream {
+                sender: pkh,
+                recipient: open.recipient.clone(),
+                token: open.token.clone(),
+                rate_per_level: open.rate_per_level,
+                deposit: open.deposit,
+                withdrawn: 0,
+                start_level: level,
+            };
+            store_stream(host, &stream_id(&stream.sender, inner), Some(&stream))
+        }
+        StreamContent::WithdrawStream { id } => {
+            let mut stream = read_stream(host, id)?.ok_or(Error::StreamNotFound)?;
+            let amount = stream.withdrawable(level);
+            if amount == 0 {
+                return Err(Error::NothingStreamed);
+            }
+            if is_frozen(host, &stream.recipient, &stream.token)? {
+                return Err(Error::AccountFrozen);
+            }
+
+            let withdrawn = stream
+                .withdrawn
+                .checked_add(amount)
+                .ok_or(Error::InvalidAmount)?;
+            credit(host, &stream.recipient, &stream.token, amount)?;
+            stream.withdrawn = withdrawn;
+            if stream.withdrawn == stream.deposit {
+                store_stream(host, id, None)
+            } else {
+                store_stream(host, id, Some(&stream))
+            }
+        }
+        StreamContent::CancelStream { id } => {
+            let stream = read_stream(host, id)?.ok_or(Error::StreamNotFound)?;
+            if !stream.is_party(&pkh) {
+                return Err(Error::NotStreamParty);
+            }
+
+            let accrued = stream.accrued(level);
+            let (paid, refunded) = (accrued - stream.withdrawn, stream.deposit - accrued);
+            if is_frozen(host, &stream.recipient, &stream.token)?
+                || is_frozen(host, &stream.sender, &stream.token)?
+            {
+                return Err(Error::AccountFrozen);
+            }
+
+            credit(host, &stream.recipient, &stream.token, paid)?;
+            credit(host, &stream.sender, &stream.token, refunded)?;
+            store_stream(host, id, None)
+        }
+    }
+}
+
+pub fn process_admin_message<Host: Runtime>(host: &mut Host, message: AdminMessage) -> Result<()> {
+    let pkh = verify_signature(host, &message)?;
+    if pkh != read_admin(host)? {
+        return Err(Error::NotAdmin);
+    }
+
+    let inner = message.inner();
+    update_nonce(host, &pkh, inner)?;
+
+    match &inner.content {
+        AdminContent::Pause(kind) => store_paused(host, kind, true),
+        // The deposits received during the pause are credited from the next level
+        AdminContent::Unpause(kind) => store_paused(host, kind, false),
+        AdminContent::Freeze(freeze) => {
+            store_frozen(host, &freeze.account, freeze.token.as_ref(), true)
+        }
+        AdminContent::Unfreeze(freeze) => {
+            store_frozen(host, &freeze.account, freeze.token.as_ref(), false)
+        }
+        AdminContent::AllowTicketer(ticketer) => store_ticketer_allowed(host, ticketer, true),
+        // The tickets already deposited can't be withdrawn until the ticketer is allowed again
+        AdminContent::RevokeTicketer(ticketer) => store_ticketer_allowed(host, ticketer, false),
     }
 }
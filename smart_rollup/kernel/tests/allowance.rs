@@ -0,0 +1,169 @@
+mod common;
+
+use common::*;
+use tezos_smart_rollup_mock::MockHost;
+
+#[test]
+fn test_transfer_from_uses_the_allowance() {
+    let mut host = MockHost::default();
+    let (alice, bob, carol) = (address(ALICE), address(BOB), address(CAROL));
+
+    add_deposit(&mut host, &alice, &TOKEN, 1000);
+    add_message(&mut host, &signed_approve(ALICE, 1, &bob, &TOKEN, 300));
+    run_level(&mut host);
+
+    assert_eq!(300, allowance(&host, &alice, &bob, &TOKEN));
+
+    add_message(
+        &mut host,
+        &signed_transfer_from(BOB, 1, &alice, &TOKEN, &carol, 200),
+    );
+    run_level(&mut host);
+
+    assert_eq!(800, balance(&host, &alice, &TOKEN));
+    assert_eq!(200, balance(&host, &carol, &TOKEN));
+    assert_eq!(100, allowance(&host, &alice, &bob, &TOKEN));
+    assert_eq!(1, nonce(&host, &bob));
+}
+
+#[test]
+fn test_transfer_from_above_allowance_is_rejected() {
+    let mut host = MockHost::default();
+    let (alice, bob) = (address(ALICE), address(BOB));
+
+    add_deposit(&mut host, &alice, &TOKEN, 1000);
+    add_message(&mut host, &signed_approve(ALICE, 1, &bob, &TOKEN, 300));
+    add_message(
+        &mut host,
+        &signed_transfer_from(BOB, 1, &alice, &TOKEN, &bob, 301),
+    );
+    run_level(&mut host);
+
+    assert_eq!(1000, balance(&host, &alice, &TOKEN));
+    assert_eq!(0, balance(&host, &bob, &TOKEN));
+    assert_eq!(300, allowance(&host, &alice, &bob, &TOKEN));
+}
+
+#[test]
+fn test_transfer_from_without_allowance_is_rejected() {
+    let mut host = MockHost::default();
+    let (alice, bob) = (address(ALICE), address(BOB));
+
+    add_deposit(&mut host, &alice, &TOKEN, 1000);
+    add_message(&mut host, &signed_approve(ALICE, 1, &bob, &[0xab], 300));
+    add_message(
+        &mut host,
+        &signed_transfer_from(BOB, 1, &alice, &TOKEN, &bob, 100),
+    );
+    run_level(&mut host);
+
+    assert_eq!(1000, balance(&host, &alice, &TOKEN));
+    assert_eq!(0, balance(&host, &bob, &TOKEN));
+}
+
+#[test]
+fn test_allowance_has_to_be_reset_before_being_changed() {
+    let mut host = MockHost::default();
+    let (alice, bob) = (address(ALICE), address(BOB));
+
+    add_message(&mut host, &signed_approve(ALICE, 1, &bob, &TOKEN, 300));
+    add_message(&mut host, &signed_approve(ALICE, 2, &bob, &TOKEN, 500));
+    run_level(&mut host);
+
+    assert_eq!(300, allowance(&host, &alice, &bob, &TOKEN));
+
+    add_message(&mut host, &signed_approve(ALICE, 3, &bob, &TOKEN, 0));
+    add_message(&mut host, &signed_approve(ALICE, 4, &bob, &TOKEN, 500));
+    run_level(&mut host);
+
+    assert_eq!(500, allowance(&host, &alice, &bob, &TOKEN));
+}
+
+#[test]
+fn test_owner_can_transfer_from_without_allowance() {
+    let mut host = MockHost::default();
+    let (alice, bob) = (address(ALICE), address(BOB));
+
+    add_deposit(&mut host, &alice, &TOKEN, 1000);
+    add_message(
+        &mut host,
+        &signed_transfer_from(ALICE, 1, &alice, &TOKEN, &bob, 100),
+    );
+    run_level(&mut host);
+
+    assert_eq!(900, balance(&host, &alice, &TOKEN));
+    assert_eq!(100, balance(&host, &bob, &TOKEN));
+}
+
+#[test]
+fn test_operator_can_transfer_any_amount() {
+    let mut host = MockHost::default();
+    let (alice, bob, carol) = (address(ALICE), address(BOB), address(CAROL));
+
+    add_deposit(&mut host, &alice, &TOKEN, 1000);
+    add_message(
+        &mut host,
+        &signed_update_operator(ALICE, 1, &bob, &TOKEN, true),
+    );
+    add_message(
+        &mut host,
+        &signed_transfer_from(BOB, 1, &alice, &TOKEN, &carol, 700),
+    );
+    add_message(
+        &mut host,
+        &signed_transfer_from(BOB, 2, &alice, &TOKEN, &bob, 300),
+    );
+    run_level(&mut host);
+
+    assert_eq!(0, balance(&host, &alice, &TOKEN));
+    assert_eq!(300, balance(&host, &bob, &TOKEN));
+    assert_eq!(700, balance(&host, &carol, &TOKEN));
+}
+
+#[test]
+fn test_operator_is_per_token() {
+    let mut host = MockHost::default();
+    let (alice, bob) = (address(ALICE), address(BOB));
+
+    add_deposit(&mut host, &alice, &TOKEN, 1000);
+    add_message(
+        &mut host,
+        &signed_update_operator(ALICE, 1, &bob, &[0xab], true),
+    );
+    add_message(
+        &mut host,
+        &signed_transfer_from(BOB, 1, &alice, &TOKEN, &bob, 100),
+    );
+    run_level(&mut host);
+
+    assert_eq!(1000, balance(&host, &alice, &TOKEN));
+    assert_eq!(0, balance(&host, &bob, &TOKEN));
+}
+
+#[test]
+fn test_removed_operator_can_not_transfer() {
+    let mut host = MockHost::default();
+    let (alice, bob) = (address(ALICE), address(BOB));
+
+    add_deposit(&mut host, &alice, &TOKEN, 1000);
+    add_message(
+        &mut host,
+        &signed_update_operator(ALICE, 1, &bob, &TOKEN, true),
+    );
+    add_message(
+        &mut host,
+        &signed_transfer_from(BOB, 1, &alice, &TOKEN, &bob, 100),
+    );
+    add_message(
+        &mut host,
+        &signed_update_operator(ALICE, 2, &bob, &TOKEN, false),
+    );
+    add_message(
+        &mut host,
+        &signed_transfer_from(BOB, 2, &alice, &TOKEN, &bob, 100),
+    );
+    run_level(&mut host);
+
+    assert_eq!(900, balance(&host, &alice, &TOKEN));
+    assert_eq!(100, balance(&host, &bob, &TOKEN));
+}
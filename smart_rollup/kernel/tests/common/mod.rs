@@ -0,0 +1,654 @@
+#![allow(dead_code)]
+
+use kernel::constants::{
+    EXTERNAL_MESSAGE_REP, L1_BRIDGE_CONTRACT_ADDRESS, L1_TEZ_BRIDGE_CONTRACT_ADDRESS,
+};
+use kernel::core::book::Fill;
+use kernel::core::hash::Blake2b;
+use kernel::core::htlc::htlc_id;
+use kernel::core::message::{
+    AdminContent, AuthorizeSessionKeyContent, DepositAction, Message, MultisigContent,
+    MultisigMessage, OpenStreamContent, OperatorUpdate, OrderContent, PoolContent,
+    SponsoredMessage, SwapContent, SwapLeg, VestContent,
+};
+use kernel::core::multisig::Multisig;
+use kernel::core::public_key_hash::PublicKeyHash;
+use kernel::core::stream::stream_id;
+use kernel::core::token::Token;
+use kernel::core::vesting::vesting_id;
+use kernel_client::{
+    admin, approve, authorize_session_key, cancel_stream, claim, claim_vested, deposit_action,
+    lock, multisig, open_stream, order, pool, refund, rotate_key, sponsor, swap, to_external_hex,
+    transfer, transfer_from, update_operators, vest, withdraw, withdraw_stream, Signer,
+};
+use tezos_crypto_rs::hash::ContractKt1Hash;
+use tezos_data_encoding::enc::{BinResult, BinWriter};
+use tezos_smart_rollup::host::Runtime;
+use tezos_smart_rollup::michelson::{
+    ticket::{BytesTicket, Ticket},
+    Michelson, MichelsonBytes, MichelsonContract, MichelsonPair, MichelsonUnit,
+};
+use tezos_smart_rollup::storage::path::OwnedPath;
+use tezos_smart_rollup::types::{Contract, PublicKeyHash as L1PublicKeyHash};
+use tezos_smart_rollup_mock::{MockHost, TransferMetadata};
+
+pub const ALICE: &str = "edsk31vznjHSSpGExDMHYASz45VZqXN4DPxvsa4hAyY8dHM28cZzp6";
+pub const BOB: &str = "edsk3f2bMT6U6bcd2PfuGaoYbf5HptpA7dMB3Cp8zCBERnsVMaA7ok";
+pub const CAROL: &str = "edsk3FSURxDvkUF3Dt9UCGH7Uza2nNcLfAjd7adKrShW4Yoap9FtrZ";
+pub const TOKEN: [u8; 2] = [0x12, 0x34];
+pub const TIMESTAMP: &str = "2023-05-19T05:45:50.473Z";
+
+/// Raw bytes of an external message, written as is in the inbox
+pub struct External(pub Vec<u8>);
+
+impl BinWriter for External {
+    fn bin_write(&self, output: &mut Vec<u8>) -> BinResult {
+        output.extend_from_slice(&self.0);
+        Ok(())
+    }
+}
+
+pub fn signer(secret_key: &str) -> Signer {
+    Signer::from_b58(secret_key).unwrap()
+}
+
+/// Signer signing for the account if given, for itself otherwise
+pub fn signer_for(secret_key: &str, account: Option<&str>) -> Signer {
+    let signer = signer(secret_key);
+    match account {
+        Some(account) => signer.with_account(PublicKeyHash::from_b58(account).unwrap()),
+        None => signer,
+    }
+}
+
+pub fn address(secret_key: &str) -> String {
+    signer(secret_key).public_key_hash().to_string()
+}
+
+/// Adds a deposit of the bridge to the inbox of the next level
+pub fn add_deposit(host: &mut MockHost, account: &str, token: &[u8], amount: u128) {
+    add_deposit_from(host, L1_BRIDGE_CONTRACT_ADDRESS, account, token, amount)
+}
+
+/// Adds a ticket transfer from any L1 contract to the inbox of the next level
+pub fn add_deposit_from(
+    host: &mut MockHost,
+    sender: &str,
+    account: &str,
+    token: &[u8],
+    amount: u128,
+) {
+    let ticketer = Contract::from_b58check(sender).unwrap();
+    let ticket = BytesTicket::new(ticketer, MichelsonBytes(token.to_vec()), amount).unwrap();
+    let payload = MichelsonPair(
+        ticket,
+        MichelsonContract(Contract::from_b58check(account).unwrap()),
+    );
+
+    let metadata = TransferMetadata::new(
+        ContractKt1Hash::from_base58_check(sender).unwrap(),
+        L1PublicKeyHash::from_b58check(account).unwrap(),
+    );
+    host.add_transfer(payload, &metadata);
+}
+
+/// Adds a deposit of the bridge, sent by the L1 account, naming a contract as its destination
+pub fn add_deposit_to_contract(
+    host: &mut MockHost,
+    source: &str,
+    contract: &str,
+    token: &[u8],
+    amount: u128,
+) {
+    add_deposit_to_contract_from(
+        host,
+        L1_BRIDGE_CONTRACT_ADDRESS,
+        source,
+        contract,
+        token,
+        amount,
+    )
+}
+
+/// Adds a deposit of bytes tickets of the ticketer, sent by the L1 account to a contract
+pub fn add_deposit_to_contract_from(
+    host: &mut MockHost,
+    ticketer: &str,
+    source: &str,
+    contract: &str,
+    token: &[u8],
+    amount: u128,
+) {
+    let ticket_ticketer = Contract::from_b58check(ticketer).unwrap();
+    let ticket = BytesTicket::new(ticket_ticketer, MichelsonBytes(token.to_vec()), amount).unwrap();
+    let payload = MichelsonPair(
+        ticket,
+        MichelsonContract(Contract::from_b58check(contract).unwrap()),
+    );
+
+    let metadata = TransferMetadata::new(
+        ContractKt1Hash::from_base58_check(ticketer).unwrap(),
+        L1PublicKeyHash::from_b58check(source).unwrap(),
+    );
+    host.add_transfer(payload, &metadata);
+}
+
+/// Adds a deposit of tez, wrapped in unit tickets by the tez bridge
+pub fn add_tez_deposit(host: &mut MockHost, account: &str, amount: u128) {
+    add_tez_deposit_from(host, L1_TEZ_BRIDGE_CONTRACT_ADDRESS, account, amount)
+}
+
+pub fn add_tez_deposit_from(host: &mut MockHost, sender: &str, account: &str, amount: u128) {
+    add_ticket_deposit(host, sender, account, MichelsonUnit, amount)
+}
+
+/// Adds a deposit of a ticket minted and sent by the ticketer
+pub fn add_ticket_deposit<Expr: Michelson>(
+    host: &mut MockHost,
+    ticketer: &str,
+    account: &str,
+    content: Expr,
+    amount: u128,
+) {
+    let contract = Contract::from_b58check(ticketer).unwrap();
+    let ticket = Ticket::new(contract, content, amount).unwrap();
+    let payload = MichelsonPair(
+        ticket,
+        MichelsonContract(Contract::from_b58check(account).unwrap()),
+    );
+
+    let metadata = TransferMetadata::new(
+        ContractKt1Hash::from_base58_check(ticketer).unwrap(),
+        L1PublicKeyHash::from_b58check(account).unwrap(),
+    );
+    host.add_transfer(payload, &metadata);
+}
+
+/// Adds a deposit of the bridge naming the L2 account and the action run with the tokens
+pub fn add_deposit_with_action(
+    host: &mut MockHost,
+    account: &str,
+    token: &[u8],
+    amount: u128,
+    action: &DepositAction,
+) {
+    add_deposit_with_action_bytes(
+        host,
+        account,
+        token,
+        amount,
+        deposit_action(action).unwrap(),
+    );
+}
+
+pub fn add_deposit_with_action_bytes(
+    host: &mut MockHost,
+    account: &str,
+    token: &[u8],
+    amount: u128,
+    action: Vec<u8>,
+) {
+    let ticketer = Contract::from_b58check(L1_BRIDGE_CONTRACT_ADDRESS).unwrap();
+    let ticket = BytesTicket::new(ticketer, MichelsonBytes(token.to_vec()), amount).unwrap();
+    let destination = MichelsonContract(Contract::from_b58check(account).unwrap());
+    let payload = MichelsonPair(ticket, MichelsonPair(destination, MichelsonBytes(action)));
+
+    let metadata = TransferMetadata::new(
+        ContractKt1Hash::from_base58_check(L1_BRIDGE_CONTRACT_ADDRESS).unwrap(),
+        L1PublicKeyHash::from_b58check(account).unwrap(),
+    );
+    host.add_transfer(payload, &metadata);
+}
+
+/// Adds an external message made of the json, as anybody could send it
+pub fn add_external_json(host: &mut MockHost, json: &str) {
+    let bytes = [&[EXTERNAL_MESSAGE_REP], json.as_bytes()].concat();
+    host.add_external(External(bytes));
+}
+
+/// Adds a message of the kernel to the inbox of the next level
+pub fn add_message(host: &mut MockHost, message: &Message) {
+    let hex = to_external_hex(message).unwrap();
+    let bytes = (0..hex.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+        .collect();
+    host.add_external(External(bytes));
+}
+
+pub fn signed_transfer(
+    secret_key: &str,
+    nonce: u64,
+    token: &[u8],
+    destination: &str,
+    amount: u128,
+) -> Message {
+    signed_transfer_on_lane(secret_key, 0, nonce, token, destination, amount)
+}
+
+pub fn signed_transfer_on_lane(
+    secret_key: &str,
+    lane: u32,
+    nonce: u64,
+    token: &[u8],
+    destination: &str,
+    amount: u128,
+) -> Message {
+    let destination = PublicKeyHash::from_b58(destination).unwrap();
+    let mut inner = transfer(nonce, Token::Bytes(token.to_vec()), destination, amount);
+    inner.lane = lane;
+    Message::Transfer(signer(secret_key).sign(inner, TIMESTAMP).unwrap())
+}
+
+pub fn signed_withdraw(
+    secret_key: &str,
+    nonce: u64,
+    token: &[u8],
+    destination: &str,
+    amount: u128,
+) -> Message {
+    let destination = PublicKeyHash::from_b58(destination).unwrap();
+    let inner = withdraw(nonce, Token::Bytes(token.to_vec()), destination, amount);
+    Message::Withdraw(signer(secret_key).sign(inner, TIMESTAMP).unwrap())
+}
+
+pub fn signed_approve(
+    secret_key: &str,
+    nonce: u64,
+    spender: &str,
+    token: &[u8],
+    amount: u128,
+) -> Message {
+    let spender = PublicKeyHash::from_b58(spender).unwrap();
+    let inner = approve(nonce, spender, Token::Bytes(token.to_vec()), amount);
+    Message::Approve(signer(secret_key).sign(inner, TIMESTAMP).unwrap())
+}
+
+pub fn signed_transfer_from(
+    secret_key: &str,
+    nonce: u64,
+    owner: &str,
+    token: &[u8],
+    destination: &str,
+    amount: u128,
+) -> Message {
+    let owner = PublicKeyHash::from_b58(owner).unwrap();
+    let destination = PublicKeyHash::from_b58(destination).unwrap();
+    let inner = transfer_from(
+        nonce,
+        owner,
+        Token::Bytes(token.to_vec()),
+        destination,
+        amount,
+    );
+    Message::TransferFrom(signer(secret_key).sign(inner, TIMESTAMP).unwrap())
+}
+
+/// Adds, or removes, an operator of the signer
+pub fn signed_update_operator(
+    secret_key: &str,
+    nonce: u64,
+    operator: &str,
+    token: &[u8],
+    add: bool,
+) -> Message {
+    let operator = PublicKeyHash::from_b58(operator).unwrap();
+    let token = Token::Bytes(token.to_vec());
+    let update = if add {
+        OperatorUpdate::Add { operator, token }
+    } else {
+        OperatorUpdate::Remove { operator, token }
+    };
+    let inner = update_operators(nonce, vec![update]);
+    Message::UpdateOperators(signer(secret_key).sign(inner, TIMESTAMP).unwrap())
+}
+
+/// Returns the lock of tokens, unlocked by the preimage, and its identifier
+pub fn signed_lock(
+    secret_key: &str,
+    nonce: u64,
+    token: &[u8],
+    amount: u128,
+    recipient: &str,
+    preimage: &[u8],
+    timeout_level: u32,
+) -> (Message, String) {
+    let signer = signer(secret_key);
+    let recipient = PublicKeyHash::from_b58(recipient).unwrap();
+    let hashlock = Blake2b::from(preimage).as_ref().to_vec();
+    let inner = lock(
+        nonce,
+        Token::Bytes(token.to_vec()),
+        amount,
+        recipient,
+        hashlock,
+        timeout_level,
+    );
+    let id = htlc_id(&signer.public_key_hash(), &inner);
+    (Message::Htlc(signer.sign(inner, TIMESTAMP).unwrap()), id)
+}
+
+pub fn signed_claim(secret_key: &str, nonce: u64, id: &str, preimage: &[u8]) -> Message {
+    let inner = claim(nonce, id.to_string(), preimage.to_vec());
+    Message::Htlc(signer(secret_key).sign(inner, TIMESTAMP).unwrap())
+}
+
+pub fn signed_refund(secret_key: &str, nonce: u64, id: &str) -> Message {
+    let inner = refund(nonce, id.to_string());
+    Message::Htlc(signer(secret_key).sign(inner, TIMESTAMP).unwrap())
+}
+
+/// Terms of a swap of first_amount of the token from the first account for second_amount of
+/// the other token from the second account, until the expiry level
+pub fn swap_content(
+    first: &str,
+    first_token: &[u8],
+    first_amount: u128,
+    second: &str,
+    second_token: &[u8],
+    second_amount: u128,
+    expires_level: u32,
+) -> SwapContent {
+    SwapContent {
+        first: PublicKeyHash::from_b58(first).unwrap(),
+        first_token: Token::Bytes(first_token.to_vec()),
+        first_amount,
+        second: PublicKeyHash::from_b58(second).unwrap(),
+        second_token: Token::Bytes(second_token.to_vec()),
+        second_amount,
+        expires_level,
+    }
+}
+
+pub fn signed_swap_leg(secret_key: &str, nonce: u64, content: SwapContent) -> SwapLeg {
+    signer(secret_key)
+        .sign(swap(nonce, content), TIMESTAMP)
+        .unwrap()
+}
+
+pub fn signed_pool(secret_key: &str, nonce: u64, content: PoolContent) -> Message {
+    Message::Pool(
+        signer(secret_key)
+            .sign(pool(nonce, content), TIMESTAMP)
+            .unwrap(),
+    )
+}
+
+pub fn signed_order(secret_key: &str, nonce: u64, content: OrderContent) -> Message {
+    Message::Order(
+        signer(secret_key)
+            .sign(order(nonce, content), TIMESTAMP)
+            .unwrap(),
+    )
+}
+
+/// Signs a schedule, returns the message and the identifier of the schedule
+pub fn signed_vest(secret_key: &str, nonce: u64, content: VestContent) -> (Message, String) {
+    let signer = signer(secret_key);
+    let inner = vest(nonce, content);
+    let id = vesting_id(&signer.public_key_hash(), &inner);
+    let message = Message::Vesting(signer.sign(inner, TIMESTAMP).unwrap());
+    (message, id)
+}
+
+pub fn signed_claim_vested(secret_key: &str, nonce: u64, id: &str) -> Message {
+    let inner = claim_vested(nonce, id.to_string());
+    Message::Vesting(signer(secret_key).sign(inner, TIMESTAMP).unwrap())
+}
+
+/// Signs a stream, returns the message and the identifier of the stream
+pub fn signed_open_stream(
+    secret_key: &str,
+    nonce: u64,
+    content: OpenStreamContent,
+) -> (Message, String) {
+    let signer = signer(secret_key);
+    let inner = open_stream(nonce, content);
+    let id = stream_id(&signer.public_key_hash(), &inner);
+    let message = Message::Stream(signer.sign(inner, TIMESTAMP).unwrap());
+    (message, id)
+}
+
+pub fn signed_withdraw_stream(secret_key: &str, nonce: u64, id: &str) -> Message {
+    let inner = withdraw_stream(nonce, id.to_string());
+    Message::Stream(signer(secret_key).sign(inner, TIMESTAMP).unwrap())
+}
+
+pub fn signed_cancel_stream(secret_key: &str, nonce: u64, id: &str) -> Message {
+    let inner = cancel_stream(nonce, id.to_string());
+    Message::Stream(signer(secret_key).sign(inner, TIMESTAMP).unwrap())
+}
+
+/// Wraps the message with the signature of the relayer, which pays no fee
+pub fn sponsored(relayer: &str, nonce: u64, message: Message) -> Message {
+    sponsored_with_fee(relayer, nonce, message, &TOKEN, 0)
+}
+
+/// Wraps the message with the signature of the relayer, which pays the fee in the token
+pub fn sponsored_with_fee(
+    relayer: &str,
+    nonce: u64,
+    message: Message,
+    fee_token: &[u8],
+    fee_amount: u128,
+) -> Message {
+    let fee_token = Token::Bytes(fee_token.to_vec());
+    let inner = sponsor(nonce, &message, fee_token, fee_amount).unwrap();
+    let sponsor = signer(relayer).sign(inner, TIMESTAMP).unwrap();
+    Message::Sponsored(Box::new(SponsoredMessage { message, sponsor }))
+}
+
+pub fn signed_authorize_session_key(
+    secret_key: &str,
+    nonce: u64,
+    key: &str,
+    token: &[u8],
+    max_amount: u128,
+    expires_level: u32,
+) -> Message {
+    let content = AuthorizeSessionKeyContent {
+        key: PublicKeyHash::from_b58(key).unwrap(),
+        token: Token::Bytes(token.to_vec()),
+        max_amount,
+        expires_level,
+    };
+    let inner = authorize_session_key(nonce, content);
+    Message::AuthorizeSessionKey(signer(secret_key).sign(inner, TIMESTAMP).unwrap())
+}
+
+/// Signs a transfer of the tokens of the source with one of its session keys
+pub fn signed_session_transfer(
+    secret_key: &str,
+    source: &str,
+    nonce: u64,
+    token: &[u8],
+    destination: &str,
+    amount: u128,
+) -> Message {
+    let destination = PublicKeyHash::from_b58(destination).unwrap();
+    let mut inner = transfer(nonce, Token::Bytes(token.to_vec()), destination, amount);
+    inner.content.source = Some(PublicKeyHash::from_b58(source).unwrap());
+    Message::Transfer(signer(secret_key).sign(inner, TIMESTAMP).unwrap())
+}
+
+/// Signs the rotation of the key of the account to the key of new_secret_key
+pub fn signed_rotate_key(
+    secret_key: &str,
+    account: Option<&str>,
+    nonce: u64,
+    new_secret_key: &str,
+) -> Message {
+    let inner = rotate_key(nonce, signer(new_secret_key).public_key().clone());
+    Message::RotateKey(
+        signer_for(secret_key, account)
+            .sign(inner, TIMESTAMP)
+            .unwrap(),
+    )
+}
+
+/// Signs a transfer for the account, whose key has been rotated to the key of the signer
+pub fn signed_transfer_for(
+    secret_key: &str,
+    account: &str,
+    nonce: u64,
+    token: &[u8],
+    destination: &str,
+    amount: u128,
+) -> Message {
+    let destination = PublicKeyHash::from_b58(destination).unwrap();
+    let inner = transfer(nonce, Token::Bytes(token.to_vec()), destination, amount);
+    let signer = signer_for(secret_key, Some(account));
+    Message::Transfer(signer.sign(inner, TIMESTAMP).unwrap())
+}
+
+/// Account controlled by threshold of the keys
+pub fn multisig_account(keys: &[&str], threshold: u8) -> Multisig {
+    let keys = keys
+        .iter()
+        .map(|key| signer(key).public_key().clone())
+        .collect();
+    Multisig::new(keys, threshold).unwrap()
+}
+
+pub fn multisig_address(keys: &[&str], threshold: u8) -> String {
+    multisig_account(keys, threshold)
+        .address()
+        .unwrap()
+        .to_string()
+}
+
+/// Builds a message of the multisig account, signed by each of the signers
+pub fn signed_multisig(
+    keys: &[&str],
+    threshold: u8,
+    signers: &[&str],
+    nonce: u64,
+    content: MultisigContent,
+) -> Message {
+    let account = multisig_account(keys, threshold);
+    let inner = multisig(account.address().unwrap(), nonce, content);
+    let signatures = signers
+        .iter()
+        .map(|key| signer(key).cosign(&inner, TIMESTAMP).unwrap())
+        .collect();
+    Message::Multisig(MultisigMessage {
+        keys: account.keys().to_vec(),
+        threshold: account.threshold(),
+        signatures,
+        inner,
+    })
+}
+
+pub fn multisig_transfer(token: &[u8], destination: &str, amount: u128) -> MultisigContent {
+    let destination = PublicKeyHash::from_b58(destination).unwrap();
+    MultisigContent::Transfer(
+        transfer(0, Token::Bytes(token.to_vec()), destination, amount).content,
+    )
+}
+
+pub fn multisig_withdraw(token: &[u8], destination: &str, amount: u128) -> MultisigContent {
+    let destination = PublicKeyHash::from_b58(destination).unwrap();
+    MultisigContent::Withdraw(
+        withdraw(0, Token::Bytes(token.to_vec()), destination, amount).content,
+    )
+}
+
+/// Makes the account of the secret key the admin, as done when the kernel is installed
+pub fn set_admin(host: &mut MockHost, secret_key: &str) {
+    let path = OwnedPath::try_from(b"/admin".to_vec()).unwrap();
+    host.store_write(&path, address(secret_key).as_bytes(), 0)
+        .unwrap();
+}
+
+pub fn signed_admin(secret_key: &str, nonce: u64, content: AdminContent) -> Message {
+    let inner = admin(nonce, content);
+    Message::Admin(signer(secret_key).sign(inner, TIMESTAMP).unwrap())
+}
+
+/// Replaces the public key of a signed message, which makes its signature invalid
+pub fn with_public_key(mut message: Message, secret_key: &str) -> Message {
+    let pkey = signer(secret_key).public_key().clone();
+    match &mut message {
+        Message::Transfer(transfer) => transfer.pkey = pkey,
+        Message::Withdraw(withdraw) => withdraw.pkey = pkey,
+        _ => (),
+    }
+    message
+}
+
+/// Runs the kernel on the inbox of the next level
+pub fn run_level(host: &mut MockHost) -> u32 {
+    host.run_level(kernel::entry)
+}
+
+/// Runs empty levels until the next level to be run is the given one
+pub fn run_until(host: &mut MockHost, level: u32) {
+    while run_level(host) + 1 < level {}
+}
+
+fn read_path(host: &MockHost, path: &str) -> Option<Vec<u8>> {
+    let path = OwnedPath::try_from(path.as_bytes().to_vec()).unwrap();
+    host.store_has(&path).unwrap()?;
+    Some(host.store_read(&path, 0, 16).unwrap())
+}
+
+/// Whether a value is stored at the path
+pub fn is_stored(host: &MockHost, path: &str) -> bool {
+    read_path(host, path).is_some()
+}
+
+/// Reads /ledger/${tz1-account}/${token-bytes}
+pub fn balance(host: &MockHost, account: &str, token: &[u8]) -> u128 {
+    let token: String = token.iter().map(|byte| format!("{:02x}", byte)).collect();
+    read_path(host, &format!("/ledger/{}/{}", account, token))
+        .map(|bytes| u128::from_be_bytes(bytes.try_into().unwrap()))
+        .unwrap_or_default()
+}
+
+/// Reads /tez/${tz1-account}
+pub fn tez_balance(host: &MockHost, account: &str) -> u128 {
+    read_path(host, &format!("/tez/{}", account))
+        .map(|bytes| u128::from_be_bytes(bytes.try_into().unwrap()))
+        .unwrap_or_default()
+}
+
+/// Reads the balance of any token, /ledger/${tz1-account}/ticket.${ticket-hash} for tickets
+pub fn token_balance(host: &MockHost, account: &str, token: &Token) -> u128 {
+    match token {
+        Token::Tez => tez_balance(host, account),
+        _ => read_path(host, &format!("/ledger/{}/{}", account, token.key()))
+            .map(|bytes| u128::from_be_bytes(bytes.try_into().unwrap()))
+            .unwrap_or_default(),
+    }
+}
+
+/// Reads /nonce/${tz1-account}
+pub fn nonce(host: &MockHost, account: &str) -> u64 {
+    read_path(host, &format!("/nonce/{}", account))
+        .map(|bytes| u64::from_be_bytes(bytes.try_into().unwrap()))
+        .unwrap_or_default()
+}
+
+/// Reads /allowance/${tz1-owner}/${tz1-spender}/${token-bytes}
+pub fn allowance(host: &MockHost, owner: &str, spender: &str, token: &[u8]) -> u128 {
+    let token: String = token.iter().map(|byte| format!("{:02x}", byte)).collect();
+    read_path(host, &format!("/allowance/{}/{}/{}", owner, spender, token))
+        .map(|bytes| u128::from_be_bytes(bytes.try_into().unwrap()))
+        .unwrap_or_default()
+}
+
+/// Reads /lane/${tz1-account}/${lane}
+pub fn lane_nonce(host: &MockHost, account: &str, lane: u32) -> u64 {
+    read_path(host, &format!("/lane/{}/{}", account, lane))
+        .map(|bytes| u64::from_be_bytes(bytes.try_into().unwrap()))
+        .unwrap_or_default()
+}
+
+/// Reads /receipts/fills/${index}
+pub fn fill(host: &MockHost, index: u64) -> Option<Fill> {
+    let path = OwnedPath::try_from(format!("/receipts/fills/{}", index).into_bytes()).unwrap();
+    host.store_has(&path).unwrap()?;
+    let size = host.store_value_size(&path).unwrap();
+    let bytes = host.store_read(&path, 0, size).unwrap();
+    Some(serde_json_wasm::from_slice(&bytes).unwrap())
+}
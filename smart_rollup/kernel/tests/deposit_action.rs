@@ -0,0 +1,116 @@
+mod common;
+
+use common::*;
+use kernel::core::hash::Blake2b;
+use kernel::core::htlc::{deposit_lock_id, Htlc};
+use kernel::core::message::{DepositAction, PoolContent};
+use kernel::core::public_key_hash::PublicKeyHash;
+use kernel::core::token::Token;
+use tezos_smart_rollup_mock::MockHost;
+
+const OTHER_TOKEN: [u8; 2] = [0xab, 0xcd];
+const PREIMAGE: &[u8] = b"secret";
+
+fn pkh(account: &str) -> PublicKeyHash {
+    PublicKeyHash::from_b58(account).unwrap()
+}
+
+#[test]
+fn test_deposit_forwarded_to_another_account() {
+    let mut host = MockHost::default();
+    let (alice, bob) = (address(ALICE), address(BOB));
+
+    let action = DepositAction::Transfer {
+        destination: pkh(&bob),
+    };
+    add_deposit_with_action(&mut host, &alice, &TOKEN, 1000, &action);
+    // Without an action, the deposit is only credited
+    add_deposit_with_action_bytes(&mut host, &alice, &TOKEN, 200, vec![]);
+    run_level(&mut host);
+
+    assert_eq!(200, balance(&host, &alice, &TOKEN));
+    assert_eq!(1000, balance(&host, &bob, &TOKEN));
+}
+
+#[test]
+fn test_deposit_swapped_in_a_pool() {
+    let mut host = MockHost::default();
+    let (alice, bob) = (address(ALICE), address(BOB));
+
+    add_deposit(&mut host, &alice, &TOKEN, 1000);
+    add_deposit(&mut host, &alice, &OTHER_TOKEN, 4000);
+    let content = PoolContent::CreatePool {
+        token_a: Token::Bytes(TOKEN.to_vec()),
+        amount_a: 1000,
+        token_b: Token::Bytes(OTHER_TOKEN.to_vec()),
+        amount_b: 4000,
+        fee: 30,
+    };
+    add_message(&mut host, &signed_pool(ALICE, 1, content));
+    run_level(&mut host);
+
+    let action = DepositAction::Swap {
+        token_out: Token::Bytes(OTHER_TOKEN.to_vec()),
+        min_out: 300,
+    };
+    add_deposit_with_action(&mut host, &bob, &TOKEN, 100, &action);
+    run_level(&mut host);
+
+    assert_eq!(0, balance(&host, &bob, &TOKEN));
+    assert!(balance(&host, &bob, &OTHER_TOKEN) >= 300);
+
+    // The deposit stays on the account when the swap fails
+    let action = DepositAction::Swap {
+        token_out: Token::Bytes(OTHER_TOKEN.to_vec()),
+        min_out: 1000,
+    };
+    add_deposit_with_action(&mut host, &bob, &TOKEN, 100, &action);
+    run_level(&mut host);
+
+    assert_eq!(100, balance(&host, &bob, &TOKEN));
+}
+
+#[test]
+fn test_deposit_funds_a_lock() {
+    let mut host = MockHost::default();
+    let (alice, bob) = (address(ALICE), address(BOB));
+    let level = run_level(&mut host);
+
+    let hashlock = Blake2b::from(PREIMAGE).as_ref().to_vec();
+    let action = DepositAction::Lock {
+        recipient: pkh(&bob),
+        hashlock: hashlock.clone(),
+        timeout_level: level + 10,
+    };
+    add_deposit_with_action(&mut host, &alice, &TOKEN, 400, &action);
+    // The same lock can't be funded twice, the second deposit is only credited
+    add_deposit_with_action(&mut host, &alice, &TOKEN, 400, &action);
+    run_level(&mut host);
+
+    assert_eq!(400, balance(&host, &alice, &TOKEN));
+
+    let id = deposit_lock_id(&Htlc {
+        sender: pkh(&alice),
+        recipient: pkh(&bob),
+        token: Token::Bytes(TOKEN.to_vec()),
+        amount: 400,
+        hashlock,
+        timeout_level: level + 10,
+    });
+    add_message(&mut host, &signed_claim(CAROL, 1, &id, PREIMAGE));
+    run_level(&mut host);
+
+    assert_eq!(400, balance(&host, &bob, &TOKEN));
+    assert_eq!(400, balance(&host, &alice, &TOKEN));
+}
+
+#[test]
+fn test_invalid_action_does_not_prevent_the_deposit() {
+    let mut host = MockHost::default();
+    let alice = address(ALICE);
+
+    add_deposit_with_action_bytes(&mut host, &alice, &TOKEN, 300, b"{\"Unknown\":{}}".to_vec());
+    run_level(&mut host);
+
+    assert_eq!(300, balance(&host, &alice, &TOKEN));
+}
@@ -0,0 +1,142 @@
+mod common;
+
+use common::*;
+use kernel::core::message::{AdminContent, DepositAction, FreezeContent};
+use kernel::core::public_key_hash::PublicKeyHash;
+use kernel::core::token::Token;
+use tezos_smart_rollup_mock::MockHost;
+
+const OTHER_TOKEN: [u8; 2] = [0x56, 0x78];
+
+fn freeze(secret_key: &str, token: Option<&[u8]>) -> FreezeContent {
+    FreezeContent {
+        account: PublicKeyHash::from_b58(&address(secret_key)).unwrap(),
+        token: token.map(|token| Token::Bytes(token.to_vec())),
+    }
+}
+
+#[test]
+fn test_frozen_account_cannot_send_or_receive() {
+    let mut host = MockHost::default();
+    let alice = address(ALICE);
+    let bob = address(BOB);
+    set_admin(&mut host, CAROL);
+
+    add_deposit(&mut host, &alice, &TOKEN, 1000);
+    add_deposit(&mut host, &bob, &TOKEN, 1000);
+    add_message(
+        &mut host,
+        &signed_admin(CAROL, 1, AdminContent::Freeze(freeze(ALICE, None))),
+    );
+    run_level(&mut host);
+
+    add_message(&mut host, &signed_transfer(ALICE, 1, &TOKEN, &bob, 400));
+    add_message(&mut host, &signed_transfer(BOB, 1, &TOKEN, &alice, 400));
+    run_level(&mut host);
+    assert_eq!(1000, balance(&host, &alice, &TOKEN));
+    assert_eq!(1000, balance(&host, &bob, &TOKEN));
+
+    add_message(
+        &mut host,
+        &signed_admin(CAROL, 2, AdminContent::Unfreeze(freeze(ALICE, None))),
+    );
+    add_message(&mut host, &signed_transfer(ALICE, 2, &TOKEN, &bob, 400));
+    run_level(&mut host);
+    assert_eq!(600, balance(&host, &alice, &TOKEN));
+    assert_eq!(1400, balance(&host, &bob, &TOKEN));
+}
+
+#[test]
+fn test_frozen_account_cannot_withdraw() {
+    let mut host = MockHost::default();
+    let alice = address(ALICE);
+    set_admin(&mut host, CAROL);
+
+    add_deposit(&mut host, &alice, &TOKEN, 1000);
+    add_message(
+        &mut host,
+        &signed_admin(CAROL, 1, AdminContent::Freeze(freeze(ALICE, None))),
+    );
+    run_level(&mut host);
+
+    add_message(&mut host, &signed_withdraw(ALICE, 1, &TOKEN, &alice, 300));
+    let level = run_level(&mut host);
+    assert_eq!(1000, balance(&host, &alice, &TOKEN));
+    assert!(host.outbox_at(level).is_empty());
+}
+
+#[test]
+fn test_account_frozen_for_a_token() {
+    let mut host = MockHost::default();
+    let alice = address(ALICE);
+    let bob = address(BOB);
+    set_admin(&mut host, CAROL);
+
+    add_deposit(&mut host, &alice, &TOKEN, 1000);
+    add_deposit(&mut host, &alice, &OTHER_TOKEN, 1000);
+    add_message(
+        &mut host,
+        &signed_admin(CAROL, 1, AdminContent::Freeze(freeze(ALICE, Some(&TOKEN)))),
+    );
+    run_level(&mut host);
+
+    add_message(&mut host, &signed_transfer(ALICE, 1, &TOKEN, &bob, 400));
+    add_message(
+        &mut host,
+        &signed_transfer(ALICE, 2, &OTHER_TOKEN, &bob, 400),
+    );
+    run_level(&mut host);
+    assert_eq!(1000, balance(&host, &alice, &TOKEN));
+    assert_eq!(600, balance(&host, &alice, &OTHER_TOKEN));
+    assert_eq!(400, balance(&host, &bob, &OTHER_TOKEN));
+}
+
+#[test]
+fn test_deposit_to_frozen_account_is_refunded() {
+    let mut host = MockHost::default();
+    let alice = address(ALICE);
+    let bob = address(BOB);
+    set_admin(&mut host, CAROL);
+
+    add_message(
+        &mut host,
+        &signed_admin(CAROL, 1, AdminContent::Freeze(freeze(ALICE, None))),
+    );
+    run_level(&mut host);
+
+    // Neither the deposit nor its action is applied, the ticket goes back to its sender
+    let action = DepositAction::Transfer {
+        destination: PublicKeyHash::from_b58(&bob).unwrap(),
+    };
+    add_deposit(&mut host, &alice, &TOKEN, 500);
+    add_deposit_with_action(&mut host, &alice, &TOKEN, 1000, &action);
+    let level = run_level(&mut host);
+
+    assert_eq!(0, balance(&host, &alice, &TOKEN));
+    assert_eq!(0, balance(&host, &bob, &TOKEN));
+    assert_eq!(2, host.outbox_at(level).len());
+}
+
+#[test]
+fn test_deposit_action_to_frozen_account_is_not_applied() {
+    let mut host = MockHost::default();
+    let alice = address(ALICE);
+    let bob = address(BOB);
+    set_admin(&mut host, CAROL);
+
+    add_message(
+        &mut host,
+        &signed_admin(CAROL, 1, AdminContent::Freeze(freeze(BOB, None))),
+    );
+    run_level(&mut host);
+
+    // The deposit is kept by alice, the frozen destination receives nothing
+    let action = DepositAction::Transfer {
+        destination: PublicKeyHash::from_b58(&bob).unwrap(),
+    };
+    add_deposit_with_action(&mut host, &alice, &TOKEN, 1000, &action);
+    run_level(&mut host);
+
+    assert_eq!(1000, balance(&host, &alice, &TOKEN));
+    assert_eq!(0, balance(&host, &bob, &TOKEN));
+}
@@ -0,0 +1,109 @@
+mod common;
+
+use common::*;
+use tezos_smart_rollup_mock::MockHost;
+
+const PREIMAGE: &[u8] = b"secret";
+
+#[test]
+fn test_claim_releases_the_tokens_to_the_recipient() {
+    let mut host = MockHost::default();
+    let (alice, bob) = (address(ALICE), address(BOB));
+
+    add_deposit(&mut host, &alice, &TOKEN, 1000);
+    let level = run_level(&mut host);
+
+    let (lock, id) = signed_lock(ALICE, 1, &TOKEN, 400, &bob, PREIMAGE, level + 10);
+    add_message(&mut host, &lock);
+    run_level(&mut host);
+
+    assert_eq!(600, balance(&host, &alice, &TOKEN));
+    assert_eq!(0, balance(&host, &bob, &TOKEN));
+
+    // Anybody knowing the preimage can claim, the tokens go to the recipient
+    add_message(&mut host, &signed_claim(CAROL, 1, &id, PREIMAGE));
+    run_level(&mut host);
+
+    assert_eq!(400, balance(&host, &bob, &TOKEN));
+    assert_eq!(0, balance(&host, &address(CAROL), &TOKEN));
+
+    // A lock can only be released once
+    add_message(&mut host, &signed_claim(CAROL, 2, &id, PREIMAGE));
+    run_level(&mut host);
+
+    assert_eq!(400, balance(&host, &bob, &TOKEN));
+}
+
+#[test]
+fn test_claim_with_invalid_preimage_is_rejected() {
+    let mut host = MockHost::default();
+    let (alice, bob) = (address(ALICE), address(BOB));
+
+    add_deposit(&mut host, &alice, &TOKEN, 1000);
+    let level = run_level(&mut host);
+
+    let (lock, id) = signed_lock(ALICE, 1, &TOKEN, 400, &bob, PREIMAGE, level + 10);
+    add_message(&mut host, &lock);
+    add_message(&mut host, &signed_claim(BOB, 1, &id, b"guess"));
+    run_level(&mut host);
+
+    assert_eq!(600, balance(&host, &alice, &TOKEN));
+    assert_eq!(0, balance(&host, &bob, &TOKEN));
+}
+
+#[test]
+fn test_refund_after_the_timeout() {
+    let mut host = MockHost::default();
+    let (alice, bob) = (address(ALICE), address(BOB));
+
+    add_deposit(&mut host, &alice, &TOKEN, 1000);
+    let level = run_level(&mut host);
+
+    let (lock, id) = signed_lock(ALICE, 1, &TOKEN, 400, &bob, PREIMAGE, level + 2);
+    add_message(&mut host, &lock);
+    // Too early to be refunded
+    add_message(&mut host, &signed_refund(ALICE, 2, &id));
+    run_level(&mut host);
+
+    assert_eq!(600, balance(&host, &alice, &TOKEN));
+
+    // The timeout level is reached, the lock can't be claimed anymore
+    add_message(&mut host, &signed_claim(BOB, 1, &id, PREIMAGE));
+    add_message(&mut host, &signed_refund(ALICE, 3, &id));
+    run_level(&mut host);
+
+    assert_eq!(1000, balance(&host, &alice, &TOKEN));
+    assert_eq!(0, balance(&host, &bob, &TOKEN));
+}
+
+#[test]
+fn test_lock_with_reached_timeout_is_rejected() {
+    let mut host = MockHost::default();
+    let (alice, bob) = (address(ALICE), address(BOB));
+
+    add_deposit(&mut host, &alice, &TOKEN, 1000);
+    let level = run_level(&mut host);
+
+    let (lock, _) = signed_lock(ALICE, 1, &TOKEN, 400, &bob, PREIMAGE, level + 1);
+    add_message(&mut host, &lock);
+    run_level(&mut host);
+
+    assert_eq!(1000, balance(&host, &alice, &TOKEN));
+}
+
+#[test]
+fn test_lock_above_balance_is_rejected() {
+    let mut host = MockHost::default();
+    let (alice, bob) = (address(ALICE), address(BOB));
+
+    add_deposit(&mut host, &alice, &TOKEN, 1000);
+    let level = run_level(&mut host);
+
+    let (lock, id) = signed_lock(ALICE, 1, &TOKEN, 1001, &bob, PREIMAGE, level + 10);
+    add_message(&mut host, &lock);
+    add_message(&mut host, &signed_claim(BOB, 1, &id, PREIMAGE));
+    run_level(&mut host);
+
+    assert_eq!(1000, balance(&host, &alice, &TOKEN));
+    assert_eq!(0, balance(&host, &bob, &TOKEN));
+}
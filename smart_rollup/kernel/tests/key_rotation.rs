@@ -0,0 +1,69 @@
+mod common;
+
+use common::*;
+use tezos_smart_rollup_mock::MockHost;
+
+#[test]
+fn test_rotated_key_signs_for_the_account() {
+    let mut host = MockHost::default();
+    let (alice, bob, carol) = (address(ALICE), address(BOB), address(CAROL));
+    add_deposit(&mut host, &alice, &TOKEN, 1000);
+    add_deposit(&mut host, &carol, &TOKEN, 1000);
+    run_level(&mut host);
+
+    add_message(&mut host, &signed_rotate_key(ALICE, None, 1, CAROL));
+    add_message(
+        &mut host,
+        &signed_transfer_for(CAROL, &alice, 2, &TOKEN, &bob, 100),
+    );
+    run_level(&mut host);
+
+    // The balances stay on the account of alice
+    assert_eq!(900, balance(&host, &alice, &TOKEN));
+    assert_eq!(100, balance(&host, &bob, &TOKEN));
+    assert_eq!(2, nonce(&host, &alice));
+
+    // The former key can't sign anymore, the account of carol is untouched
+    add_message(&mut host, &signed_transfer(ALICE, 3, &TOKEN, &bob, 100));
+    add_message(&mut host, &signed_transfer(CAROL, 1, &TOKEN, &bob, 50));
+    run_level(&mut host);
+
+    assert_eq!(900, balance(&host, &alice, &TOKEN));
+    assert_eq!(950, balance(&host, &carol, &TOKEN));
+    assert_eq!(150, balance(&host, &bob, &TOKEN));
+    assert_eq!(2, nonce(&host, &alice));
+}
+
+#[test]
+fn test_only_the_current_key_can_rotate() {
+    let mut host = MockHost::default();
+    let (alice, bob) = (address(ALICE), address(BOB));
+    add_deposit(&mut host, &alice, &TOKEN, 1000);
+    run_level(&mut host);
+
+    // Bob can't take over the account of alice
+    add_message(&mut host, &signed_rotate_key(BOB, Some(&alice), 1, BOB));
+    add_message(
+        &mut host,
+        &signed_transfer_for(BOB, &alice, 1, &TOKEN, &bob, 100),
+    );
+    run_level(&mut host);
+    assert_eq!(1000, balance(&host, &alice, &TOKEN));
+    assert_eq!(0, nonce(&host, &alice));
+
+    // Rotated twice, only the last key signs
+    add_message(&mut host, &signed_rotate_key(ALICE, None, 1, CAROL));
+    add_message(&mut host, &signed_rotate_key(CAROL, Some(&alice), 2, BOB));
+    add_message(
+        &mut host,
+        &signed_transfer_for(CAROL, &alice, 3, &TOKEN, &bob, 100),
+    );
+    add_message(
+        &mut host,
+        &signed_transfer_for(BOB, &alice, 3, &TOKEN, &bob, 200),
+    );
+    run_level(&mut host);
+
+    assert_eq!(800, balance(&host, &alice, &TOKEN));
+    assert_eq!(200, balance(&host, &bob, &TOKEN));
+}
@@ -0,0 +1,337 @@
+mod common;
+
+use common::*;
+use kernel::core::message::{AdminContent, BridgeMessage, Message};
+use kernel::core::public_key_hash::PublicKeyHash;
+use kernel::core::token::{TicketContent, Token};
+use tezos_smart_rollup_mock::MockHost;
+
+const DESTINATION: &str = "tz1Pe4aBjsW9ZGWaFXa47megxFD1LGGFAW3C";
+const TICKETER: &str = "KT1PWx2mnDueood7fEmfbBDKx1D9BAnnXitn";
+
+#[test]
+fn test_deposit_credits_the_account() {
+    let mut host = MockHost::default();
+    let alice = address(ALICE);
+
+    add_deposit(&mut host, &alice, &TOKEN, 1000);
+    add_deposit(&mut host, &alice, &TOKEN, 500);
+    run_level(&mut host);
+
+    assert_eq!(1500, balance(&host, &alice, &TOKEN));
+}
+
+#[test]
+fn test_deposit_not_from_bridge_is_a_ticket_of_its_ticketer() {
+    let mut host = MockHost::default();
+    let alice = address(ALICE);
+    set_admin(&mut host, CAROL);
+
+    add_message(
+        &mut host,
+        &signed_admin(CAROL, 1, AdminContent::AllowTicketer(TICKETER.to_string())),
+    );
+    add_deposit_from(&mut host, TICKETER, &alice, &TOKEN, 1000);
+    run_level(&mut host);
+
+    let ticket = Token::Ticket {
+        ticketer: TICKETER.to_string(),
+        content: TicketContent::Bytes(TOKEN.to_vec()),
+    };
+    assert_eq!(0, balance(&host, &alice, &TOKEN));
+    assert_eq!(1000, token_balance(&host, &alice, &ticket));
+}
+
+#[test]
+fn test_deposit_of_a_ticketer_not_allowed_is_returned() {
+    let mut host = MockHost::default();
+    let alice = address(ALICE);
+
+    // Both tickets are sent back as is to alice, not to the release entrypoint of the ticketer
+    add_deposit_from(&mut host, TICKETER, &alice, &TOKEN, 1000);
+    add_deposit_to_contract_from(&mut host, TICKETER, &alice, TICKETER, &TOKEN, 500);
+    let level = run_level(&mut host);
+
+    let ticket = Token::Ticket {
+        ticketer: TICKETER.to_string(),
+        content: TicketContent::Bytes(TOKEN.to_vec()),
+    };
+    assert_eq!(0, token_balance(&host, &alice, &ticket));
+    assert_eq!(2, host.outbox_at(level).len());
+}
+
+#[test]
+fn test_deposit_which_cannot_be_credited_is_refunded() {
+    let mut host = MockHost::default();
+    let alice = address(ALICE);
+
+    add_deposit(&mut host, &alice, &TOKEN, u128::MAX);
+    // The balance of alice would overflow
+    add_deposit(&mut host, &alice, &TOKEN, 1);
+    let level = run_level(&mut host);
+
+    assert_eq!(u128::MAX, balance(&host, &alice, &TOKEN));
+    assert_eq!(1, host.outbox_at(level).len());
+}
+
+#[test]
+fn test_external_deposit_is_rejected() {
+    let mut host = MockHost::default();
+    let alice = address(ALICE);
+
+    // Only the tickets sent by the L1 can credit an account
+    let deposit = BridgeMessage {
+        account: PublicKeyHash::from_b58(&alice).unwrap(),
+        token: Token::Bytes(TOKEN.to_vec()),
+        amount: 1000,
+        action: None,
+        source: None,
+        invalid_action: false,
+    };
+    let json = serde_json_wasm::to_string(&deposit).unwrap();
+    add_external_json(&mut host, &format!("{{\"Bridge\":{}}}", json));
+    run_level(&mut host);
+
+    assert_eq!(0, balance(&host, &alice, &TOKEN));
+}
+
+#[test]
+fn test_transfer() {
+    let mut host = MockHost::default();
+    let alice = address(ALICE);
+
+    add_deposit(&mut host, &alice, &TOKEN, 1000);
+    add_message(
+        &mut host,
+        &signed_transfer(ALICE, 1, &TOKEN, DESTINATION, 400),
+    );
+    run_level(&mut host);
+
+    assert_eq!(600, balance(&host, &alice, &TOKEN));
+    assert_eq!(400, balance(&host, DESTINATION, &TOKEN));
+    assert_eq!(1, nonce(&host, &alice));
+}
+
+#[test]
+fn test_transfers_across_levels() {
+    let mut host = MockHost::default();
+    let (alice, bob) = (address(ALICE), address(BOB));
+
+    add_deposit(&mut host, &alice, &TOKEN, 1000);
+    run_level(&mut host);
+
+    add_message(&mut host, &signed_transfer(ALICE, 1, &TOKEN, &bob, 300));
+    add_message(&mut host, &signed_transfer(ALICE, 2, &TOKEN, &bob, 200));
+    run_level(&mut host);
+
+    add_message(&mut host, &signed_transfer(BOB, 1, &TOKEN, &alice, 50));
+    run_level(&mut host);
+
+    assert_eq!(550, balance(&host, &alice, &TOKEN));
+    assert_eq!(450, balance(&host, &bob, &TOKEN));
+    assert_eq!(2, nonce(&host, &alice));
+    assert_eq!(1, nonce(&host, &bob));
+}
+
+#[test]
+fn test_transfer_with_invalid_nonce_is_rejected() {
+    let mut host = MockHost::default();
+    let alice = address(ALICE);
+
+    add_deposit(&mut host, &alice, &TOKEN, 1000);
+    add_message(
+        &mut host,
+        &signed_transfer(ALICE, 2, &TOKEN, DESTINATION, 400),
+    );
+    add_message(
+        &mut host,
+        &signed_transfer(ALICE, 0, &TOKEN, DESTINATION, 400),
+    );
+    run_level(&mut host);
+
+    assert_eq!(1000, balance(&host, &alice, &TOKEN));
+    assert_eq!(0, nonce(&host, &alice));
+}
+
+#[test]
+fn test_replayed_transfer_is_rejected() {
+    let mut host = MockHost::default();
+    let alice = address(ALICE);
+    let transfer = signed_transfer(ALICE, 1, &TOKEN, DESTINATION, 400);
+
+    add_deposit(&mut host, &alice, &TOKEN, 1000);
+    add_message(&mut host, &transfer);
+    add_message(&mut host, &transfer);
+    run_level(&mut host);
+
+    assert_eq!(600, balance(&host, &alice, &TOKEN));
+    assert_eq!(400, balance(&host, DESTINATION, &TOKEN));
+}
+
+#[test]
+fn test_lanes_have_their_own_nonces() {
+    let mut host = MockHost::default();
+    let (alice, bob) = (address(ALICE), address(BOB));
+
+    add_deposit(&mut host, &alice, &TOKEN, 1000);
+    run_level(&mut host);
+
+    // The first message of lane 1 is missing, which only blocks lane 1
+    add_message(
+        &mut host,
+        &signed_transfer_on_lane(ALICE, 1, 2, &TOKEN, &bob, 100),
+    );
+    add_message(
+        &mut host,
+        &signed_transfer_on_lane(ALICE, 2, 1, &TOKEN, &bob, 200),
+    );
+    add_message(&mut host, &signed_transfer(ALICE, 1, &TOKEN, &bob, 300));
+    run_level(&mut host);
+
+    assert_eq!(500, balance(&host, &bob, &TOKEN));
+    assert_eq!(1, nonce(&host, &alice));
+    assert_eq!(0, lane_nonce(&host, &alice, 1));
+    assert_eq!(1, lane_nonce(&host, &alice, 2));
+
+    // Once it lands, the lane goes on
+    add_message(
+        &mut host,
+        &signed_transfer_on_lane(ALICE, 1, 1, &TOKEN, &bob, 100),
+    );
+    add_message(
+        &mut host,
+        &signed_transfer_on_lane(ALICE, 1, 2, &TOKEN, &bob, 100),
+    );
+    run_level(&mut host);
+
+    assert_eq!(700, balance(&host, &bob, &TOKEN));
+    assert_eq!(2, lane_nonce(&host, &alice, 1));
+}
+
+#[test]
+fn test_lane_is_signed() {
+    let mut host = MockHost::default();
+    let (alice, bob) = (address(ALICE), address(BOB));
+
+    add_deposit(&mut host, &alice, &TOKEN, 1000);
+    run_level(&mut host);
+
+    // Moving a signed message to another lane invalidates its signature
+    let mut message = signed_transfer_on_lane(ALICE, 1, 1, &TOKEN, &bob, 100);
+    if let Message::Transfer(transfer) = &mut message {
+        transfer.inner.lane = 2;
+    }
+    add_message(&mut host, &message);
+    run_level(&mut host);
+
+    assert_eq!(0, balance(&host, &bob, &TOKEN));
+    assert_eq!(0, lane_nonce(&host, &alice, 2));
+}
+
+#[test]
+fn test_transfer_above_balance_consumes_the_nonce() {
+    let mut host = MockHost::default();
+    let alice = address(ALICE);
+
+    add_deposit(&mut host, &alice, &TOKEN, 100);
+    add_message(
+        &mut host,
+        &signed_transfer(ALICE, 1, &TOKEN, DESTINATION, 400),
+    );
+    run_level(&mut host);
+
+    assert_eq!(100, balance(&host, &alice, &TOKEN));
+    assert_eq!(0, balance(&host, DESTINATION, &TOKEN));
+    assert_eq!(1, nonce(&host, &alice));
+}
+
+#[test]
+fn test_transfer_overflowing_the_destination_is_rejected() {
+    let mut host = MockHost::default();
+    let (alice, bob) = (address(ALICE), address(BOB));
+
+    add_deposit(&mut host, &alice, &TOKEN, 1000);
+    add_deposit(&mut host, &bob, &TOKEN, u128::MAX);
+    add_message(&mut host, &signed_transfer(ALICE, 1, &TOKEN, &bob, 400));
+    run_level(&mut host);
+
+    assert_eq!(1000, balance(&host, &alice, &TOKEN));
+    assert_eq!(u128::MAX, balance(&host, &bob, &TOKEN));
+}
+
+#[test]
+fn test_transfer_with_forged_signature_is_rejected() {
+    let mut host = MockHost::default();
+    let alice = address(ALICE);
+
+    // Bob signs a transfer but presents Alice's public key
+    let forged = with_public_key(signed_transfer(BOB, 1, &TOKEN, DESTINATION, 400), ALICE);
+
+    add_deposit(&mut host, &alice, &TOKEN, 1000);
+    add_message(&mut host, &forged);
+    run_level(&mut host);
+
+    assert_eq!(1000, balance(&host, &alice, &TOKEN));
+    assert_eq!(0, nonce(&host, &alice));
+}
+
+#[test]
+fn test_transfer_to_oneself_does_not_mint() {
+    let mut host = MockHost::default();
+    let alice = address(ALICE);
+
+    add_deposit(&mut host, &alice, &TOKEN, 1000);
+    run_level(&mut host);
+
+    add_message(&mut host, &signed_transfer(ALICE, 1, &TOKEN, &alice, 400));
+    add_message(&mut host, &signed_transfer(ALICE, 2, &TOKEN, &alice, 1000));
+    add_message(&mut host, &signed_transfer(ALICE, 3, &TOKEN, &alice, 1001));
+    run_level(&mut host);
+
+    assert_eq!(1000, balance(&host, &alice, &TOKEN));
+}
+
+#[test]
+fn test_withdraw_burns_the_balance_and_writes_the_outbox() {
+    let mut host = MockHost::default();
+    let alice = address(ALICE);
+
+    add_deposit(&mut host, &alice, &TOKEN, 1000);
+    run_level(&mut host);
+
+    add_message(&mut host, &signed_withdraw(ALICE, 1, &TOKEN, &alice, 300));
+    add_message(&mut host, &signed_withdraw(ALICE, 2, &TOKEN, &alice, 3000));
+    let level = run_level(&mut host);
+
+    assert_eq!(700, balance(&host, &alice, &TOKEN));
+    assert_eq!(1, host.outbox_at(level).len());
+}
+
+#[test]
+fn test_deposit_to_a_contract_is_refunded() {
+    let mut host = MockHost::default();
+    let alice = address(ALICE);
+    let contract = "KT1PWx2mnDueood7fEmfbBDKx1D9BAnnXitn";
+
+    add_deposit_to_contract(&mut host, &alice, contract, &TOKEN, 1000);
+    add_deposit(&mut host, &alice, &TOKEN, 500);
+    let level = run_level(&mut host);
+
+    // Only the implicit accounts can be credited, the ticket goes back to its sender
+    assert_eq!(500, balance(&host, &alice, &TOKEN));
+    assert_eq!(1, host.outbox_at(level).len());
+}
+
+#[test]
+fn test_garbage_external_messages_are_ignored() {
+    let mut host = MockHost::default();
+    let alice = address(ALICE);
+
+    host.add_external(External(vec![0x55, 0xff, 0x00]));
+    host.add_external(External(b"U{\"Transfer\":{}}".to_vec()));
+    host.add_external(External(vec![0x00, 0x01]));
+    add_deposit(&mut host, &alice, &TOKEN, 1000);
+    run_level(&mut host);
+
+    assert_eq!(1000, balance(&host, &alice, &TOKEN));
+}
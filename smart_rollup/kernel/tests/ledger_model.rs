@@ -0,0 +1,215 @@
+mod common;
+
+use std::collections::BTreeMap;
+
+use common::*;
+use proptest::prelude::*;
+use tezos_smart_rollup_mock::MockHost;
+
+const ACCOUNTS: [&str; 3] = [ALICE, BOB, CAROL];
+const TOKENS: [[u8; 2]; 2] = [TOKEN, [0xab, 0xcd]];
+
+#[derive(Debug, Clone)]
+enum Operation {
+    Deposit {
+        to: usize,
+        token: usize,
+        amount: u128,
+    },
+    Transfer {
+        from: usize,
+        to: usize,
+        token: usize,
+        amount: u128,
+        nonce_offset: i64,
+        forged: bool,
+    },
+    Withdraw {
+        from: usize,
+        token: usize,
+        amount: u128,
+        nonce_offset: i64,
+    },
+}
+
+/// Mostly valid nonces, sometimes a replayed or a skipped one
+fn nonce_offset() -> impl Strategy<Value = i64> {
+    prop_oneof![8 => Just(0_i64), 1 => Just(-1_i64), 1 => Just(1_i64)]
+}
+
+fn operation() -> impl Strategy<Value = Operation> {
+    prop_oneof![
+        (0..3_usize, 0..2_usize, 1..1000_u128)
+            .prop_map(|(to, token, amount)| { Operation::Deposit { to, token, amount } }),
+        (
+            0..3_usize,
+            0..3_usize,
+            0..2_usize,
+            0..1200_u128,
+            nonce_offset(),
+            prop::bool::weighted(0.1)
+        )
+            .prop_map(|(from, to, token, amount, nonce_offset, forged)| {
+                Operation::Transfer {
+                    from,
+                    to,
+                    token,
+                    amount,
+                    nonce_offset,
+                    forged,
+                }
+            }),
+        (0..3_usize, 0..2_usize, 1..1200_u128, nonce_offset()).prop_map(
+            |(from, token, amount, nonce_offset)| Operation::Withdraw {
+                from,
+                token,
+                amount,
+                nonce_offset,
+            }
+        ),
+    ]
+}
+
+/// In-memory reference of the ledger
+#[derive(Default)]
+struct Model {
+    balances: BTreeMap<(usize, usize), u128>,
+    nonces: BTreeMap<usize, u64>,
+    deposited: BTreeMap<usize, u128>,
+    withdrawn: BTreeMap<usize, u128>,
+}
+
+impl Model {
+    fn balance(&self, account: usize, token: usize) -> u128 {
+        self.balances
+            .get(&(account, token))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    fn nonce(&self, account: usize) -> u64 {
+        self.nonces.get(&account).copied().unwrap_or_default()
+    }
+
+    /// Returns the nonce the operation is signed with
+    fn signed_nonce(&self, account: usize, offset: i64) -> u64 {
+        (self.nonce(account) as i64 + 1 + offset).max(0) as u64
+    }
+
+    /// Applies a signed operation, the nonce is consumed even if the amount is too high
+    fn debit(&mut self, account: usize, token: usize, amount: u128, nonce: u64) -> bool {
+        if nonce != self.nonce(account) + 1 {
+            return false;
+        }
+        self.nonces.insert(account, nonce);
+
+        let balance = self.balance(account, token);
+        if balance < amount {
+            return false;
+        }
+        self.balances.insert((account, token), balance - amount);
+        true
+    }
+
+    fn credit(&mut self, account: usize, token: usize, amount: u128) {
+        let balance = self.balance(account, token);
+        self.balances.insert((account, token), balance + amount);
+    }
+}
+
+fn apply(host: &mut MockHost, model: &mut Model, operation: &Operation) {
+    match *operation {
+        Operation::Deposit { to, token, amount } => {
+            add_deposit(host, &address(ACCOUNTS[to]), &TOKENS[token], amount);
+            model.credit(to, token, amount);
+            *model.deposited.entry(token).or_default() += amount;
+        }
+        Operation::Transfer {
+            from,
+            to,
+            token,
+            amount,
+            nonce_offset,
+            forged,
+        } => {
+            let nonce = model.signed_nonce(from, nonce_offset);
+            let destination = address(ACCOUNTS[to]);
+            let message = if forged {
+                // Signed by the next account on behalf of the source
+                let forger = ACCOUNTS[(from + 1) % ACCOUNTS.len()];
+                let message = signed_transfer(forger, nonce, &TOKENS[token], &destination, amount);
+                with_public_key(message, ACCOUNTS[from])
+            } else {
+                signed_transfer(ACCOUNTS[from], nonce, &TOKENS[token], &destination, amount)
+            };
+            add_message(host, &message);
+
+            if !forged && model.debit(from, token, amount, nonce) {
+                model.credit(to, token, amount);
+            }
+        }
+        Operation::Withdraw {
+            from,
+            token,
+            amount,
+            nonce_offset,
+        } => {
+            let nonce = model.signed_nonce(from, nonce_offset);
+            let account = address(ACCOUNTS[from]);
+            let message = signed_withdraw(ACCOUNTS[from], nonce, &TOKENS[token], &account, amount);
+            add_message(host, &message);
+
+            if model.debit(from, token, amount, nonce) {
+                *model.withdrawn.entry(token).or_default() += amount;
+            }
+        }
+    }
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(64))]
+
+    #[test]
+    fn test_ledger_matches_model(
+        levels in prop::collection::vec(prop::collection::vec(operation(), 0..12), 1..5)
+    ) {
+        let mut host = MockHost::default();
+        let mut model = Model::default();
+        let mut previous_nonces = [0_u64; 3];
+
+        for operations in levels.iter() {
+            for operation in operations.iter() {
+                apply(&mut host, &mut model, operation);
+            }
+            run_level(&mut host);
+
+            for (index, secret_key) in ACCOUNTS.iter().enumerate() {
+                let account = address(secret_key);
+
+                // Nonces only go up
+                let nonce = nonce(&host, &account);
+                prop_assert!(nonce >= previous_nonces[index]);
+                prop_assert_eq!(model.nonce(index), nonce);
+                previous_nonces[index] = nonce;
+
+                for (token_index, token) in TOKENS.iter().enumerate() {
+                    prop_assert_eq!(
+                        model.balance(index, token_index),
+                        balance(&host, &account, token)
+                    );
+                }
+            }
+        }
+
+        // Supply is conserved per token: what is on the ledger has been deposited and not withdrawn
+        for (token_index, token) in TOKENS.iter().enumerate() {
+            let supply: u128 = ACCOUNTS
+                .iter()
+                .map(|secret_key| balance(&host, &address(secret_key), token))
+                .sum();
+            let deposited = model.deposited.get(&token_index).copied().unwrap_or_default();
+            let withdrawn = model.withdrawn.get(&token_index).copied().unwrap_or_default();
+            prop_assert_eq!(deposited - withdrawn, supply);
+        }
+    }
+}
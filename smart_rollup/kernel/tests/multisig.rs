@@ -0,0 +1,170 @@
+mod common;
+
+use common::*;
+use kernel::core::message::{Message, MultisigContent, MultisigMessage};
+use kernel::core::public_key_hash::PublicKeyHash;
+use kernel_client::multisig;
+use tezos_smart_rollup_mock::MockHost;
+
+const KEYS: [&str; 3] = [ALICE, BOB, CAROL];
+
+#[test]
+fn test_threshold_of_the_keys_spends_from_the_account() {
+    let mut host = MockHost::default();
+    let (account, bob) = (multisig_address(&KEYS, 2), address(BOB));
+    add_deposit(&mut host, &account, &TOKEN, 1000);
+    run_level(&mut host);
+
+    // The order of the signatures does not matter
+    let content = multisig_transfer(&TOKEN, &bob, 300);
+    add_message(
+        &mut host,
+        &signed_multisig(&KEYS, 2, &[CAROL, ALICE], 1, content),
+    );
+    run_level(&mut host);
+
+    assert_eq!(700, balance(&host, &account, &TOKEN));
+    assert_eq!(300, balance(&host, &bob, &TOKEN));
+    assert_eq!(1, nonce(&host, &account));
+
+    // The nonce is tracked on the multisig account, not on its keys
+    assert_eq!(0, nonce(&host, &address(ALICE)));
+    assert_eq!(0, nonce(&host, &address(CAROL)));
+
+    // A replay is rejected
+    let content = multisig_transfer(&TOKEN, &bob, 300);
+    add_message(
+        &mut host,
+        &signed_multisig(&KEYS, 2, &[CAROL, ALICE], 1, content),
+    );
+    run_level(&mut host);
+    assert_eq!(700, balance(&host, &account, &TOKEN));
+}
+
+#[test]
+fn test_not_enough_signatures_are_rejected() {
+    let mut host = MockHost::default();
+    let (account, bob) = (multisig_address(&KEYS, 2), address(BOB));
+    add_deposit(&mut host, &account, &TOKEN, 1000);
+    run_level(&mut host);
+
+    // Below the threshold
+    let content = multisig_transfer(&TOKEN, &bob, 100);
+    add_message(&mut host, &signed_multisig(&KEYS, 2, &[ALICE], 1, content));
+    // The same key twice
+    let content = multisig_transfer(&TOKEN, &bob, 100);
+    add_message(
+        &mut host,
+        &signed_multisig(&KEYS, 2, &[ALICE, ALICE], 1, content),
+    );
+    // A key of another account
+    let content = multisig_transfer(&TOKEN, &bob, 100);
+    add_message(
+        &mut host,
+        &signed_multisig(&[ALICE, BOB], 1, &[CAROL], 1, content),
+    );
+    run_level(&mut host);
+
+    assert_eq!(1000, balance(&host, &account, &TOKEN));
+    assert_eq!(0, balance(&host, &bob, &TOKEN));
+    assert_eq!(0, nonce(&host, &account));
+}
+
+#[test]
+fn test_multisig_withdraw() {
+    let mut host = MockHost::default();
+    let (account, alice) = (multisig_address(&KEYS, 2), address(ALICE));
+    add_deposit(&mut host, &account, &TOKEN, 1000);
+    run_level(&mut host);
+
+    let content = multisig_withdraw(&TOKEN, &alice, 400);
+    add_message(
+        &mut host,
+        &signed_multisig(&KEYS, 2, &[ALICE, BOB, CAROL], 1, content),
+    );
+    let level = run_level(&mut host);
+
+    assert_eq!(600, balance(&host, &account, &TOKEN));
+    assert_eq!(1, host.outbox_at(level).len());
+}
+
+#[test]
+fn test_signatures_are_bound_to_the_multisig_address() {
+    let mut host = MockHost::default();
+    let (shared, other) = (
+        multisig_address(&[ALICE, BOB], 2),
+        multisig_address(&[ALICE, CAROL], 1),
+    );
+    let bob = address(BOB);
+    add_deposit(&mut host, &shared, &TOKEN, 1000);
+    add_deposit(&mut host, &other, &TOKEN, 1000);
+    run_level(&mut host);
+
+    // Alice signs her part of a transfer of the 2 of 2 account
+    let message = signed_multisig(
+        &[ALICE, BOB],
+        2,
+        &[ALICE],
+        1,
+        multisig_transfer(&TOKEN, &bob, 300),
+    );
+    let Message::Multisig(message) = message else {
+        unreachable!()
+    };
+
+    // Her signature alone meets the threshold of the 1 of 2 account
+    let account = multisig_account(&[ALICE, CAROL], 1);
+    let replay = MultisigMessage {
+        keys: account.keys().to_vec(),
+        threshold: account.threshold(),
+        signatures: message.signatures,
+        inner: message.inner,
+    };
+    add_message(&mut host, &Message::Multisig(replay));
+
+    // Signed without the address, her part of the 2 of 2 transfer was valid for any multisig
+    // with her key, such an inner is rejected
+    let mut inner = multisig(
+        PublicKeyHash::from_b58(&shared).unwrap(),
+        1,
+        multisig_transfer(&TOKEN, &bob, 300),
+    );
+    inner.account = None;
+    let signature = signer(ALICE).cosign(&inner, TIMESTAMP).unwrap();
+    let unbound = MultisigMessage {
+        keys: account.keys().to_vec(),
+        threshold: account.threshold(),
+        signatures: vec![signature],
+        inner,
+    };
+    add_message(&mut host, &Message::Multisig(unbound));
+    run_level(&mut host);
+
+    assert_eq!(1000, balance(&host, &other, &TOKEN));
+    assert_eq!(0, balance(&host, &bob, &TOKEN));
+    assert_eq!(0, nonce(&host, &other));
+}
+
+#[test]
+fn test_multisig_transfer_with_a_source_is_rejected() {
+    let mut host = MockHost::default();
+    let (account, bob) = (multisig_address(&KEYS, 1), address(BOB));
+    add_deposit(&mut host, &account, &TOKEN, 1000);
+    add_deposit(&mut host, &address(ALICE), &TOKEN, 1000);
+    run_level(&mut host);
+
+    let MultisigContent::Transfer(mut transfer) = multisig_transfer(&TOKEN, &bob, 300) else {
+        unreachable!()
+    };
+    transfer.source = Some(PublicKeyHash::from_b58(&address(ALICE)).unwrap());
+    add_message(
+        &mut host,
+        &signed_multisig(&KEYS, 1, &[ALICE], 1, MultisigContent::Transfer(transfer)),
+    );
+    run_level(&mut host);
+
+    assert_eq!(1000, balance(&host, &account, &TOKEN));
+    assert_eq!(1000, balance(&host, &address(ALICE), &TOKEN));
+    assert_eq!(0, balance(&host, &bob, &TOKEN));
+    assert_eq!(0, nonce(&host, &account));
+}
@@ -0,0 +1,423 @@
+mod common;
+
+use common::*;
+use kernel::constants::{MAX_ORDERS_PER_PRICE_LEVEL, MAX_ORDER_MATCHES, MAX_PRICE_LEVELS};
+use kernel::core::book::{market_account, market_id, Side};
+use kernel::core::message::{AdminContent, FreezeContent, OrderContent};
+use kernel::core::public_key_hash::PublicKeyHash;
+use kernel::core::token::Token;
+use tezos_smart_rollup_mock::MockHost;
+
+const QUOTE: [u8; 2] = [0xab, 0xcd];
+
+fn place(side: Side, price: u128, amount: u128) -> OrderContent {
+    OrderContent::Place {
+        base: Token::Bytes(TOKEN.to_vec()),
+        quote: Token::Bytes(QUOTE.to_vec()),
+        side,
+        price,
+        amount,
+    }
+}
+
+/// Balances of the account escrowing the orders of the market
+fn escrow(host: &MockHost) -> (u128, u128) {
+    let market = market_id(&Token::Bytes(TOKEN.to_vec()), &Token::Bytes(QUOTE.to_vec()));
+    let account = market_account(&market).unwrap().to_string();
+    (
+        balance(host, &account, &TOKEN),
+        balance(host, &account, &QUOTE),
+    )
+}
+
+/// Alice, bob and carol hold 100_000 of the base token and 100_000 of the quote token each
+fn setup(host: &mut MockHost) {
+    for secret_key in [ALICE, BOB, CAROL] {
+        add_deposit(host, &address(secret_key), &TOKEN, 100_000);
+        add_deposit(host, &address(secret_key), &QUOTE, 100_000);
+    }
+    run_level(host);
+}
+
+#[test]
+fn test_order_rests_in_the_book() {
+    let mut host = MockHost::default();
+    setup(&mut host);
+    let alice = address(ALICE);
+
+    add_message(
+        &mut host,
+        &signed_order(ALICE, 1, place(Side::Buy, 3, 10_000)),
+    );
+    add_message(
+        &mut host,
+        &signed_order(BOB, 1, place(Side::Sell, 4, 10_000)),
+    );
+    run_level(&mut host);
+
+    // The orders do not cross, their funds are escrowed
+    assert_eq!(70_000, balance(&host, &alice, &QUOTE));
+    assert_eq!(90_000, balance(&host, &address(BOB), &TOKEN));
+    assert_eq!((10_000, 30_000), escrow(&host));
+    assert!(fill(&host, 0).is_none());
+}
+
+#[test]
+fn test_order_is_filled_at_the_price_of_the_resting_order() {
+    let mut host = MockHost::default();
+    setup(&mut host);
+    let (alice, bob) = (address(ALICE), address(BOB));
+
+    add_message(
+        &mut host,
+        &signed_order(ALICE, 1, place(Side::Sell, 3, 10_000)),
+    );
+    add_message(
+        &mut host,
+        &signed_order(BOB, 1, place(Side::Buy, 4, 10_000)),
+    );
+    let level = run_level(&mut host);
+
+    assert_eq!(90_000, balance(&host, &alice, &TOKEN));
+    assert_eq!(130_000, balance(&host, &alice, &QUOTE));
+    assert_eq!(110_000, balance(&host, &bob, &TOKEN));
+    assert_eq!(70_000, balance(&host, &bob, &QUOTE));
+    assert_eq!((0, 0), escrow(&host));
+
+    let receipt = fill(&host, 0).unwrap();
+    assert_eq!((0, 1), (receipt.maker_order, receipt.taker_order));
+    assert_eq!(alice, receipt.maker.to_string());
+    assert_eq!(bob, receipt.taker.to_string());
+    assert!(receipt.taker_side == Side::Buy);
+    assert_eq!(
+        (3, 10_000, level),
+        (receipt.price, receipt.amount, receipt.level)
+    );
+    assert!(fill(&host, 1).is_none());
+
+    // The filled order is not in the book anymore
+    add_message(
+        &mut host,
+        &signed_order(ALICE, 2, OrderContent::Cancel { id: 0 }),
+    );
+    run_level(&mut host);
+    assert_eq!(130_000, balance(&host, &alice, &QUOTE));
+}
+
+#[test]
+fn test_remaining_part_of_a_partially_filled_order_rests() {
+    let mut host = MockHost::default();
+    setup(&mut host);
+    let (bob, carol) = (address(BOB), address(CAROL));
+
+    add_message(
+        &mut host,
+        &signed_order(ALICE, 1, place(Side::Sell, 3, 5_000)),
+    );
+    add_message(
+        &mut host,
+        &signed_order(BOB, 1, place(Side::Buy, 3, 10_000)),
+    );
+    run_level(&mut host);
+
+    assert_eq!(105_000, balance(&host, &bob, &TOKEN));
+    assert_eq!(70_000, balance(&host, &bob, &QUOTE));
+    assert_eq!((0, 15_000), escrow(&host));
+
+    // Carol sells below the price of bob, she gets the price of bob
+    add_message(
+        &mut host,
+        &signed_order(CAROL, 1, place(Side::Sell, 2, 8_000)),
+    );
+    run_level(&mut host);
+
+    assert_eq!(110_000, balance(&host, &bob, &TOKEN));
+    assert_eq!(92_000, balance(&host, &carol, &TOKEN));
+    assert_eq!(115_000, balance(&host, &carol, &QUOTE));
+    assert_eq!((3_000, 0), escrow(&host));
+
+    let receipt = fill(&host, 1).unwrap();
+    assert_eq!(
+        (1, 2, 3, 5_000),
+        (
+            receipt.maker_order,
+            receipt.taker_order,
+            receipt.price,
+            receipt.amount
+        )
+    );
+}
+
+#[test]
+fn test_orders_are_filled_by_price_then_by_time() {
+    let mut host = MockHost::default();
+    setup(&mut host);
+    let (alice, bob, carol) = (address(ALICE), address(BOB), address(CAROL));
+
+    add_message(
+        &mut host,
+        &signed_order(ALICE, 1, place(Side::Sell, 4, 5_000)),
+    );
+    add_message(
+        &mut host,
+        &signed_order(BOB, 1, place(Side::Sell, 3, 5_000)),
+    );
+    add_message(
+        &mut host,
+        &signed_order(ALICE, 2, place(Side::Sell, 3, 5_000)),
+    );
+    add_message(
+        &mut host,
+        &signed_order(CAROL, 1, place(Side::Buy, 4, 7_000)),
+    );
+    run_level(&mut host);
+
+    // Bob was first at the best price, then the second order of alice
+    let (first, second) = (fill(&host, 0).unwrap(), fill(&host, 1).unwrap());
+    assert_eq!(
+        (1, 5_000, 3),
+        (first.maker_order, first.amount, first.price)
+    );
+    assert_eq!(
+        (2, 2_000, 3),
+        (second.maker_order, second.amount, second.price)
+    );
+    assert!(fill(&host, 2).is_none());
+
+    assert_eq!(115_000, balance(&host, &bob, &QUOTE));
+    assert_eq!(106_000, balance(&host, &alice, &QUOTE));
+    assert_eq!(107_000, balance(&host, &carol, &TOKEN));
+    assert_eq!(79_000, balance(&host, &carol, &QUOTE));
+    assert_eq!((8_000, 0), escrow(&host));
+}
+
+#[test]
+fn test_cancel_returns_the_escrow() {
+    let mut host = MockHost::default();
+    setup(&mut host);
+    let alice = address(ALICE);
+
+    add_message(
+        &mut host,
+        &signed_order(ALICE, 1, place(Side::Buy, 3, 10_000)),
+    );
+    add_message(
+        &mut host,
+        &signed_order(BOB, 1, place(Side::Sell, 3, 4_000)),
+    );
+    // Only the owner can cancel the order
+    add_message(
+        &mut host,
+        &signed_order(BOB, 2, OrderContent::Cancel { id: 0 }),
+    );
+    run_level(&mut host);
+    assert_eq!((0, 18_000), escrow(&host));
+
+    add_message(
+        &mut host,
+        &signed_order(ALICE, 2, OrderContent::Cancel { id: 0 }),
+    );
+    run_level(&mut host);
+
+    assert_eq!(104_000, balance(&host, &alice, &TOKEN));
+    assert_eq!(88_000, balance(&host, &alice, &QUOTE));
+    assert_eq!((0, 0), escrow(&host));
+
+    // The cancelled order can't be filled
+    add_message(
+        &mut host,
+        &signed_order(BOB, 3, place(Side::Sell, 3, 4_000)),
+    );
+    run_level(&mut host);
+    assert_eq!((4_000, 0), escrow(&host));
+    assert!(fill(&host, 1).is_none());
+}
+
+#[test]
+fn test_order_beyond_the_balance_is_rejected() {
+    let mut host = MockHost::default();
+    setup(&mut host);
+    let alice = address(ALICE);
+
+    add_message(
+        &mut host,
+        &signed_order(ALICE, 1, place(Side::Buy, 3, 34_000)),
+    );
+    add_message(
+        &mut host,
+        &signed_order(ALICE, 2, place(Side::Sell, 3, 100_001)),
+    );
+    add_message(
+        &mut host,
+        &signed_order(ALICE, 3, place(Side::Sell, 0, 10_000)),
+    );
+    run_level(&mut host);
+
+    assert_eq!(3, nonce(&host, &alice));
+    assert_eq!(100_000, balance(&host, &alice, &TOKEN));
+    assert_eq!(100_000, balance(&host, &alice, &QUOTE));
+    assert_eq!((0, 0), escrow(&host));
+}
+
+#[test]
+fn test_order_below_the_minimum_is_rejected() {
+    let mut host = MockHost::default();
+    setup(&mut host);
+    let alice = address(ALICE);
+
+    add_message(
+        &mut host,
+        &signed_order(ALICE, 1, place(Side::Sell, 3, 999)),
+    );
+    add_message(
+        &mut host,
+        &signed_order(ALICE, 2, place(Side::Sell, 3, 1_000)),
+    );
+    run_level(&mut host);
+
+    assert_eq!(99_000, balance(&host, &alice, &TOKEN));
+    assert_eq!((1_000, 0), escrow(&host));
+}
+
+#[test]
+fn test_order_with_too_many_matches_is_rejected() {
+    let mut host = MockHost::default();
+    setup(&mut host);
+    let carol = address(CAROL);
+
+    let resting = MAX_ORDER_MATCHES as u64 + 1;
+    for nonce in 1..=resting {
+        add_message(
+            &mut host,
+            &signed_order(ALICE, nonce, place(Side::Sell, 3, 1_000)),
+        );
+    }
+    run_level(&mut host);
+
+    // The order would still cross the last resting order after the capped matches
+    add_message(
+        &mut host,
+        &signed_order(CAROL, 1, place(Side::Buy, 3, 34_000)),
+    );
+    run_level(&mut host);
+
+    assert!(fill(&host, 0).is_none());
+    assert_eq!(100_000, balance(&host, &carol, &TOKEN));
+    assert_eq!(100_000, balance(&host, &carol, &QUOTE));
+    assert_eq!((resting as u128 * 1_000, 0), escrow(&host));
+
+    // Up to the cap, the order is matched
+    let matched = MAX_ORDER_MATCHES as u128 * 1_000;
+    add_message(
+        &mut host,
+        &signed_order(CAROL, 2, place(Side::Buy, 3, matched)),
+    );
+    run_level(&mut host);
+
+    assert!(fill(&host, MAX_ORDER_MATCHES as u64 - 1).is_some());
+    assert!(fill(&host, MAX_ORDER_MATCHES as u64).is_none());
+    assert_eq!(100_000 + matched, balance(&host, &carol, &TOKEN));
+    assert_eq!(100_000 - 3 * matched, balance(&host, &carol, &QUOTE));
+    assert_eq!((1_000, 0), escrow(&host));
+}
+
+#[test]
+fn test_price_levels_of_a_side_are_bounded() {
+    let mut host = MockHost::default();
+    setup(&mut host);
+    let alice = address(ALICE);
+
+    let levels = MAX_PRICE_LEVELS as u64;
+    for nonce in 1..=levels {
+        let price = nonce as u128 + 1;
+        add_message(
+            &mut host,
+            &signed_order(ALICE, nonce, place(Side::Sell, price, 1_000)),
+        );
+    }
+    run_level(&mut host);
+    let escrowed = MAX_PRICE_LEVELS as u128 * 1_000;
+    assert_eq!((escrowed, 0), escrow(&host));
+
+    // A new price is rejected, an existing one is still accepted
+    add_message(
+        &mut host,
+        &signed_order(ALICE, levels + 1, place(Side::Sell, 1_000, 1_000)),
+    );
+    add_message(
+        &mut host,
+        &signed_order(ALICE, levels + 2, place(Side::Sell, 2, 1_000)),
+    );
+    run_level(&mut host);
+
+    assert_eq!((escrowed + 1_000, 0), escrow(&host));
+    assert_eq!(100_000 - escrowed - 1_000, balance(&host, &alice, &TOKEN));
+}
+
+#[test]
+fn test_orders_of_a_price_level_are_bounded() {
+    let mut host = MockHost::default();
+    setup(&mut host);
+
+    let orders = MAX_ORDERS_PER_PRICE_LEVEL as u64;
+    for nonce in 1..=orders + 1 {
+        add_message(
+            &mut host,
+            &signed_order(ALICE, nonce, place(Side::Sell, 3, 1_000)),
+        );
+    }
+    run_level(&mut host);
+
+    assert_eq!((orders as u128 * 1_000, 0), escrow(&host));
+}
+
+#[test]
+fn test_orders_of_frozen_makers_are_cancelled() {
+    let mut host = MockHost::default();
+    setup(&mut host);
+    let (alice, bob, carol) = (address(ALICE), address(BOB), address(CAROL));
+    set_admin(&mut host, CAROL);
+
+    add_message(
+        &mut host,
+        &signed_order(ALICE, 1, place(Side::Sell, 3, 10_000)),
+    );
+    add_message(
+        &mut host,
+        &signed_order(BOB, 1, place(Side::Sell, 3, 5_000)),
+    );
+    let freeze = FreezeContent {
+        account: PublicKeyHash::from_b58(&alice).unwrap(),
+        token: None,
+    };
+    add_message(
+        &mut host,
+        &signed_admin(CAROL, 1, AdminContent::Freeze(freeze)),
+    );
+    run_level(&mut host);
+
+    add_message(
+        &mut host,
+        &signed_order(CAROL, 2, place(Side::Buy, 3, 10_000)),
+    );
+    run_level(&mut host);
+
+    // The escrow of alice is credited back to her frozen account
+    assert_eq!(100_000, balance(&host, &alice, &TOKEN));
+    assert_eq!(100_000, balance(&host, &alice, &QUOTE));
+
+    // Bob is filled, the rest of the order of carol rests
+    let receipt = fill(&host, 0).unwrap();
+    assert_eq!((1, 5_000), (receipt.maker_order, receipt.amount));
+    assert!(fill(&host, 1).is_none());
+    assert_eq!(115_000, balance(&host, &bob, &QUOTE));
+    assert_eq!(105_000, balance(&host, &carol, &TOKEN));
+    assert_eq!(70_000, balance(&host, &carol, &QUOTE));
+    assert_eq!((0, 15_000), escrow(&host));
+
+    // The order of alice is not in the book anymore
+    add_message(&mut host, &signed_order(BOB, 2, place(Side::Buy, 3, 5_000)));
+    run_level(&mut host);
+    assert!(fill(&host, 1).is_none());
+    assert_eq!((0, 30_000), escrow(&host));
+}
@@ -0,0 +1,192 @@
+mod common;
+
+use common::*;
+use kernel::constants::MAX_PENDING_DEPOSITS_PER_LEVEL;
+use kernel::core::message::{AdminContent, OperationKind};
+use tezos_smart_rollup_mock::MockHost;
+
+#[test]
+fn test_only_the_admin_can_pause() {
+    let mut host = MockHost::default();
+    let alice = address(ALICE);
+    let bob = address(BOB);
+    set_admin(&mut host, CAROL);
+
+    add_deposit(&mut host, &alice, &TOKEN, 1000);
+    add_message(
+        &mut host,
+        &signed_admin(ALICE, 1, AdminContent::Pause(OperationKind::Transfer)),
+    );
+    run_level(&mut host);
+
+    add_message(&mut host, &signed_transfer(ALICE, 1, &TOKEN, &bob, 400));
+    run_level(&mut host);
+
+    assert_eq!(600, balance(&host, &alice, &TOKEN));
+    assert_eq!(400, balance(&host, &bob, &TOKEN));
+}
+
+#[test]
+fn test_paused_transfers_are_rejected() {
+    let mut host = MockHost::default();
+    let alice = address(ALICE);
+    let bob = address(BOB);
+    set_admin(&mut host, CAROL);
+
+    add_deposit(&mut host, &alice, &TOKEN, 1000);
+    add_message(
+        &mut host,
+        &signed_admin(CAROL, 1, AdminContent::Pause(OperationKind::Transfer)),
+    );
+    run_level(&mut host);
+
+    add_message(&mut host, &signed_transfer(ALICE, 1, &TOKEN, &bob, 400));
+    run_level(&mut host);
+    assert_eq!(1000, balance(&host, &alice, &TOKEN));
+    assert_eq!(0, nonce(&host, &alice));
+
+    add_message(
+        &mut host,
+        &signed_admin(CAROL, 2, AdminContent::Unpause(OperationKind::Transfer)),
+    );
+    add_message(&mut host, &signed_transfer(ALICE, 1, &TOKEN, &bob, 400));
+    run_level(&mut host);
+    assert_eq!(600, balance(&host, &alice, &TOKEN));
+    assert_eq!(400, balance(&host, &bob, &TOKEN));
+}
+
+#[test]
+fn test_paused_withdrawals_are_rejected() {
+    let mut host = MockHost::default();
+    let alice = address(ALICE);
+    set_admin(&mut host, CAROL);
+
+    add_deposit(&mut host, &alice, &TOKEN, 1000);
+    add_message(
+        &mut host,
+        &signed_admin(CAROL, 1, AdminContent::Pause(OperationKind::Withdraw)),
+    );
+    run_level(&mut host);
+
+    add_message(&mut host, &signed_withdraw(ALICE, 1, &TOKEN, &alice, 300));
+    let level = run_level(&mut host);
+    assert_eq!(1000, balance(&host, &alice, &TOKEN));
+    assert!(host.outbox_at(level).is_empty());
+
+    add_message(
+        &mut host,
+        &signed_admin(CAROL, 2, AdminContent::Unpause(OperationKind::Withdraw)),
+    );
+    add_message(&mut host, &signed_withdraw(ALICE, 1, &TOKEN, &alice, 300));
+    let level = run_level(&mut host);
+    assert_eq!(700, balance(&host, &alice, &TOKEN));
+    assert_eq!(1, host.outbox_at(level).len());
+}
+
+#[test]
+fn test_deposits_are_queued_while_paused() {
+    let mut host = MockHost::default();
+    let alice = address(ALICE);
+    let bob = address(BOB);
+    set_admin(&mut host, CAROL);
+
+    add_message(
+        &mut host,
+        &signed_admin(CAROL, 1, AdminContent::Pause(OperationKind::Deposit)),
+    );
+    run_level(&mut host);
+
+    add_deposit(&mut host, &alice, &TOKEN, 1000);
+    add_deposit(&mut host, &bob, &TOKEN, 500);
+    add_deposit(&mut host, &alice, &TOKEN, 200);
+    run_level(&mut host);
+    assert_eq!(0, balance(&host, &alice, &TOKEN));
+    assert_eq!(0, balance(&host, &bob, &TOKEN));
+
+    add_message(
+        &mut host,
+        &signed_admin(CAROL, 2, AdminContent::Unpause(OperationKind::Deposit)),
+    );
+    run_level(&mut host);
+    run_level(&mut host);
+
+    assert_eq!(1200, balance(&host, &alice, &TOKEN));
+    assert_eq!(500, balance(&host, &bob, &TOKEN));
+
+    // The queue starts over once emptied
+    add_message(
+        &mut host,
+        &signed_admin(CAROL, 3, AdminContent::Pause(OperationKind::Deposit)),
+    );
+    run_level(&mut host);
+    add_deposit(&mut host, &bob, &TOKEN, 100);
+    add_message(
+        &mut host,
+        &signed_admin(CAROL, 4, AdminContent::Unpause(OperationKind::Deposit)),
+    );
+    run_level(&mut host);
+    run_level(&mut host);
+    assert_eq!(600, balance(&host, &bob, &TOKEN));
+}
+
+#[test]
+fn test_queued_deposits_are_credited_in_batches() {
+    let mut host = MockHost::default();
+    let alice = address(ALICE);
+    set_admin(&mut host, CAROL);
+
+    add_message(
+        &mut host,
+        &signed_admin(CAROL, 1, AdminContent::Pause(OperationKind::Deposit)),
+    );
+    run_level(&mut host);
+
+    let deposits = MAX_PENDING_DEPOSITS_PER_LEVEL as u128 + 5;
+    for _ in 0..deposits {
+        add_deposit(&mut host, &alice, &TOKEN, 1);
+    }
+    add_message(
+        &mut host,
+        &signed_admin(CAROL, 2, AdminContent::Unpause(OperationKind::Deposit)),
+    );
+    run_level(&mut host);
+    assert_eq!(0, balance(&host, &alice, &TOKEN));
+
+    run_level(&mut host);
+    assert_eq!(
+        MAX_PENDING_DEPOSITS_PER_LEVEL as u128,
+        balance(&host, &alice, &TOKEN)
+    );
+
+    run_level(&mut host);
+    assert_eq!(deposits, balance(&host, &alice, &TOKEN));
+}
+
+#[test]
+fn test_queued_deposit_which_cannot_be_credited_is_refunded() {
+    let mut host = MockHost::default();
+    let (alice, bob) = (address(ALICE), address(BOB));
+    set_admin(&mut host, CAROL);
+
+    add_deposit(&mut host, &alice, &TOKEN, u128::MAX);
+    add_message(
+        &mut host,
+        &signed_admin(CAROL, 1, AdminContent::Pause(OperationKind::Deposit)),
+    );
+    run_level(&mut host);
+
+    // The balance of alice would overflow, the deposit is at the head of the queue
+    add_deposit(&mut host, &alice, &TOKEN, 1);
+    add_deposit(&mut host, &bob, &TOKEN, 500);
+    add_message(
+        &mut host,
+        &signed_admin(CAROL, 2, AdminContent::Unpause(OperationKind::Deposit)),
+    );
+    run_level(&mut host);
+    let level = run_level(&mut host);
+
+    assert_eq!(u128::MAX, balance(&host, &alice, &TOKEN));
+    assert_eq!(500, balance(&host, &bob, &TOKEN));
+    assert_eq!(1, host.outbox_at(level).len());
+    assert!(!is_stored(&host, "/pending/length"));
+}
@@ -0,0 +1,264 @@
+mod common;
+
+use common::*;
+use kernel::core::message::PoolContent;
+use kernel::core::pool::Pool;
+use kernel::core::token::Token;
+use tezos_smart_rollup_mock::MockHost;
+
+const OTHER_TOKEN: [u8; 2] = [0xab, 0xcd];
+
+fn token() -> Token {
+    Token::Bytes(TOKEN.to_vec())
+}
+
+fn other_token() -> Token {
+    Token::Bytes(OTHER_TOKEN.to_vec())
+}
+
+fn pool() -> Pool {
+    Pool::new(&token(), &other_token(), 30)
+}
+
+/// Bytes of the LP token of the pool
+fn lp_token() -> Vec<u8> {
+    match pool().lp_token() {
+        Token::Bytes(bytes) => bytes,
+        _ => unreachable!(),
+    }
+}
+
+/// Alice creates a pool of 1000 TOKEN and 4000 OTHER_TOKEN, bob holds 1000 of each, returns the level
+fn setup(host: &mut MockHost) -> u32 {
+    let (alice, bob) = (address(ALICE), address(BOB));
+    add_deposit(host, &alice, &TOKEN, 1000);
+    add_deposit(host, &alice, &OTHER_TOKEN, 4000);
+    add_deposit(host, &bob, &TOKEN, 1000);
+    add_deposit(host, &bob, &OTHER_TOKEN, 1000);
+    let content = PoolContent::CreatePool {
+        token_a: token(),
+        amount_a: 1000,
+        token_b: other_token(),
+        amount_b: 4000,
+        fee: 30,
+    };
+    add_message(host, &signed_pool(ALICE, 1, content));
+    run_level(host)
+}
+
+fn reserves(host: &MockHost) -> (u128, u128) {
+    let account = pool().account().unwrap().to_string();
+    (
+        balance(host, &account, &TOKEN),
+        balance(host, &account, &OTHER_TOKEN),
+    )
+}
+
+#[test]
+fn test_create_pool() {
+    let mut host = MockHost::default();
+    setup(&mut host);
+    let alice = address(ALICE);
+
+    assert_eq!((1000, 4000), reserves(&host));
+    assert_eq!(0, balance(&host, &alice, &TOKEN));
+    assert_eq!(0, balance(&host, &alice, &OTHER_TOKEN));
+    assert_eq!(2000, balance(&host, &alice, &lp_token()));
+}
+
+#[test]
+fn test_pool_of_a_pair_is_created_once() {
+    let mut host = MockHost::default();
+    setup(&mut host);
+
+    // Same pair, in the other order
+    let content = PoolContent::CreatePool {
+        token_a: other_token(),
+        amount_a: 100,
+        token_b: token(),
+        amount_b: 100,
+        fee: 10,
+    };
+    add_message(&mut host, &signed_pool(BOB, 1, content));
+    run_level(&mut host);
+
+    assert_eq!((1000, 4000), reserves(&host));
+    assert_eq!(1000, balance(&host, &address(BOB), &TOKEN));
+}
+
+#[test]
+fn test_swap() {
+    let mut host = MockHost::default();
+    let level = setup(&mut host);
+    let bob = address(BOB);
+
+    let content = PoolContent::Swap {
+        token_in: token(),
+        amount_in: 100,
+        token_out: other_token(),
+        min_out: 360,
+        deadline_level: level + 1,
+    };
+    add_message(&mut host, &signed_pool(BOB, 1, content));
+    run_level(&mut host);
+
+    // 99.7 * 4000 / (1000 + 99.7), rounded down
+    assert_eq!(900, balance(&host, &bob, &TOKEN));
+    assert_eq!(1362, balance(&host, &bob, &OTHER_TOKEN));
+    assert_eq!((1100, 3638), reserves(&host));
+}
+
+#[test]
+fn test_swap_beyond_slippage_is_rejected() {
+    let mut host = MockHost::default();
+    let level = setup(&mut host);
+    let bob = address(BOB);
+
+    let content = PoolContent::Swap {
+        token_in: token(),
+        amount_in: 100,
+        token_out: other_token(),
+        min_out: 363,
+        deadline_level: level + 1,
+    };
+    add_message(&mut host, &signed_pool(BOB, 1, content));
+    run_level(&mut host);
+
+    assert_eq!(1000, balance(&host, &bob, &TOKEN));
+    assert_eq!((1000, 4000), reserves(&host));
+}
+
+#[test]
+fn test_swap_after_deadline_is_rejected() {
+    let mut host = MockHost::default();
+    let level = setup(&mut host);
+    let bob = address(BOB);
+
+    let content = PoolContent::Swap {
+        token_in: token(),
+        amount_in: 100,
+        token_out: other_token(),
+        min_out: 0,
+        deadline_level: level,
+    };
+    add_message(&mut host, &signed_pool(BOB, 1, content));
+    run_level(&mut host);
+
+    assert_eq!(1000, balance(&host, &bob, &TOKEN));
+    assert_eq!((1000, 4000), reserves(&host));
+}
+
+#[test]
+fn test_add_and_remove_liquidity() {
+    let mut host = MockHost::default();
+    let level = setup(&mut host);
+    let bob = address(BOB);
+    let lp_token = lp_token();
+
+    let content = PoolContent::AddLiquidity {
+        token_a: other_token(),
+        max_a: 1000,
+        token_b: token(),
+        max_b: 250,
+        shares: 500,
+        deadline_level: level + 1,
+    };
+    add_message(&mut host, &signed_pool(BOB, 1, content));
+    run_level(&mut host);
+
+    assert_eq!((1250, 5000), reserves(&host));
+    assert_eq!(500, balance(&host, &bob, &lp_token));
+    assert_eq!(750, balance(&host, &bob, &TOKEN));
+    assert_eq!(0, balance(&host, &bob, &OTHER_TOKEN));
+
+    let content = PoolContent::RemoveLiquidity {
+        token_a: token(),
+        min_a: 250,
+        token_b: other_token(),
+        min_b: 1000,
+        shares: 500,
+        deadline_level: level + 2,
+    };
+    add_message(&mut host, &signed_pool(BOB, 2, content));
+    run_level(&mut host);
+
+    assert_eq!((1000, 4000), reserves(&host));
+    assert_eq!(0, balance(&host, &bob, &lp_token));
+    assert_eq!(1000, balance(&host, &bob, &TOKEN));
+    assert_eq!(1000, balance(&host, &bob, &OTHER_TOKEN));
+}
+
+#[test]
+fn test_add_liquidity_beyond_slippage_is_rejected() {
+    let mut host = MockHost::default();
+    let level = setup(&mut host);
+    let bob = address(BOB);
+
+    let content = PoolContent::AddLiquidity {
+        token_a: token(),
+        max_a: 249,
+        token_b: other_token(),
+        max_b: 1000,
+        shares: 500,
+        deadline_level: level + 1,
+    };
+    add_message(&mut host, &signed_pool(BOB, 1, content));
+    run_level(&mut host);
+
+    assert_eq!((1000, 4000), reserves(&host));
+    assert_eq!(0, balance(&host, &bob, &lp_token()));
+}
+
+#[test]
+fn test_lp_token_can_not_be_withdrawn() {
+    let mut host = MockHost::default();
+    setup(&mut host);
+    let alice = address(ALICE);
+    let lp_token = lp_token();
+
+    add_message(
+        &mut host,
+        &signed_withdraw(ALICE, 2, &lp_token, &alice, 100),
+    );
+    let level = run_level(&mut host);
+
+    assert_eq!(2000, balance(&host, &alice, &lp_token));
+    assert!(host.outbox_at(level).is_empty());
+}
+
+#[test]
+fn test_pool_without_liquidity_is_deleted_and_created_again() {
+    let mut host = MockHost::default();
+    let level = setup(&mut host);
+    let alice = address(ALICE);
+    let pool_path = format!("/pool/{}", pool().id());
+
+    let content = PoolContent::RemoveLiquidity {
+        token_a: token(),
+        min_a: 1000,
+        token_b: other_token(),
+        min_b: 4000,
+        shares: 2000,
+        deadline_level: level + 1,
+    };
+    add_message(&mut host, &signed_pool(ALICE, 2, content));
+    run_level(&mut host);
+
+    assert_eq!((0, 0), reserves(&host));
+    assert_eq!(0, balance(&host, &alice, &lp_token()));
+    assert!(!is_stored(&host, &pool_path));
+
+    let content = PoolContent::CreatePool {
+        token_a: token(),
+        amount_a: 100,
+        token_b: other_token(),
+        amount_b: 900,
+        fee: 10,
+    };
+    add_message(&mut host, &signed_pool(ALICE, 3, content));
+    run_level(&mut host);
+
+    assert!(is_stored(&host, &pool_path));
+    assert_eq!((100, 900), reserves(&host));
+    assert_eq!(300, balance(&host, &alice, &lp_token()));
+}
@@ -0,0 +1,118 @@
+mod common;
+
+use common::*;
+use tezos_smart_rollup_mock::MockHost;
+
+const OTHER_TOKEN: [u8; 2] = [0xab, 0xcd];
+
+#[test]
+fn test_session_key_transfers_up_to_its_limit() {
+    let mut host = MockHost::default();
+    let (alice, bob, carol) = (address(ALICE), address(BOB), address(CAROL));
+    add_deposit(&mut host, &alice, &TOKEN, 1000);
+    let level = run_level(&mut host);
+
+    add_message(
+        &mut host,
+        &signed_authorize_session_key(ALICE, 1, &bob, &TOKEN, 300, level + 20),
+    );
+    // Bob signs with the nonces of alice
+    add_message(
+        &mut host,
+        &signed_session_transfer(BOB, &alice, 2, &TOKEN, &carol, 200),
+    );
+    run_level(&mut host);
+
+    assert_eq!(800, balance(&host, &alice, &TOKEN));
+    assert_eq!(200, balance(&host, &carol, &TOKEN));
+    assert_eq!(2, nonce(&host, &alice));
+    assert_eq!(0, nonce(&host, &bob));
+
+    // Beyond the limit, the nonce is not consumed
+    add_message(
+        &mut host,
+        &signed_session_transfer(BOB, &alice, 3, &TOKEN, &carol, 101),
+    );
+    run_level(&mut host);
+    assert_eq!(200, balance(&host, &carol, &TOKEN));
+    assert_eq!(2, nonce(&host, &alice));
+
+    add_message(
+        &mut host,
+        &signed_session_transfer(BOB, &alice, 3, &TOKEN, &carol, 100),
+    );
+    add_message(
+        &mut host,
+        &signed_session_transfer(BOB, &alice, 4, &TOKEN, &carol, 1),
+    );
+    run_level(&mut host);
+    assert_eq!(300, balance(&host, &carol, &TOKEN));
+    assert_eq!(3, nonce(&host, &alice));
+
+    // Alice still signs for herself
+    add_message(&mut host, &signed_transfer(ALICE, 4, &TOKEN, &carol, 50));
+    run_level(&mut host);
+    assert_eq!(350, balance(&host, &carol, &TOKEN));
+}
+
+#[test]
+fn test_session_key_expires() {
+    let mut host = MockHost::default();
+    let (alice, bob, carol) = (address(ALICE), address(BOB), address(CAROL));
+    add_deposit(&mut host, &alice, &TOKEN, 1000);
+    let level = run_level(&mut host);
+
+    add_message(
+        &mut host,
+        &signed_authorize_session_key(ALICE, 1, &bob, &TOKEN, 300, level + 5),
+    );
+    run_until(&mut host, level + 5);
+    add_message(
+        &mut host,
+        &signed_session_transfer(BOB, &alice, 2, &TOKEN, &carol, 100),
+    );
+    run_level(&mut host);
+
+    assert_eq!(1000, balance(&host, &alice, &TOKEN));
+    assert_eq!(1, nonce(&host, &alice));
+}
+
+#[test]
+fn test_unauthorized_key_is_rejected() {
+    let mut host = MockHost::default();
+    let (alice, bob, carol) = (address(ALICE), address(BOB), address(CAROL));
+    add_deposit(&mut host, &alice, &TOKEN, 1000);
+    add_deposit(&mut host, &alice, &OTHER_TOKEN, 1000);
+    let level = run_level(&mut host);
+
+    add_message(
+        &mut host,
+        &signed_authorize_session_key(ALICE, 1, &bob, &TOKEN, 300, level + 20),
+    );
+    // Carol has no key, bob has no key for the other token
+    add_message(
+        &mut host,
+        &signed_session_transfer(CAROL, &alice, 2, &TOKEN, &carol, 100),
+    );
+    add_message(
+        &mut host,
+        &signed_session_transfer(BOB, &alice, 2, &OTHER_TOKEN, &carol, 100),
+    );
+    run_level(&mut host);
+
+    assert_eq!(1000, balance(&host, &alice, &TOKEN));
+    assert_eq!(1000, balance(&host, &alice, &OTHER_TOKEN));
+    assert_eq!(1, nonce(&host, &alice));
+
+    // Authorizing a limit of 0 revokes the key
+    add_message(
+        &mut host,
+        &signed_authorize_session_key(ALICE, 2, &bob, &TOKEN, 0, level + 20),
+    );
+    add_message(
+        &mut host,
+        &signed_session_transfer(BOB, &alice, 3, &TOKEN, &carol, 100),
+    );
+    run_level(&mut host);
+    assert_eq!(1000, balance(&host, &alice, &TOKEN));
+}
@@ -0,0 +1,134 @@
+mod common;
+
+use common::*;
+use kernel::core::message::{Message, SponsoredMessage};
+use kernel::core::token::Token;
+use kernel_client::sponsor;
+use tezos_smart_rollup_mock::MockHost;
+
+#[test]
+fn test_sponsored_transfer_is_applied() {
+    let mut host = MockHost::default();
+    let (alice, bob, carol) = (address(ALICE), address(BOB), address(CAROL));
+    add_deposit(&mut host, &alice, &TOKEN, 1000);
+    run_level(&mut host);
+
+    // Carol relays the transfer of alice, without holding any token
+    let transfer = signed_transfer(ALICE, 1, &TOKEN, &bob, 400);
+    add_message(&mut host, &sponsored(CAROL, 1, transfer));
+    run_level(&mut host);
+
+    assert_eq!(600, balance(&host, &alice, &TOKEN));
+    assert_eq!(400, balance(&host, &bob, &TOKEN));
+    assert_eq!(1, nonce(&host, &alice));
+    assert_eq!(1, nonce(&host, &carol));
+
+    // Each nonce is tracked independently
+    let transfer = signed_transfer(ALICE, 2, &TOKEN, &bob, 100);
+    add_message(&mut host, &sponsored(CAROL, 2, transfer));
+    let transfer = signed_transfer(ALICE, 3, &TOKEN, &bob, 100);
+    add_message(&mut host, &sponsored(BOB, 1, transfer));
+    run_level(&mut host);
+
+    assert_eq!(600, balance(&host, &bob, &TOKEN));
+    assert_eq!(3, nonce(&host, &alice));
+    assert_eq!(2, nonce(&host, &carol));
+    assert_eq!(1, nonce(&host, &bob));
+}
+
+#[test]
+fn test_sponsored_message_cannot_be_replayed() {
+    let mut host = MockHost::default();
+    let (alice, bob) = (address(ALICE), address(BOB));
+    add_deposit(&mut host, &alice, &TOKEN, 1000);
+    run_level(&mut host);
+
+    let message = sponsored(CAROL, 1, signed_transfer(ALICE, 1, &TOKEN, &bob, 400));
+    add_message(&mut host, &message);
+    add_message(&mut host, &message);
+    run_level(&mut host);
+
+    assert_eq!(400, balance(&host, &bob, &TOKEN));
+}
+
+#[test]
+fn test_sponsor_must_commit_to_the_message() {
+    let mut host = MockHost::default();
+    let (alice, bob, carol) = (address(ALICE), address(BOB), address(CAROL));
+    add_deposit(&mut host, &alice, &TOKEN, 1000);
+    run_level(&mut host);
+
+    // The relayer signed another transfer than the one it wraps
+    let message = sponsored(CAROL, 1, signed_transfer(ALICE, 1, &TOKEN, &bob, 10));
+    let message = match message {
+        Message::Sponsored(mut sponsored) => {
+            sponsored.message = signed_transfer(ALICE, 1, &TOKEN, &bob, 900);
+            Message::Sponsored(sponsored)
+        }
+        _ => unreachable!(),
+    };
+    add_message(&mut host, &message);
+
+    // Sponsored messages can't be nested, even if the relayer commits to the wrapped transfer
+    let transfer = signed_transfer(ALICE, 1, &TOKEN, &bob, 20);
+    let inner = sponsor(1, &transfer, Token::Bytes(TOKEN.to_vec()), 0).unwrap();
+    let message = Message::Sponsored(Box::new(SponsoredMessage {
+        message: sponsored(BOB, 1, transfer),
+        sponsor: signer(CAROL).sign(inner, TIMESTAMP).unwrap(),
+    }));
+    add_message(&mut host, &message);
+    run_level(&mut host);
+
+    assert_eq!(1000, balance(&host, &alice, &TOKEN));
+    assert_eq!(0, nonce(&host, &alice));
+    // A relayer which does not commit to the message does not consume its nonce
+    assert_eq!(0, nonce(&host, &carol));
+}
+
+#[test]
+fn test_relayer_pays_the_fee_to_the_admin() {
+    let mut host = MockHost::default();
+    let (alice, bob, carol) = (address(ALICE), address(BOB), address(CAROL));
+    set_admin(&mut host, BOB);
+    add_deposit(&mut host, &carol, &TOKEN, 100);
+    run_level(&mut host);
+
+    let transfer = signed_transfer(ALICE, 1, &TOKEN, &carol, 0);
+    add_message(
+        &mut host,
+        &sponsored_with_fee(CAROL, 1, transfer, &TOKEN, 30),
+    );
+    run_level(&mut host);
+
+    assert_eq!(70, balance(&host, &carol, &TOKEN));
+    assert_eq!(30, balance(&host, &bob, &TOKEN));
+    assert_eq!(1, nonce(&host, &alice));
+    assert_eq!(1, nonce(&host, &carol));
+
+    // A relayer which can't pay the fee does not consume any nonce
+    let transfer = signed_transfer(ALICE, 2, &TOKEN, &carol, 0);
+    add_message(
+        &mut host,
+        &sponsored_with_fee(CAROL, 2, transfer, &TOKEN, 80),
+    );
+    run_level(&mut host);
+
+    assert_eq!(70, balance(&host, &carol, &TOKEN));
+    assert_eq!(1, nonce(&host, &alice));
+    assert_eq!(1, nonce(&host, &carol));
+
+    // The fee is part of what the relayer signed
+    let transfer = signed_transfer(ALICE, 2, &TOKEN, &carol, 0);
+    let message = match sponsored_with_fee(CAROL, 2, transfer, &TOKEN, 10) {
+        Message::Sponsored(mut sponsored) => {
+            sponsored.sponsor.inner.content.fee_amount = 1;
+            Message::Sponsored(sponsored)
+        }
+        _ => unreachable!(),
+    };
+    add_message(&mut host, &message);
+    run_level(&mut host);
+
+    assert_eq!(70, balance(&host, &carol, &TOKEN));
+    assert_eq!(1, nonce(&host, &carol));
+}
@@ -0,0 +1,112 @@
+mod common;
+
+use common::*;
+use kernel::core::message::OpenStreamContent;
+use kernel::core::public_key_hash::PublicKeyHash;
+use kernel::core::token::Token;
+use tezos_smart_rollup_mock::MockHost;
+
+fn stream(recipient: &str, rate_per_level: u128, deposit: u128) -> OpenStreamContent {
+    OpenStreamContent {
+        recipient: PublicKeyHash::from_b58(recipient).unwrap(),
+        token: Token::Bytes(TOKEN.to_vec()),
+        rate_per_level,
+        deposit,
+    }
+}
+
+/// Alice opens a stream for bob, returns the level it is opened at and its identifier
+fn setup(host: &mut MockHost, rate_per_level: u128, deposit: u128) -> (u32, String) {
+    let alice = address(ALICE);
+    add_deposit(host, &alice, &TOKEN, 1000);
+    run_level(host);
+
+    let (open, id) = signed_open_stream(ALICE, 1, stream(&address(BOB), rate_per_level, deposit));
+    add_message(host, &open);
+    (run_level(host), id)
+}
+
+#[test]
+fn test_tokens_accrue_every_level() {
+    let mut host = MockHost::default();
+    let (alice, bob) = (address(ALICE), address(BOB));
+    let (start, id) = setup(&mut host, 10, 500);
+    assert_eq!(500, balance(&host, &alice, &TOKEN));
+
+    // Anybody can withdraw, the tokens go to the recipient
+    run_until(&mut host, start + 20);
+    add_message(&mut host, &signed_withdraw_stream(CAROL, 1, &id));
+    run_level(&mut host);
+    assert_eq!(200, balance(&host, &bob, &TOKEN));
+    assert_eq!(0, balance(&host, &address(CAROL), &TOKEN));
+
+    // Nothing accrues within a level
+    add_message(&mut host, &signed_withdraw_stream(BOB, 1, &id));
+    add_message(&mut host, &signed_withdraw_stream(BOB, 2, &id));
+    run_level(&mut host);
+    assert_eq!(210, balance(&host, &bob, &TOKEN));
+
+    // Cancelling pays what is accrued and refunds the rest
+    run_until(&mut host, start + 30);
+    add_message(&mut host, &signed_cancel_stream(ALICE, 2, &id));
+    run_level(&mut host);
+    assert_eq!(300, balance(&host, &bob, &TOKEN));
+    assert_eq!(700, balance(&host, &alice, &TOKEN));
+
+    add_message(&mut host, &signed_withdraw_stream(BOB, 3, &id));
+    run_level(&mut host);
+    assert_eq!(300, balance(&host, &bob, &TOKEN));
+}
+
+#[test]
+fn test_accrual_stops_at_the_deposit() {
+    let mut host = MockHost::default();
+    let bob = address(BOB);
+    let (start, id) = setup(&mut host, 100, 300);
+
+    run_until(&mut host, start + 10);
+    add_message(&mut host, &signed_withdraw_stream(BOB, 1, &id));
+    run_level(&mut host);
+    assert_eq!(300, balance(&host, &bob, &TOKEN));
+
+    // The stream is closed once its deposit is withdrawn
+    add_message(&mut host, &signed_cancel_stream(BOB, 2, &id));
+    run_level(&mut host);
+    assert_eq!(300, balance(&host, &bob, &TOKEN));
+    assert_eq!(700, balance(&host, &address(ALICE), &TOKEN));
+}
+
+#[test]
+fn test_only_the_parties_can_cancel() {
+    let mut host = MockHost::default();
+    let (alice, bob) = (address(ALICE), address(BOB));
+    let (start, id) = setup(&mut host, 10, 500);
+
+    run_until(&mut host, start + 5);
+    add_message(&mut host, &signed_cancel_stream(CAROL, 1, &id));
+    run_level(&mut host);
+    assert_eq!(0, balance(&host, &bob, &TOKEN));
+    assert_eq!(500, balance(&host, &alice, &TOKEN));
+
+    add_message(&mut host, &signed_cancel_stream(BOB, 1, &id));
+    run_level(&mut host);
+    assert_eq!(60, balance(&host, &bob, &TOKEN));
+    assert_eq!(940, balance(&host, &alice, &TOKEN));
+}
+
+#[test]
+fn test_invalid_stream_is_rejected() {
+    let mut host = MockHost::default();
+    let (alice, bob) = (address(ALICE), address(BOB));
+    add_deposit(&mut host, &alice, &TOKEN, 1000);
+    run_level(&mut host);
+
+    let (open, _) = signed_open_stream(ALICE, 1, stream(&bob, 0, 100));
+    add_message(&mut host, &open);
+    let (open, _) = signed_open_stream(ALICE, 2, stream(&bob, 10, 1001));
+    add_message(&mut host, &open);
+    run_level(&mut host);
+
+    assert_eq!(2, nonce(&host, &alice));
+    assert_eq!(1000, balance(&host, &alice, &TOKEN));
+}
@@ -0,0 +1,167 @@
+mod common;
+
+use common::*;
+use kernel::core::message::{Message, SwapMessage};
+use tezos_smart_rollup_mock::MockHost;
+
+const OTHER_TOKEN: [u8; 2] = [0xab, 0xcd];
+const EXPIRES_LEVEL: u32 = 100;
+
+fn setup() -> MockHost {
+    let mut host = MockHost::default();
+    add_deposit(&mut host, &address(ALICE), &TOKEN, 1000);
+    add_deposit(&mut host, &address(BOB), &OTHER_TOKEN, 50);
+    run_level(&mut host);
+    host
+}
+
+fn swap_message(first: (&str, u64), second: (&str, u64), first_amount: u128) -> Message {
+    expiring_swap_message(first, second, first_amount, EXPIRES_LEVEL)
+}
+
+fn expiring_swap_message(
+    first: (&str, u64),
+    second: (&str, u64),
+    first_amount: u128,
+    expires_level: u32,
+) -> Message {
+    let (alice, bob) = (address(ALICE), address(BOB));
+    let content = || {
+        swap_content(
+            &alice,
+            &TOKEN,
+            first_amount,
+            &bob,
+            &OTHER_TOKEN,
+            50,
+            expires_level,
+        )
+    };
+    Message::Swap(Box::new(SwapMessage {
+        first: signed_swap_leg(first.0, first.1, content()),
+        second: signed_swap_leg(second.0, second.1, content()),
+    }))
+}
+
+#[test]
+fn test_swap_applies_both_legs() {
+    let mut host = setup();
+    let (alice, bob) = (address(ALICE), address(BOB));
+
+    add_message(&mut host, &swap_message((ALICE, 1), (BOB, 1), 600));
+    run_level(&mut host);
+
+    assert_eq!(400, balance(&host, &alice, &TOKEN));
+    assert_eq!(50, balance(&host, &alice, &OTHER_TOKEN));
+    assert_eq!(600, balance(&host, &bob, &TOKEN));
+    assert_eq!(0, balance(&host, &bob, &OTHER_TOKEN));
+    assert_eq!(1, nonce(&host, &alice));
+    assert_eq!(1, nonce(&host, &bob));
+}
+
+#[test]
+fn test_swap_with_one_leg_above_balance_applies_none() {
+    let mut host = setup();
+    let (alice, bob) = (address(ALICE), address(BOB));
+
+    add_message(&mut host, &swap_message((ALICE, 1), (BOB, 1), 1001));
+    run_level(&mut host);
+
+    assert_eq!(1000, balance(&host, &alice, &TOKEN));
+    assert_eq!(50, balance(&host, &bob, &OTHER_TOKEN));
+    // As for transfers, the signed nonces are consumed
+    assert_eq!(1, nonce(&host, &alice));
+    assert_eq!(1, nonce(&host, &bob));
+}
+
+#[test]
+fn test_swap_with_invalid_nonce_consumes_none() {
+    let mut host = setup();
+    let (alice, bob) = (address(ALICE), address(BOB));
+
+    add_message(&mut host, &swap_message((ALICE, 1), (BOB, 2), 600));
+    run_level(&mut host);
+
+    assert_eq!(1000, balance(&host, &alice, &TOKEN));
+    assert_eq!(0, nonce(&host, &alice));
+    assert_eq!(0, nonce(&host, &bob));
+}
+
+#[test]
+fn test_swap_signed_by_a_third_party_is_rejected() {
+    let mut host = setup();
+    let alice = address(ALICE);
+
+    add_message(&mut host, &swap_message((ALICE, 1), (CAROL, 1), 600));
+    run_level(&mut host);
+
+    assert_eq!(1000, balance(&host, &alice, &TOKEN));
+    assert_eq!(0, nonce(&host, &alice));
+}
+
+#[test]
+fn test_swap_with_different_legs_is_rejected() {
+    let mut host = setup();
+    let (alice, bob) = (address(ALICE), address(BOB));
+
+    let message = Message::Swap(Box::new(SwapMessage {
+        first: signed_swap_leg(
+            ALICE,
+            1,
+            swap_content(&alice, &TOKEN, 600, &bob, &OTHER_TOKEN, 50, EXPIRES_LEVEL),
+        ),
+        second: signed_swap_leg(
+            BOB,
+            1,
+            swap_content(&alice, &TOKEN, 900, &bob, &OTHER_TOKEN, 50, EXPIRES_LEVEL),
+        ),
+    }));
+    add_message(&mut host, &message);
+    run_level(&mut host);
+
+    assert_eq!(1000, balance(&host, &alice, &TOKEN));
+    assert_eq!(50, balance(&host, &bob, &OTHER_TOKEN));
+}
+
+#[test]
+fn test_expired_swap_is_rejected() {
+    let mut host = setup();
+    let (alice, bob) = (address(ALICE), address(BOB));
+
+    // setup ran the level 0, the swap is applied at the level 1
+    add_message(
+        &mut host,
+        &expiring_swap_message((ALICE, 1), (BOB, 1), 600, 1),
+    );
+    run_level(&mut host);
+
+    assert_eq!(1000, balance(&host, &alice, &TOKEN));
+    assert_eq!(50, balance(&host, &bob, &OTHER_TOKEN));
+    assert_eq!(0, nonce(&host, &alice));
+    assert_eq!(0, nonce(&host, &bob));
+
+    add_message(
+        &mut host,
+        &expiring_swap_message((ALICE, 1), (BOB, 1), 600, 3),
+    );
+    run_level(&mut host);
+
+    assert_eq!(400, balance(&host, &alice, &TOKEN));
+    assert_eq!(600, balance(&host, &bob, &TOKEN));
+}
+
+#[test]
+fn test_swap_leg_is_cancelled_by_consuming_its_nonce() {
+    let mut host = setup();
+    let (alice, bob) = (address(ALICE), address(BOB));
+
+    // Alice signed her leg with the nonce 1, and uses it before Bob posts the swap
+    let swap = swap_message((ALICE, 1), (BOB, 1), 600);
+    add_message(&mut host, &signed_transfer(ALICE, 1, &TOKEN, &alice, 1));
+    add_message(&mut host, &swap);
+    run_level(&mut host);
+
+    assert_eq!(1000, balance(&host, &alice, &TOKEN));
+    assert_eq!(50, balance(&host, &bob, &OTHER_TOKEN));
+    assert_eq!(0, nonce(&host, &bob));
+}
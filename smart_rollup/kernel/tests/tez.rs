@@ -0,0 +1,71 @@
+mod common;
+
+use common::*;
+use kernel::constants::L1_BRIDGE_CONTRACT_ADDRESS;
+use kernel::core::message::Message;
+use kernel::core::public_key_hash::PublicKeyHash;
+use kernel::core::token::{TicketContent, Token};
+use kernel_client::{transfer, withdraw};
+use tezos_smart_rollup_mock::MockHost;
+
+fn tez_transfer(secret_key: &str, nonce: u64, destination: &str, amount: u128) -> Message {
+    let destination = PublicKeyHash::from_b58(destination).unwrap();
+    let inner = transfer(nonce, Token::Tez, destination, amount);
+    Message::Transfer(signer(secret_key).sign(inner, TIMESTAMP).unwrap())
+}
+
+fn tez_withdraw(secret_key: &str, nonce: u64, destination: &str, amount: u128) -> Message {
+    let destination = PublicKeyHash::from_b58(destination).unwrap();
+    let inner = withdraw(nonce, Token::Tez, destination, amount);
+    Message::Withdraw(signer(secret_key).sign(inner, TIMESTAMP).unwrap())
+}
+
+#[test]
+fn test_tez_deposit_and_transfer() {
+    let mut host = MockHost::default();
+    let (alice, bob) = (address(ALICE), address(BOB));
+
+    add_tez_deposit(&mut host, &alice, 1_000_000);
+    run_level(&mut host);
+    assert_eq!(1_000_000, tez_balance(&host, &alice));
+
+    add_message(&mut host, &tez_transfer(ALICE, 1, &bob, 400_000));
+    run_level(&mut host);
+
+    assert_eq!(600_000, tez_balance(&host, &alice));
+    assert_eq!(400_000, tez_balance(&host, &bob));
+}
+
+#[test]
+fn test_unit_tickets_of_other_ticketers_are_not_tez() {
+    let mut host = MockHost::default();
+    let alice = address(ALICE);
+
+    add_tez_deposit_from(&mut host, L1_BRIDGE_CONTRACT_ADDRESS, &alice, 1000);
+    run_level(&mut host);
+
+    let ticket = Token::Ticket {
+        ticketer: L1_BRIDGE_CONTRACT_ADDRESS.to_string(),
+        content: TicketContent::Unit,
+    };
+    assert_eq!(0, tez_balance(&host, &alice));
+    assert_eq!(1000, token_balance(&host, &alice, &ticket));
+}
+
+#[test]
+fn test_tez_withdraw_writes_the_outbox() {
+    let mut host = MockHost::default();
+    let alice = address(ALICE);
+
+    add_tez_deposit(&mut host, &alice, 1000);
+    add_deposit(&mut host, &alice, &TOKEN, 1000);
+    run_level(&mut host);
+
+    add_message(&mut host, &tez_withdraw(ALICE, 1, &alice, 300));
+    add_message(&mut host, &tez_withdraw(ALICE, 2, &alice, 3000));
+    let level = run_level(&mut host);
+
+    assert_eq!(700, tez_balance(&host, &alice));
+    assert_eq!(1000, balance(&host, &alice, &TOKEN));
+    assert_eq!(1, host.outbox_at(level).len());
+}
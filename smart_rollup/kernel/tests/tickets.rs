@@ -0,0 +1,250 @@
+mod common;
+
+use common::*;
+use kernel::core::message::{AdminContent, Message};
+use kernel::core::public_key_hash::PublicKeyHash;
+use kernel::core::token::{TicketContent, Token};
+use kernel_client::{transfer, withdraw};
+use tezos_smart_rollup::michelson::{
+    MichelsonBytes, MichelsonNat, MichelsonOption, MichelsonPair, MichelsonString, Zarith,
+};
+use tezos_smart_rollup_mock::MockHost;
+
+const TICKETER: &str = "KT1PWx2mnDueood7fEmfbBDKx1D9BAnnXitn";
+
+fn ticket(content: TicketContent) -> Token {
+    Token::Ticket {
+        ticketer: TICKETER.to_string(),
+        content,
+    }
+}
+
+/// Makes carol the admin, and allows the tickets of TICKETER with her nonce 1
+fn allow_ticketer(host: &mut MockHost) {
+    set_admin(host, CAROL);
+    add_message(
+        host,
+        &signed_admin(CAROL, 1, AdminContent::AllowTicketer(TICKETER.to_string())),
+    );
+    run_level(host);
+}
+
+fn nat(nat: u64) -> MichelsonNat {
+    MichelsonNat::new(Zarith(nat.into())).unwrap()
+}
+
+fn ticket_transfer(
+    secret_key: &str,
+    nonce: u64,
+    token: Token,
+    destination: &str,
+    amount: u128,
+) -> Message {
+    let destination = PublicKeyHash::from_b58(destination).unwrap();
+    let inner = transfer(nonce, token, destination, amount);
+    Message::Transfer(signer(secret_key).sign(inner, TIMESTAMP).unwrap())
+}
+
+fn ticket_withdraw(
+    secret_key: &str,
+    nonce: u64,
+    token: Token,
+    destination: &str,
+    amount: u128,
+) -> Message {
+    let destination = PublicKeyHash::from_b58(destination).unwrap();
+    let inner = withdraw(nonce, token, destination, amount);
+    Message::Withdraw(signer(secret_key).sign(inner, TIMESTAMP).unwrap())
+}
+
+#[test]
+fn test_deposit_of_structured_tickets() {
+    let mut host = MockHost::default();
+    allow_ticketer(&mut host);
+    let alice = address(ALICE);
+
+    let metadata = MichelsonOption(Some(MichelsonBytes(vec![0x12])));
+    add_ticket_deposit(
+        &mut host,
+        TICKETER,
+        &alice,
+        MichelsonString("gold".to_string()),
+        100,
+    );
+    add_ticket_deposit(&mut host, TICKETER, &alice, nat(7), 200);
+    add_ticket_deposit(
+        &mut host,
+        TICKETER,
+        &alice,
+        MichelsonPair(nat(7), metadata),
+        300,
+    );
+    add_ticket_deposit(
+        &mut host,
+        TICKETER,
+        &alice,
+        MichelsonPair(nat(7), MichelsonOption::<MichelsonBytes>(None)),
+        400,
+    );
+    run_level(&mut host);
+
+    let gold = ticket(TicketContent::String("gold".to_string()));
+    assert_eq!(100, token_balance(&host, &alice, &gold));
+    let id = ticket(TicketContent::Nat(7));
+    assert_eq!(200, token_balance(&host, &alice, &id));
+    let with_metadata = ticket(TicketContent::Pair(7, Some(vec![0x12])));
+    assert_eq!(300, token_balance(&host, &alice, &with_metadata));
+    let without_metadata = ticket(TicketContent::Pair(7, None));
+    assert_eq!(400, token_balance(&host, &alice, &without_metadata));
+}
+
+#[test]
+fn test_ticket_transfer() {
+    let mut host = MockHost::default();
+    allow_ticketer(&mut host);
+    let alice = address(ALICE);
+    let bob = address(BOB);
+    let token = ticket(TicketContent::Nat(7));
+
+    add_ticket_deposit(&mut host, TICKETER, &alice, nat(7), 1000);
+    add_ticket_deposit(&mut host, TICKETER, &alice, nat(8), 1000);
+    run_level(&mut host);
+
+    add_message(
+        &mut host,
+        &ticket_transfer(ALICE, 1, token.clone(), &bob, 400),
+    );
+    run_level(&mut host);
+
+    assert_eq!(600, token_balance(&host, &alice, &token));
+    assert_eq!(400, token_balance(&host, &bob, &token));
+    let other = ticket(TicketContent::Nat(8));
+    assert_eq!(1000, token_balance(&host, &alice, &other));
+    assert_eq!(0, token_balance(&host, &bob, &other));
+}
+
+#[test]
+fn test_ticket_withdraw_writes_the_outbox() {
+    let mut host = MockHost::default();
+    allow_ticketer(&mut host);
+    let alice = address(ALICE);
+    let token = ticket(TicketContent::Pair(7, Some(vec![0x12])));
+
+    let metadata = MichelsonOption(Some(MichelsonBytes(vec![0x12])));
+    add_ticket_deposit(
+        &mut host,
+        TICKETER,
+        &alice,
+        MichelsonPair(nat(7), metadata),
+        1000,
+    );
+    run_level(&mut host);
+
+    add_message(
+        &mut host,
+        &ticket_withdraw(ALICE, 1, token.clone(), &alice, 300),
+    );
+    add_message(
+        &mut host,
+        &ticket_withdraw(ALICE, 2, token.clone(), &alice, 3000),
+    );
+    let level = run_level(&mut host);
+
+    assert_eq!(700, token_balance(&host, &alice, &token));
+    assert_eq!(1, host.outbox_at(level).len());
+}
+
+#[test]
+fn test_withdraw_of_a_revoked_ticketer_is_rejected() {
+    let mut host = MockHost::default();
+    allow_ticketer(&mut host);
+    let alice = address(ALICE);
+    let token = ticket(TicketContent::Nat(7));
+
+    add_ticket_deposit(&mut host, TICKETER, &alice, nat(7), 1000);
+    add_message(
+        &mut host,
+        &signed_admin(CAROL, 2, AdminContent::RevokeTicketer(TICKETER.to_string())),
+    );
+    run_level(&mut host);
+
+    // The ticket is not burnt
+    add_message(
+        &mut host,
+        &ticket_withdraw(ALICE, 1, token.clone(), &alice, 300),
+    );
+    let level = run_level(&mut host);
+    assert_eq!(1000, token_balance(&host, &alice, &token));
+    assert!(host.outbox_at(level).is_empty());
+
+    // Deposits are not credited either, they are returned
+    add_ticket_deposit(&mut host, TICKETER, &alice, nat(7), 500);
+    let level = run_level(&mut host);
+    assert_eq!(1000, token_balance(&host, &alice, &token));
+    assert_eq!(1, host.outbox_at(level).len());
+
+    add_message(
+        &mut host,
+        &signed_admin(CAROL, 3, AdminContent::AllowTicketer(TICKETER.to_string())),
+    );
+    add_message(
+        &mut host,
+        &ticket_withdraw(ALICE, 2, token.clone(), &alice, 300),
+    );
+    let level = run_level(&mut host);
+    assert_eq!(700, token_balance(&host, &alice, &token));
+    assert_eq!(1, host.outbox_at(level).len());
+}
+
+#[test]
+fn test_only_the_admin_allows_a_ticketer() {
+    let mut host = MockHost::default();
+    let alice = address(ALICE);
+    set_admin(&mut host, CAROL);
+
+    add_message(
+        &mut host,
+        &signed_admin(ALICE, 1, AdminContent::AllowTicketer(TICKETER.to_string())),
+    );
+    add_ticket_deposit(&mut host, TICKETER, &alice, nat(7), 1000);
+    run_level(&mut host);
+
+    assert_eq!(
+        0,
+        token_balance(&host, &alice, &ticket(TicketContent::Nat(7)))
+    );
+}
+
+#[test]
+fn test_ticket_with_long_metadata() {
+    let mut host = MockHost::default();
+    allow_ticketer(&mut host);
+    let (alice, bob) = (address(ALICE), address(BOB));
+
+    // The hex of the metadata alone does not fit in a storage path
+    let metadata = vec![0xab; 200];
+    let token = ticket(TicketContent::Pair(7, Some(metadata.clone())));
+    add_ticket_deposit(
+        &mut host,
+        TICKETER,
+        &alice,
+        MichelsonPair(nat(7), MichelsonOption(Some(MichelsonBytes(metadata)))),
+        1000,
+    );
+    run_level(&mut host);
+    assert_eq!(1000, token_balance(&host, &alice, &token));
+
+    add_message(
+        &mut host,
+        &ticket_transfer(ALICE, 1, token.clone(), &bob, 400),
+    );
+    add_message(
+        &mut host,
+        &ticket_withdraw(ALICE, 2, token.clone(), &alice, 100),
+    );
+    let level = run_level(&mut host);
+
+    assert_eq!(500, token_balance(&host, &alice, &token));
+    assert_eq!(400, token_balance(&host, &bob, &token));
+    assert_eq!(1, host.outbox_at(level).len());
+}
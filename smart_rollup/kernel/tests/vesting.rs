@@ -0,0 +1,109 @@
+mod common;
+
+use common::*;
+use kernel::core::message::VestContent;
+use kernel::core::public_key_hash::PublicKeyHash;
+use kernel::core::token::Token;
+use tezos_smart_rollup_mock::MockHost;
+
+fn schedule(beneficiary: &str, total: u128, start: u32, cliff: u32, end: u32) -> VestContent {
+    VestContent {
+        beneficiary: PublicKeyHash::from_b58(beneficiary).unwrap(),
+        token: Token::Bytes(TOKEN.to_vec()),
+        total,
+        start_level: start,
+        cliff_level: cliff,
+        end_level: end,
+    }
+}
+
+#[test]
+fn test_tokens_are_released_linearly_after_the_cliff() {
+    let mut host = MockHost::default();
+    let (alice, bob) = (address(ALICE), address(BOB));
+
+    add_deposit(&mut host, &alice, &TOKEN, 1000);
+    let level = run_level(&mut host);
+
+    let content = schedule(&bob, 600, level, level + 10, level + 100);
+    let (vest, id) = signed_vest(ALICE, 1, content);
+    add_message(&mut host, &vest);
+    run_level(&mut host);
+    assert_eq!(400, balance(&host, &alice, &TOKEN));
+
+    // Nothing is vested before the cliff
+    add_message(&mut host, &signed_claim_vested(BOB, 1, &id));
+    run_level(&mut host);
+    assert_eq!(0, balance(&host, &bob, &TOKEN));
+    assert_eq!(1, nonce(&host, &bob));
+
+    // Anybody can claim, the tokens go to the beneficiary
+    run_until(&mut host, level + 50);
+    add_message(&mut host, &signed_claim_vested(CAROL, 1, &id));
+    run_level(&mut host);
+    assert_eq!(300, balance(&host, &bob, &TOKEN));
+    assert_eq!(0, balance(&host, &address(CAROL), &TOKEN));
+
+    run_until(&mut host, level + 150);
+    add_message(&mut host, &signed_claim_vested(BOB, 2, &id));
+    run_level(&mut host);
+    assert_eq!(600, balance(&host, &bob, &TOKEN));
+
+    // The schedule is removed once everything is claimed
+    assert!(!is_stored(&host, &format!("/vesting/{}", id)));
+    add_message(&mut host, &signed_claim_vested(BOB, 3, &id));
+    run_level(&mut host);
+    assert_eq!(600, balance(&host, &bob, &TOKEN));
+    assert_eq!(400, balance(&host, &alice, &TOKEN));
+}
+
+#[test]
+fn test_schedule_is_stored_until_fully_claimed() {
+    let mut host = MockHost::default();
+    let (alice, bob) = (address(ALICE), address(BOB));
+
+    add_deposit(&mut host, &alice, &TOKEN, 1000);
+    let level = run_level(&mut host);
+
+    let content = schedule(&bob, 600, level, level + 1, level + 10);
+    let (vest, id) = signed_vest(ALICE, 1, content);
+    add_message(&mut host, &vest);
+    run_level(&mut host);
+    let path = format!("/vesting/{}", id);
+    assert!(is_stored(&host, &path));
+
+    run_until(&mut host, level + 5);
+    add_message(&mut host, &signed_claim_vested(BOB, 1, &id));
+    run_level(&mut host);
+    assert_eq!(300, balance(&host, &bob, &TOKEN));
+    assert!(is_stored(&host, &path));
+
+    run_until(&mut host, level + 10);
+    add_message(&mut host, &signed_claim_vested(BOB, 2, &id));
+    run_level(&mut host);
+    assert_eq!(600, balance(&host, &bob, &TOKEN));
+    assert!(!is_stored(&host, &path));
+}
+
+#[test]
+fn test_invalid_schedule_is_rejected() {
+    let mut host = MockHost::default();
+    let (alice, bob) = (address(ALICE), address(BOB));
+
+    add_deposit(&mut host, &alice, &TOKEN, 1000);
+    let level = run_level(&mut host);
+
+    // Cliff after the end
+    let (vest, _) = signed_vest(ALICE, 1, schedule(&bob, 100, level, level + 20, level + 10));
+    add_message(&mut host, &vest);
+    // Empty schedule
+    let (vest, _) = signed_vest(ALICE, 2, schedule(&bob, 100, level, level, level));
+    add_message(&mut host, &vest);
+    // More than the balance
+    let (vest, _) = signed_vest(ALICE, 3, schedule(&bob, 1001, level, level, level + 10));
+    add_message(&mut host, &vest);
+    run_level(&mut host);
+
+    assert_eq!(3, nonce(&host, &alice));
+    assert_eq!(1000, balance(&host, &alice, &TOKEN));
+}